@@ -573,6 +573,8 @@ fn test_with_no_doc_stage0() {
         bless: false,
         compare_mode: None,
         rustfix_coverage: false,
+        error_code_coverage: false,
+        deny_untested_error_codes: false,
         pass: None,
     };
 
@@ -615,6 +617,8 @@ fn test_exclude() {
         bless: false,
         compare_mode: None,
         rustfix_coverage: false,
+        error_code_coverage: false,
+        deny_untested_error_codes: false,
         pass: None,
     };
 
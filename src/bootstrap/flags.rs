@@ -67,6 +67,8 @@ pub enum Subcommand {
         fail_fast: bool,
         doc_tests: DocTests,
         rustfix_coverage: bool,
+        error_code_coverage: bool,
+        deny_untested_error_codes: bool,
     },
     Bench {
         paths: Vec<PathBuf>,
@@ -215,6 +217,19 @@ To learn more about a subcommand, run `./x.py <subcommand> -h`"
                     "enable this to generate a Rustfix coverage file, which is saved in \
                         `/<build_base>/rustfix_missing_coverage.txt`",
                 );
+                opts.optflag(
+                    "",
+                    "error-code-coverage",
+                    "generate a report of the tests emitting each error code, which is saved in \
+                        `/<build_base>/error_code_coverage.json`; all the selected tests are run \
+                        even if they are up to date",
+                );
+                opts.optflag(
+                    "",
+                    "deny-untested-error-codes",
+                    "fail if a documented error code is not emitted by any test, \
+                        implies --error-code-coverage",
+                );
             }
             "bench" => {
                 opts.optmulti("", "test-args", "extra arguments", "ARGS");
@@ -335,6 +350,10 @@ Arguments:
         ./x.py test src/libstd --stage 0 --no-doc
         ./x.py test src/test/ui --bless
         ./x.py test src/test/ui --compare-mode nll
+        ./x.py test src/test/ui/foo.rs#revision
+
+    A single revision of a test declared with `// revisions:` can be selected
+    by appending `#` and the revision name to the path of the test.
 
     Note that `test src/test/* --stage N` does NOT depend on `build src/rustc --stage N`;
     just like `build src/libstd --stage N` it tests the compiler produced by the previous
@@ -416,6 +435,8 @@ Arguments:
                 rustc_args: matches.opt_strs("rustc-args"),
                 fail_fast: !matches.opt_present("no-fail-fast"),
                 rustfix_coverage: matches.opt_present("rustfix-coverage"),
+                error_code_coverage: matches.opt_present("error-code-coverage"),
+                deny_untested_error_codes: matches.opt_present("deny-untested-error-codes"),
                 doc_tests: if matches.opt_present("doc") {
                     DocTests::Only
                 } else if matches.opt_present("no-doc") {
@@ -527,6 +548,20 @@ impl Subcommand {
         }
     }
 
+    pub fn error_code_coverage(&self) -> bool {
+        match *self {
+            Subcommand::Test { error_code_coverage, .. } => error_code_coverage,
+            _ => false,
+        }
+    }
+
+    pub fn deny_untested_error_codes(&self) -> bool {
+        match *self {
+            Subcommand::Test { deny_untested_error_codes, .. } => deny_untested_error_codes,
+            _ => false,
+        }
+    }
+
     pub fn compare_mode(&self) -> Option<&str> {
         match *self {
             Subcommand::Test {
@@ -1214,7 +1214,13 @@ impl Step for Compiletest {
                     Err(_) => p,
                 }
             })
-            .filter(|p| p.starts_with(suite_path) && (p.is_dir() || p.is_file()))
+            .filter(|p| p.starts_with(suite_path) && (p.is_dir() || p.is_file() || {
+                // A single revision of a test can be selected with `path/to/test.rs#revision`.
+                match p.to_str().and_then(|s| s.rfind('#').map(|i| Path::new(&s[..i]))) {
+                    Some(test_path) => test_path.is_file(),
+                    None => false,
+                }
+            }))
             .filter_map(|p| {
                 // Since test suite paths are themselves directories, if we don't
                 // specify a directory or file, we'll get an empty string here
@@ -1356,6 +1362,14 @@ impl Step for Compiletest {
             cmd.arg("--rustfix-coverage");
         }
 
+        if builder.config.cmd.error_code_coverage() {
+            cmd.arg("--error-code-coverage");
+        }
+
+        if builder.config.cmd.deny_untested_error_codes() {
+            cmd.arg("--deny-untested-error-codes");
+        }
+
         builder.ci_env.force_coloring_in_ci(&mut cmd);
 
         builder.info(&format!(
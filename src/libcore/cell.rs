@@ -198,6 +198,11 @@ use crate::mem;
 use crate::ops::{Deref, DerefMut, CoerceUnsized};
 use crate::ptr;
 
+mod lazy;
+
+#[unstable(feature = "once_cell", issue = "0")]
+pub use self::lazy::{Lazy, OnceCell};
+
 /// A mutable memory location.
 ///
 /// # Examples
@@ -1365,6 +1365,35 @@ pub(crate) mod builtin {
         /* compiler built-in */
     }
 
+    /// Attribute macro applied to a function to run it once before the tests of
+    /// the module it is defined in (or of the whole crate, at the crate root).
+    #[unstable(
+        feature = "test_fixtures",
+        issue = "0",
+        reason = "test fixtures are an experimental feature of the test harness"
+    )]
+    #[allow_internal_unstable(test, rustc_attrs)]
+    #[rustc_builtin_macro]
+    #[cfg(not(bootstrap))]
+    pub macro test_setup($item:item) {
+        /* compiler built-in */
+    }
+
+    /// Attribute macro applied to a function to run it once after the tests of
+    /// the module it is defined in (or of the whole crate, at the crate root)
+    /// have completed.
+    #[unstable(
+        feature = "test_fixtures",
+        issue = "0",
+        reason = "test fixtures are an experimental feature of the test harness"
+    )]
+    #[allow_internal_unstable(test, rustc_attrs)]
+    #[rustc_builtin_macro]
+    #[cfg(not(bootstrap))]
+    pub macro test_teardown($item:item) {
+        /* compiler built-in */
+    }
+
     /// An implementation detail of the `#[test]` and `#[bench]` macros.
     #[unstable(
         feature = "custom_test_frameworks",
@@ -67,3 +67,12 @@ pub use crate::{
 pub use crate::macros::builtin::{
     bench, global_allocator, test, test_case, RustcDecodable, RustcEncodable,
 };
+
+#[unstable(
+    feature = "test_fixtures",
+    issue = "0",
+    reason = "test fixtures are an experimental feature of the test harness"
+)]
+#[doc(no_inline)]
+#[cfg(not(bootstrap))]
+pub use crate::macros::builtin::{test_setup, test_teardown};
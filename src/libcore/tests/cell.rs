@@ -363,3 +363,93 @@ fn refcell_replace_borrows() {
     let _b = x.borrow();
     x.replace(1);
 }
+
+#[test]
+fn once_cell() {
+    let c = OnceCell::new();
+    assert!(c.get().is_none());
+    c.get_or_init(|| 92);
+    assert_eq!(c.get(), Some(&92));
+
+    c.get_or_init(|| panic!("Kabom!"));
+    assert_eq!(c.get(), Some(&92));
+}
+
+#[test]
+fn once_cell_get_mut() {
+    let mut c = OnceCell::new();
+    assert!(c.get_mut().is_none());
+    c.set(90).unwrap();
+    *c.get_mut().unwrap() += 2;
+    assert_eq!(c.get_mut(), Some(&mut 92));
+}
+
+#[test]
+fn once_cell_drop() {
+    static mut DROP_CNT: usize = 0;
+    struct Dropper;
+    impl Drop for Dropper {
+        fn drop(&mut self) {
+            unsafe { DROP_CNT += 1 }
+        }
+    }
+
+    let x = OnceCell::new();
+    x.get_or_init(|| Dropper);
+    assert_eq!(unsafe { DROP_CNT }, 0);
+    drop(x);
+    assert_eq!(unsafe { DROP_CNT }, 1);
+}
+
+#[test]
+fn once_cell_get_or_try_init() {
+    let c = OnceCell::new();
+    assert_eq!(c.get_or_try_init(|| Err(())), Err(()));
+    assert!(c.get().is_none());
+    assert_eq!(c.get_or_try_init(|| Ok::<_, ()>(92)), Ok(&92));
+    assert_eq!(c.get(), Some(&92));
+}
+
+#[test]
+#[should_panic(expected = "reentrant init")]
+fn once_cell_reentrant_init() {
+    let x: OnceCell<Box<i32>> = OnceCell::new();
+    x.get_or_init(|| {
+        let _ = x.get_or_init(|| Box::new(92));
+        Box::new(62)
+    });
+}
+
+#[test]
+fn once_cell_take() {
+    let mut c = OnceCell::from(92);
+    assert_eq!(c.take(), Some(92));
+    assert!(c.get().is_none());
+    assert_eq!(c.take(), None);
+    assert_eq!(OnceCell::from("hello").into_inner(), Some("hello"));
+}
+
+#[test]
+fn lazy_new() {
+    let called = Cell::new(0);
+    let x = Lazy::new(|| {
+        called.set(called.get() + 1);
+        92
+    });
+
+    assert_eq!(called.get(), 0);
+
+    let y = *x - 30;
+    assert_eq!(y, 62);
+    assert_eq!(called.get(), 1);
+
+    let y = *x - 30;
+    assert_eq!(y, 62);
+    assert_eq!(called.get(), 1);
+}
+
+#[test]
+fn lazy_default() {
+    let x: Lazy<Vec<i32>> = Lazy::default();
+    assert!(x.is_empty());
+}
@@ -396,6 +396,12 @@ impl Definitions {
         self.node_to_hir_id[node_id]
     }
 
+    /// Returns the `HirId` of the given node, if it was lowered to HIR.
+    #[inline]
+    pub fn opt_node_to_hir_id(&self, node_id: ast::NodeId) -> Option<hir::HirId> {
+        self.node_to_hir_id.get(node_id).cloned().filter(|&hir_id| hir_id != hir::DUMMY_HIR_ID)
+    }
+
     #[inline]
     pub fn def_index_to_hir_id(&self, def_index: DefIndex) -> hir::HirId {
         let node_id = self.def_index_to_node[def_index];
@@ -335,6 +335,11 @@ impl<'hir> Map<'hir> {
         self.definitions.node_to_hir_id(node_id)
     }
 
+    #[inline]
+    pub fn opt_node_to_hir_id(&self, node_id: NodeId) -> Option<HirId> {
+        self.definitions.opt_node_to_hir_id(node_id)
+    }
+
     #[inline]
     pub fn def_index_to_hir_id(&self, def_index: DefIndex) -> HirId {
         self.definitions.def_index_to_hir_id(def_index)
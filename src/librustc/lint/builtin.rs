@@ -466,6 +466,12 @@ declare_lint! {
     };
 }
 
+declare_lint! {
+    pub PANIC_REACHABLE,
+    Allow,
+    "functions from which a panic is reachable"
+}
+
 declare_lint_pass! {
     /// Does nothing as a lint pass, but registers some `Lint`s
     /// that are used by other parts of the compiler.
@@ -527,6 +533,7 @@ declare_lint_pass! {
         MUTABLE_BORROW_RESERVATION_CONFLICT,
         INDIRECT_STRUCTURAL_MATCH,
         SOFT_UNSTABLE,
+        PANIC_REACHABLE,
     ]
 }
 
@@ -526,6 +526,17 @@ impl LintLevelMap {
             self.sets.get_lint_level(lint, *idx, None, session)
         })
     }
+
+    /// Returns whether the level of `lint` is set by the command line or by an
+    /// attribute anywhere in the crate. If it isn't, the lint has its default
+    /// level on every node.
+    pub fn is_level_set_anywhere(&self, lint: &'static Lint) -> bool {
+        let id = LintId::of(lint);
+        self.sets.list.iter().any(|set| match set {
+            LintSet::CommandLine { specs } |
+            LintSet::Node { specs, .. } => specs.contains_key(&id),
+        })
+    }
 }
 
 impl<'a> HashStable<StableHashingContext<'a>> for LintLevelMap {
@@ -1,8 +1,13 @@
+use crate::session::config::CodeSizeFormat;
 use rustc_target::abi::{Align, Size};
-use rustc_data_structures::fx::{FxHashSet};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_serialize::json::as_pretty_json;
 use std::cmp::{self, Ordering};
 use rustc_data_structures::sync::Lock;
 
+/// Incremented whenever the format of the JSON code size report changes.
+const CODE_SIZE_REPORT_VERSION: u32 = 1;
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct VariantInfo {
     pub name: Option<String>,
@@ -45,9 +50,48 @@ pub struct TypeSizeInfo {
     pub variants: Vec<VariantInfo>,
 }
 
+/// The item a symbol was instantiated from, to which `-Z print-code-sizes`
+/// attributes the size of the symbol.
+#[derive(Clone, Debug)]
+pub struct SymbolDefinition {
+    /// The crate defining the item, which is not the local crate for the
+    /// instances of upstream generic items.
+    pub krate: String,
+    /// The innermost module containing the item.
+    pub module: String,
+    /// The path of the item, with its generic parameters, shared by all its
+    /// instances.
+    pub definition: String,
+}
+
+#[derive(RustcEncodable)]
+struct CodeSize {
+    name: String,
+    bytes: u64,
+    /// The number of distinct symbols attributed to this crate, module or
+    /// definition.
+    symbols: usize,
+}
+
+#[derive(RustcEncodable)]
+struct CodeSizeReport {
+    version: u32,
+    total_bytes: u64,
+    /// The size of the symbols which aren't the instance of any item, e.g.,
+    /// anonymous constants.
+    unattributed_bytes: u64,
+    crates: Vec<CodeSize>,
+    modules: Vec<CodeSize>,
+    definitions: Vec<CodeSize>,
+}
+
 #[derive(Default)]
 pub struct CodeStats {
     type_sizes: Lock<FxHashSet<TypeSizeInfo>>,
+    symbol_definitions: Lock<FxHashMap<String, SymbolDefinition>>,
+    /// The size of the sections of each symbol in the object files, summed
+    /// over the object files defining it.
+    symbol_sizes: Lock<FxHashMap<String, u64>>,
 }
 
 impl CodeStats {
@@ -77,6 +121,93 @@ impl CodeStats {
         self.type_sizes.borrow_mut().insert(info);
     }
 
+    pub fn record_symbol_definition(&self, symbol: String, definition: SymbolDefinition) {
+        self.symbol_definitions.borrow_mut().insert(symbol, definition);
+    }
+
+    pub fn record_symbol_size(&self, symbol: String, bytes: u64) {
+        *self.symbol_sizes.borrow_mut().entry(symbol).or_insert(0) += bytes;
+    }
+
+    pub fn print_code_sizes(&self, format: CodeSizeFormat) {
+        let definitions = self.symbol_definitions.borrow();
+        let symbol_sizes = self.symbol_sizes.borrow();
+
+        let mut total_bytes = 0;
+        let mut unattributed_bytes = 0;
+        let mut crates = FxHashMap::default();
+        let mut modules = FxHashMap::default();
+        let mut items = FxHashMap::default();
+        for (symbol, &bytes) in symbol_sizes.iter() {
+            total_bytes += bytes;
+            let definition = match definitions.get(symbol) {
+                Some(definition) => definition,
+                None => {
+                    unattributed_bytes += bytes;
+                    continue;
+                }
+            };
+            for (groups, name) in [
+                (&mut crates, &definition.krate),
+                (&mut modules, &definition.module),
+                (&mut items, &definition.definition),
+            ].iter_mut() {
+                let size = groups.entry(*name).or_insert((0, 0));
+                size.0 += bytes;
+                size.1 += 1;
+            }
+        }
+
+        // Large-to-small, then by name.
+        let sorted = |groups: FxHashMap<&String, (u64, usize)>| {
+            let mut sizes: Vec<_> = groups.into_iter().map(|(name, (bytes, symbols))| {
+                CodeSize { name: name.clone(), bytes, symbols }
+            }).collect();
+            sizes.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+            sizes
+        };
+        let report = CodeSizeReport {
+            version: CODE_SIZE_REPORT_VERSION,
+            total_bytes,
+            unattributed_bytes,
+            crates: sorted(crates),
+            modules: sorted(modules),
+            definitions: sorted(items),
+        };
+
+        match format {
+            CodeSizeFormat::Json => println!("{}", as_pretty_json(&report)),
+            CodeSizeFormat::Table => {
+                // One line per group, in columns, so that the table can be
+                // sorted and filtered with the usual tools.
+                println!("print-code-size {:>10} {:>6} {:>8}  {:<10}  {}",
+                         "bytes", "share", "symbols", "kind", "name");
+                let share = |bytes: u64| {
+                    if total_bytes == 0 {
+                        0.0
+                    } else {
+                        bytes as f64 * 100.0 / total_bytes as f64
+                    }
+                };
+                for &(kind, ref sizes) in &[
+                    ("crate", &report.crates),
+                    ("module", &report.modules),
+                    ("definition", &report.definitions),
+                ] {
+                    for size in sizes.iter() {
+                        println!("print-code-size {:>10} {:>5.1}% {:>8}  {:<10}  {}",
+                                 size.bytes, share(size.bytes), size.symbols, kind, size.name);
+                    }
+                }
+                println!("print-code-size {:>10} {:>5.1}% {:>8}  {:<10}  {}",
+                         unattributed_bytes, share(unattributed_bytes), "-", "other",
+                         "<unattributed>");
+                println!("print-code-size {:>10} {:>5.1}% {:>8}  {:<10}  {}",
+                         total_bytes, share(total_bytes), symbol_sizes.len(), "total", "<total>");
+            }
+        }
+    }
+
     pub fn print_type_sizes(&self) {
         let type_sizes = self.type_sizes.borrow();
         let mut sorted: Vec<_> = type_sizes.iter().collect();
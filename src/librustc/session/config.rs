@@ -147,6 +147,19 @@ pub enum SymbolManglingVersion {
 
 impl_stable_hash_via_hash!(SymbolManglingVersion);
 
+/// The format of the report written by `-Z time-report`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TimeReportFormat {
+    Json,
+}
+
+/// The format of the report printed by `-Z print-code-sizes`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CodeSizeFormat {
+    Table,
+    Json,
+}
+
 #[derive(Clone, Copy, PartialEq, Hash)]
 pub enum DebugInfo {
     None,
@@ -160,6 +173,7 @@ pub enum OutputType {
     Assembly,
     LlvmAssembly,
     Mir,
+    MirJson,
     Metadata,
     Object,
     Exe,
@@ -176,6 +190,7 @@ impl OutputType {
             | OutputType::Assembly
             | OutputType::LlvmAssembly
             | OutputType::Mir
+            | OutputType::MirJson
             | OutputType::Object => false,
         }
     }
@@ -186,6 +201,7 @@ impl OutputType {
             OutputType::Assembly => "asm",
             OutputType::LlvmAssembly => "llvm-ir",
             OutputType::Mir => "mir",
+            OutputType::MirJson => "mir-json",
             OutputType::Object => "obj",
             OutputType::Metadata => "metadata",
             OutputType::Exe => "link",
@@ -198,6 +214,7 @@ impl OutputType {
             "asm" => OutputType::Assembly,
             "llvm-ir" => OutputType::LlvmAssembly,
             "mir" => OutputType::Mir,
+            "mir-json" => OutputType::MirJson,
             "llvm-bc" => OutputType::Bitcode,
             "obj" => OutputType::Object,
             "metadata" => OutputType::Metadata,
@@ -209,11 +226,12 @@ impl OutputType {
 
     fn shorthands_display() -> String {
         format!(
-            "`{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`",
+            "`{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`",
             OutputType::Bitcode.shorthand(),
             OutputType::Assembly.shorthand(),
             OutputType::LlvmAssembly.shorthand(),
             OutputType::Mir.shorthand(),
+            OutputType::MirJson.shorthand(),
             OutputType::Object.shorthand(),
             OutputType::Metadata.shorthand(),
             OutputType::Exe.shorthand(),
@@ -227,6 +245,7 @@ impl OutputType {
             OutputType::Assembly => "s",
             OutputType::LlvmAssembly => "ll",
             OutputType::Mir => "mir",
+            OutputType::MirJson => "mir.json",
             OutputType::Object => "o",
             OutputType::Metadata => "rmeta",
             OutputType::DepInfo => "d",
@@ -248,6 +267,8 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// Output in the SARIF format, that's consumed by static analysis tools.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -298,6 +319,7 @@ impl OutputTypes {
             | OutputType::Assembly
             | OutputType::LlvmAssembly
             | OutputType::Mir
+            | OutputType::MirJson
             | OutputType::Object
             | OutputType::Exe => true,
             OutputType::Metadata | OutputType::DepInfo => false,
@@ -471,6 +493,7 @@ pub enum PrintRequest {
     TlsModels,
     TargetSpec,
     NativeStaticLibs,
+    CrateGraph,
 }
 
 #[derive(Copy, Clone)]
@@ -851,12 +874,16 @@ macro_rules! options {
             Some("one of: `disabled`, `trampolines`, or `aliases`");
         pub const parse_symbol_mangling_version: Option<&str> =
             Some("either `legacy` or `v0` (RFC 2603)");
+        pub const parse_time_report: Option<&str> =
+            Some("`json`");
+        pub const parse_code_size_format: Option<&str> =
+            Some("either `table` or `json`");
     }
 
     #[allow(dead_code)]
     mod $mod_set {
         use super::{$struct_name, Passes, Sanitizer, LtoCli, LinkerPluginLto, SwitchWithOptPath,
-            SymbolManglingVersion};
+            SymbolManglingVersion, TimeReportFormat, CodeSizeFormat};
         use rustc_target::spec::{LinkerFlavor, MergeFunctions, PanicStrategy, RelroLevel};
         use std::path::PathBuf;
         use std::str::FromStr;
@@ -1177,6 +1204,23 @@ macro_rules! options {
             };
             true
         }
+
+        fn parse_time_report(slot: &mut Option<TimeReportFormat>, v: Option<&str>) -> bool {
+            *slot = match v {
+                Some("json") => Some(TimeReportFormat::Json),
+                _ => return false,
+            };
+            true
+        }
+
+        fn parse_code_size_format(slot: &mut Option<CodeSizeFormat>, v: Option<&str>) -> bool {
+            *slot = match v {
+                None | Some("table") => Some(CodeSizeFormat::Table),
+                Some("json") => Some(CodeSizeFormat::Json),
+                _ => return false,
+            };
+            true
+        }
     }
 ) }
 
@@ -1286,6 +1330,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "measure time of rustc processes"),
     time_llvm_passes: bool = (false, parse_bool, [UNTRACKED],
         "measure time of each LLVM pass"),
+    time_report: Option<TimeReportFormat> = (None, parse_time_report, [UNTRACKED],
+        "write a report of the pass durations, query counts, peak memory usage and
+        codegen unit sizes next to the output, as `<output>.time-report.json`"),
     input_stats: bool = (false, parse_bool, [UNTRACKED],
         "gather statistics about the input"),
     asm_comments: bool = (false, parse_bool, [TRACKED],
@@ -1384,6 +1431,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "print layout information for each type encountered"),
     print_mono_items: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "print the result of the monomorphization collection pass"),
+    explain_mono_item: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "print the chain of uses which caused the mono items with the given path to be \
+         instantiated"),
     mir_opt_level: usize = (1, parse_uint, [TRACKED],
         "set the MIR optimization level (0-3, default: 1)"),
     mutable_noalias: Option<bool> = (None, parse_opt_bool, [TRACKED],
@@ -1469,6 +1519,7 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         valid types are any of the types for `--pretty`, as well as:
         `expanded`, `expanded,identified`,
         `expanded,hygiene` (with internal representations),
+        `expanded,compilable` (with absolute paths and unique hygienic names),
         `everybody_loops` (all function bodies replaced with `loop {}`),
         `hir` (the HIR), `hir,identified`,
         `hir,typed` (HIR with types for each node),
@@ -1501,6 +1552,16 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "specifies which kinds of events get recorded by the self profiler"),
     emit_stack_sizes: bool = (false, parse_bool, [UNTRACKED],
         "emits a section containing stack size metadata"),
+    dump_call_graph: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write the call graph of the monomorphized functions to `<path>.dot` and `<path>.json`"),
+    print_stack_usage: bool = (false, parse_bool, [UNTRACKED],
+        "print the worst-case stack usage of the entry points of the crate"),
+    print_code_sizes: Option<CodeSizeFormat> = (None, parse_code_size_format, [UNTRACKED],
+        "print the size of the code and data of the crate, by crate, module and generic \
+         definition (table or json)"),
+    verify_reproducible: bool = (false, parse_bool, [UNTRACKED],
+        "compile the crate twice, with different temporary directories, environments and \
+         (in parallel compilers) numbers of threads, and check that the artifacts are identical"),
     plt: Option<bool> = (None, parse_opt_bool, [TRACKED],
           "whether to use the PLT when calling into shared libraries;
           only has effect for PIC code on systems with ELF binaries
@@ -1788,7 +1849,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
             "emit",
             "Comma separated list of types of output for \
              the compiler to emit",
-            "[asm|llvm-bc|llvm-ir|obj|metadata|link|dep-info|mir]",
+            "[asm|llvm-bc|llvm-ir|obj|metadata|link|dep-info|mir|mir-json]",
         ),
         opt::multi_s(
             "",
@@ -1796,7 +1857,8 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
             "Compiler information to print on stdout",
             "[crate-name|file-names|sysroot|cfg|target-list|\
              target-cpus|target-features|relocation-models|\
-             code-models|tls-models|target-spec-json|native-static-libs]",
+             code-models|tls-models|target-spec-json|native-static-libs|\
+             crate-graph]",
         ),
         opt::flagmulti_s("g", "", "Equivalent to -C debuginfo=2"),
         opt::flagmulti_s("O", "", "Equivalent to -C opt-level=2"),
@@ -1864,7 +1926,7 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
             "",
             "error-format",
             "How errors and other messages are produced",
-            "human|json|short",
+            "human|json|short|sarif",
         ),
         opt::multi_s(
             "",
@@ -2000,12 +2062,13 @@ pub fn parse_error_format(
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
+            Some("sarif") => ErrorOutputType::Sarif,
 
             Some(arg) => early_error(
                 ErrorOutputType::HumanReadable(HumanReadableErrorType::Default(color)),
                 &format!(
-                    "argument for `--error-format` must be `human`, `json` or \
-                     `short` (instead was `{}`)",
+                    "argument for `--error-format` must be `human`, `json`, \
+                     `short` or `sarif` (instead was `{}`)",
                     arg
                 ),
             ),
@@ -2082,6 +2145,12 @@ fn check_debug_option_stability(
                 "`--error-format=human-annotate-rs` is unstable",
             );
         }
+        if let ErrorOutputType::Sarif = error_format {
+            early_error(
+                ErrorOutputType::Json { pretty: false, json_rendered },
+                "`--error-format=sarif` is unstable",
+            );
+        }
     }
 }
 
@@ -2265,6 +2334,17 @@ fn collect_print_requests(
                 );
             }
         }
+        "crate-graph" => {
+            if dopts.unstable_options {
+                PrintRequest::CrateGraph
+            } else {
+                early_error(
+                    error_format,
+                    "the `-Z unstable-options` flag must also be passed to \
+                     enable the crate-graph print option",
+                );
+            }
+        }
         req => early_error(error_format, &format!("unknown print request `{}`", req)),
     }));
 
@@ -2639,6 +2719,7 @@ fn parse_pretty(
             ("expanded", _) => PpmSource(PpmExpanded),
             ("expanded,identified", _) => PpmSource(PpmExpandedIdentified),
             ("expanded,hygiene", _) => PpmSource(PpmExpandedHygiene),
+            ("expanded,compilable", true) => PpmSource(PpmExpandedCompilable),
             ("hir", true) => PpmHir(PpmNormal),
             ("hir,identified", true) => PpmHir(PpmIdentified),
             ("hir,typed", true) => PpmHir(PpmTyped),
@@ -2649,8 +2730,9 @@ fn parse_pretty(
                 if extended {
                     early_error(efmt, &format!("argument to `unpretty` must be one of `normal`, \
                                         `expanded`, `identified`, `expanded,identified`, \
-                                        `expanded,hygiene`, `everybody_loops`, \
-                                        `hir`, `hir,identified`, `hir,typed`, `hir-tree`, \
+                                        `expanded,hygiene`, `expanded,compilable`, \
+                                        `everybody_loops`, `hir`, `hir,identified`, \
+                                        `hir,typed`, `hir-tree`, \
                                         `mir` or `mir-cfg`; got {}",
                                         name));
                 } else {
@@ -2779,6 +2861,7 @@ pub enum PpSourceMode {
     PpmIdentified,
     PpmExpandedIdentified,
     PpmExpandedHygiene,
+    PpmExpandedCompilable,
     PpmTyped,
 }
 
@@ -2803,6 +2886,7 @@ impl PpMode {
             PpmSource(PpmExpanded) |
             PpmSource(PpmExpandedIdentified) |
             PpmSource(PpmExpandedHygiene) |
+            PpmSource(PpmExpandedCompilable) |
             PpmHir(_) |
             PpmHirTree(_) |
             PpmMir |
@@ -1,5 +1,7 @@
-pub use self::code_stats::{DataTypeKind, SizeKind, FieldInfo, VariantInfo};
+pub use self::code_stats::{DataTypeKind, SizeKind, FieldInfo, VariantInfo, SymbolDefinition};
 use self::code_stats::CodeStats;
+pub use self::time_report::TimeReport;
+pub use self::stack_usage::{CallTarget, FunctionCalls, StackUsage};
 
 use crate::dep_graph::cgu_reuse_tracker::CguReuseTracker;
 use rustc_data_structures::fingerprint::Fingerprint;
@@ -22,6 +24,7 @@ use errors::emitter::HumanReadableErrorType;
 use errors::annotate_snippet_emitter_writer::{AnnotateSnippetEmitterWriter};
 use syntax::edition::Edition;
 use errors::json::JsonEmitter;
+use errors::sarif::SarifEmitter;
 use syntax::source_map;
 use syntax::sess::ParseSess;
 use syntax_pos::{MultiSpan, Span};
@@ -43,6 +46,8 @@ use std::time::Duration;
 use std::sync::Arc;
 
 mod code_stats;
+mod stack_usage;
+mod time_report;
 pub mod config;
 pub mod filesearch;
 pub mod search_paths;
@@ -112,6 +117,12 @@ pub struct Session {
     /// Data about code being compiled, gathered during compilation.
     pub code_stats: CodeStats,
 
+    /// Used by `-Z time-report`.
+    pub time_report: TimeReport,
+
+    /// Used by `-Z print-stack-usage`.
+    pub stack_usage: StackUsage,
+
     /// If `-zfuel=crate=n` is specified, `Some(crate)`.
     optimization_fuel_crate: Option<String>,
 
@@ -432,6 +443,9 @@ impl Session {
     pub fn time_passes(&self) -> bool {
         self.opts.debugging_opts.time_passes || self.opts.debugging_opts.time
     }
+    pub fn time_report(&self) -> bool {
+        self.opts.debugging_opts.time_report.is_some()
+    }
     pub fn time_extended(&self) -> bool {
         self.opts.debugging_opts.time_passes
     }
@@ -990,6 +1004,12 @@ fn default_emitter(
                 external_macro_backtrace,
             ).ui_testing(sopts.debugging_opts.ui_testing),
         ),
+        (config::ErrorOutputType::Sarif, None) => Box::new(
+            SarifEmitter::stderr(Some(registry), source_map.clone()),
+        ),
+        (config::ErrorOutputType::Sarif, Some(dst)) => Box::new(
+            SarifEmitter::new(dst, Some(registry), source_map.clone()),
+        ),
     }
 }
 
@@ -1165,6 +1185,8 @@ fn build_session_(
             normalize_projection_ty: AtomicUsize::new(0),
         },
         code_stats: Default::default(),
+        time_report: TimeReport::new(),
+        stack_usage: Default::default(),
         optimization_fuel_crate,
         optimization_fuel,
         print_fuel_crate,
@@ -1280,6 +1302,7 @@ pub fn early_error(output: config::ErrorOutputType, msg: &str) -> ! {
         }
         config::ErrorOutputType::Json { pretty, json_rendered } =>
             Box::new(JsonEmitter::basic(pretty, json_rendered, false)),
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
     };
     let handler = errors::Handler::with_emitter(true, None, emitter);
     handler.struct_fatal(msg).emit();
@@ -1294,6 +1317,7 @@ pub fn early_warn(output: config::ErrorOutputType, msg: &str) {
         }
         config::ErrorOutputType::Json { pretty, json_rendered } =>
             Box::new(JsonEmitter::basic(pretty, json_rendered, false)),
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
     };
     let handler = errors::Handler::with_emitter(true, None, emitter);
     handler.struct_warn(msg).emit();
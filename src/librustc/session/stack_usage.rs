@@ -0,0 +1,178 @@
+//! The worst-case stack usage of the entry points of a crate, printed by
+//! `-Z print-stack-usage`.
+//!
+//! This combines the call graph of the functions monomorphized in the crate,
+//! recorded when the mono items are collected, with the stack size of each
+//! function, read from the `.stack_sizes` sections emitted by LLVM (see
+//! `-Z emit-stack-sizes`) once the object files have been written. Functions
+//! of the crate without a stack size only count as inlined if they are not in
+//! the symbol tables of the object files either, otherwise their stack size is
+//! unknown.
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::sync::Lock;
+use std::collections::BTreeSet;
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum CallTarget {
+    /// A function known at compile time, by its symbol.
+    Function(String),
+    /// A call whose target is only known at runtime, e.g., a call through a
+    /// vtable or a function pointer, with its description.
+    Unknown(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct FunctionCalls {
+    pub symbol: String,
+    pub name: String,
+    pub calls: Vec<CallTarget>,
+}
+
+/// The worst-case stack usage of a function and the functions it calls.
+#[derive(Clone, Debug)]
+struct Usage {
+    /// The stack usage of the deepest chain of calls whose stack sizes are
+    /// all known.
+    bytes: u64,
+    /// The callee on the deepest chain of calls.
+    deepest_callee: Option<String>,
+    /// The reasons for which the stack usage may be larger than `bytes`.
+    unknowns: BTreeSet<String>,
+}
+
+#[derive(Default)]
+pub struct StackUsage {
+    /// The functions monomorphized in the local crate, by symbol.
+    functions: Lock<FxHashMap<String, FunctionCalls>>,
+    /// The symbols of the functions the usage of which is printed.
+    entry_points: Lock<Vec<String>>,
+    /// The names of the functions called, by symbol.
+    names: Lock<FxHashMap<String, String>>,
+    stack_sizes: Lock<FxHashMap<String, u64>>,
+    /// The symbols in the symbol tables of the object files.
+    object_symbols: Lock<FxHashSet<String>>,
+}
+
+impl StackUsage {
+    pub fn record_function(&self, function: FunctionCalls) {
+        self.names.borrow_mut().insert(function.symbol.clone(), function.name.clone());
+        self.functions.borrow_mut().insert(function.symbol.clone(), function);
+    }
+
+    pub fn record_name(&self, symbol: String, name: String) {
+        self.names.borrow_mut().entry(symbol).or_insert(name);
+    }
+
+    pub fn record_entry_point(&self, symbol: String) {
+        self.entry_points.borrow_mut().push(symbol);
+    }
+
+    pub fn record_stack_size(&self, symbol: String, size: u64) {
+        self.stack_sizes.borrow_mut().insert(symbol, size);
+    }
+
+    pub fn record_object_symbol(&self, symbol: String) {
+        self.object_symbols.borrow_mut().insert(symbol);
+    }
+
+    pub fn print_stack_usage(&self) {
+        let functions = self.functions.borrow();
+        let names = self.names.borrow();
+        let stack_sizes = self.stack_sizes.borrow();
+        let object_symbols = self.object_symbols.borrow();
+        let name = |symbol: &str| names.get(symbol).cloned().unwrap_or_else(|| symbol.to_owned());
+
+        let mut computation = UsageComputation {
+            functions: &functions,
+            stack_sizes: &stack_sizes,
+            object_symbols: &object_symbols,
+            name: &name,
+            usages: FxHashMap::default(),
+            active: Vec::new(),
+        };
+
+        let mut entry_points = self.entry_points.borrow().clone();
+        entry_points.sort_by_cached_key(|symbol| name(symbol));
+        entry_points.dedup();
+        for symbol in &entry_points {
+            let usage = computation.usage(symbol);
+            let bound = if usage.unknowns.is_empty() { "" } else { "at least " };
+            println!("print-stack-usage entry: `{}`: {}{} bytes",
+                     name(symbol), bound, usage.bytes);
+
+            let mut path = vec![name(symbol)];
+            let mut callee = usage.deepest_callee.clone();
+            while let Some(symbol) = callee {
+                path.push(name(&symbol));
+                callee = computation.usages[&symbol].deepest_callee.clone();
+            }
+            if path.len() > 1 {
+                println!("print-stack-usage     deepest path: {}", path.join(" -> "));
+            }
+            for unknown in &usage.unknowns {
+                println!("print-stack-usage     unknown: {}", unknown);
+            }
+        }
+    }
+}
+
+struct UsageComputation<'a> {
+    functions: &'a FxHashMap<String, FunctionCalls>,
+    stack_sizes: &'a FxHashMap<String, u64>,
+    object_symbols: &'a FxHashSet<String>,
+    name: &'a dyn Fn(&str) -> String,
+    usages: FxHashMap<String, Usage>,
+    /// The functions whose usage is being computed, i.e., the current chain
+    /// of calls, to detect recursion.
+    active: Vec<String>,
+}
+
+impl UsageComputation<'_> {
+    fn usage(&mut self, symbol: &str) -> Usage {
+        if let Some(usage) = self.usages.get(symbol) {
+            return usage.clone();
+        }
+
+        let mut usage = Usage { bytes: 0, deepest_callee: None, unknowns: BTreeSet::new() };
+        match self.stack_sizes.get(symbol) {
+            Some(&size) => usage.bytes = size,
+            // Functions of the local crate which aren't in any object file
+            // were inlined into all of their callers, and use the stack frames
+            // of those.
+            None if self.functions.contains_key(symbol) &&
+                !self.object_symbols.contains(symbol) => {}
+            None => {
+                usage.unknowns.insert(format!("no stack size for `{}`", (self.name)(symbol)));
+            }
+        }
+
+        let functions = self.functions;
+        let calls = functions.get(symbol).map_or(&[][..], |function| &function.calls[..]);
+        let frame = usage.bytes;
+        self.active.push(symbol.to_owned());
+        for call in calls {
+            match *call {
+                CallTarget::Function(ref callee) if self.active.contains(callee) => {
+                    usage.unknowns.insert(format!("recursion through `{}`", (self.name)(callee)));
+                }
+                CallTarget::Function(ref callee) => {
+                    let callee_usage = self.usage(callee);
+                    let bytes = frame + callee_usage.bytes;
+                    if usage.deepest_callee.is_none() || bytes > usage.bytes {
+                        usage.bytes = bytes;
+                        usage.deepest_callee = Some(callee.clone());
+                    }
+                    usage.unknowns.extend(callee_usage.unknowns);
+                }
+                CallTarget::Unknown(ref description) => {
+                    usage.unknowns.insert(format!("{} in `{}`", description, (self.name)(symbol)));
+                }
+            }
+        }
+        self.active.pop();
+
+        self.usages.insert(symbol.to_owned(), usage.clone());
+        usage
+    }
+}
@@ -0,0 +1,122 @@
+//! The report written by `-Z time-report=json`: a single JSON document per
+//! compilation, summarizing where it spent its time and memory, which unlike
+//! the output of `-Z time-passes` and `-Z self-profile` can be ingested as-is.
+
+use crate::util::common::get_peak_resident;
+use rustc_data_structures::sync::Lock;
+use rustc_serialize::json::as_pretty_json;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// The `version` written at the top of the report.
+const REPORT_VERSION: u32 = 1;
+
+#[derive(RustcEncodable)]
+struct PassTiming {
+    name: String,
+    /// How many passes this one is nested in.
+    depth: usize,
+    duration_secs: f64,
+    /// The resident set size at the end of the pass, in bytes.
+    rss_bytes: Option<usize>,
+}
+
+#[derive(RustcEncodable)]
+struct QueryCount {
+    name: &'static str,
+    /// The number of distinct results computed by the query.
+    entries: usize,
+    /// Only tracked by compilers built with debug assertions.
+    cache_hits: Option<usize>,
+}
+
+#[derive(RustcEncodable)]
+struct CodegenUnitSize {
+    name: String,
+    items: usize,
+    size_estimate: usize,
+}
+
+#[derive(RustcEncodable)]
+struct Report<'a> {
+    version: u32,
+    crate_name: &'a str,
+    total_secs: f64,
+    peak_rss_bytes: Option<usize>,
+    passes: &'a [PassTiming],
+    queries: &'a [QueryCount],
+    codegen_units: &'a [CodegenUnitSize],
+}
+
+/// Measurements gathered for `-Z time-report`.
+pub struct TimeReport {
+    start: Instant,
+    /// Where to write the report, and the name of the crate being compiled,
+    /// known once the outputs have been determined.
+    output: Lock<Option<(PathBuf, String)>>,
+    passes: Lock<Vec<PassTiming>>,
+    queries: Lock<Vec<QueryCount>>,
+    codegen_units: Lock<Vec<CodegenUnitSize>>,
+}
+
+impl TimeReport {
+    pub fn new() -> TimeReport {
+        TimeReport {
+            start: Instant::now(),
+            output: Lock::new(None),
+            passes: Lock::new(Vec::new()),
+            queries: Lock::new(Vec::new()),
+            codegen_units: Lock::new(Vec::new()),
+        }
+    }
+
+    pub fn set_output(&self, path: PathBuf, crate_name: String) {
+        *self.output.lock() = Some((path, crate_name));
+    }
+
+    /// Records a pass timed with `util::common::time`. Passes are recorded
+    /// as they end, so nested passes come before the ones containing them.
+    pub fn record_pass(&self, name: &str, depth: usize, duration: Duration, rss: Option<usize>) {
+        self.passes.lock().push(PassTiming {
+            name: name.to_owned(),
+            depth,
+            duration_secs: duration.as_secs_f64(),
+            rss_bytes: rss,
+        });
+    }
+
+    pub fn record_query(&self, name: &'static str, entries: usize, cache_hits: Option<usize>) {
+        self.queries.lock().push(QueryCount { name, entries, cache_hits });
+    }
+
+    pub fn record_codegen_unit(&self, name: String, items: usize, size_estimate: usize) {
+        self.codegen_units.lock().push(CodegenUnitSize { name, items, size_estimate });
+    }
+
+    /// Writes the report, if the outputs of the compilation have been
+    /// determined. Returns the path of the report.
+    pub fn write(&self) -> io::Result<Option<PathBuf>> {
+        let output = self.output.lock();
+        let (path, crate_name) = match *output {
+            Some((ref path, ref crate_name)) => (path, crate_name),
+            None => return Ok(None),
+        };
+
+        let passes = self.passes.lock();
+        let queries = self.queries.lock();
+        let codegen_units = self.codegen_units.lock();
+        let report = Report {
+            version: REPORT_VERSION,
+            crate_name,
+            total_secs: self.start.elapsed().as_secs_f64(),
+            peak_rss_bytes: get_peak_resident(),
+            passes: &passes,
+            queries: &queries,
+            codegen_units: &codegen_units,
+        };
+        fs::write(path, format!("{}\n", as_pretty_json(&report)))?;
+        Ok(Some(path.clone()))
+    }
+}
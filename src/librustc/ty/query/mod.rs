@@ -61,7 +61,7 @@ use syntax::symbol::Symbol;
 #[macro_use]
 mod plumbing;
 use self::plumbing::*;
-pub use self::plumbing::{force_from_dep_node, CycleError};
+pub use self::plumbing::{force_from_dep_node, CycleError, QueryStats};
 
 mod job;
 pub use self::job::{QueryJob, QueryInfo};
@@ -670,6 +670,19 @@ macro_rules! hash_result {
     };
 }
 
+/// Statistics about the cache of a query, as printed by `-Z query-stats`.
+#[derive(Clone)]
+pub struct QueryStats {
+    pub name: &'static str,
+    /// Only tracked by compilers built with debug assertions.
+    pub cache_hits: usize,
+    pub key_size: usize,
+    pub key_type: &'static str,
+    pub value_size: usize,
+    pub value_type: &'static str,
+    pub entry_count: usize,
+}
+
 macro_rules! define_queries {
     (<$tcx:tt> $($category:tt {
         $($(#[$attr:meta])* [$($modifiers:tt)*] fn $name:ident: $node:ident($K:ty) -> $V:ty,)*
@@ -733,20 +746,9 @@ macro_rules! define_queries_inner {
                 jobs
             }
 
-            pub fn print_stats(&self) {
+            pub fn query_stats(&self) -> Vec<QueryStats> {
                 let mut queries = Vec::new();
 
-                #[derive(Clone)]
-                struct QueryStats {
-                    name: &'static str,
-                    cache_hits: usize,
-                    key_size: usize,
-                    key_type: &'static str,
-                    value_size: usize,
-                    value_type: &'static str,
-                    entry_count: usize,
-                }
-
                 fn stats<'tcx, Q: QueryConfig<'tcx>>(
                     name: &'static str,
                     map: &Sharded<QueryCache<'tcx, Q>>,
@@ -773,6 +775,12 @@ macro_rules! define_queries_inner {
                     ));
                 )*
 
+                queries
+            }
+
+            pub fn print_stats(&self) {
+                let queries = self.query_stats();
+
                 if cfg!(debug_assertions) {
                     let hits: usize = queries.iter().map(|s| s.cache_hits).sum();
                     let results: usize = queries.iter().map(|s| s.entry_count).sum();
@@ -19,6 +19,10 @@ pub use errors::ErrorReported;
 
 thread_local!(static TIME_DEPTH: Cell<usize> = Cell::new(0));
 
+// The depth of `time()` calls for `-Z time-report`, which unlike `TIME_DEPTH`
+// is tracked even if the passes are not printed.
+thread_local!(static REPORT_DEPTH: Cell<usize> = Cell::new(0));
+
 #[allow(nonstandard_style)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct QueryMsg {
@@ -42,7 +46,17 @@ pub fn set_time_depth(depth: usize) {
 pub fn time<T, F>(sess: &Session, what: &str, f: F) -> T where
     F: FnOnce() -> T,
 {
-    time_ext(sess.time_passes(), what, f)
+    if !sess.time_report() {
+        return time_ext(sess.time_passes(), what, f);
+    }
+
+    let depth = REPORT_DEPTH.with(|slot| slot.replace(slot.get() + 1));
+    let start = Instant::now();
+    let rv = time_ext(sess.time_passes(), what, f);
+    sess.time_report.record_pass(what, depth, start.elapsed(), get_resident());
+    REPORT_DEPTH.with(|slot| slot.set(depth));
+
+    rv
 }
 
 pub fn time_ext<T, F>(do_it: bool, what: &str, f: F) -> T where
@@ -144,6 +158,29 @@ fn get_resident() -> Option<usize> {
 
 #[cfg(windows)]
 fn get_resident() -> Option<usize> {
+    windows::process_memory_counters().map(|pmc| pmc.WorkingSetSize as usize)
+}
+
+/// Returns the peak resident set size of the process, in bytes.
+#[cfg(unix)]
+pub fn get_peak_resident() -> Option<usize> {
+    use std::fs;
+
+    // Only Linux provides it through `/proc`.
+    let contents = fs::read_to_string("/proc/self/status").ok()?;
+    let line = contents.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb = line["VmHWM:".len()..].trim().trim_end_matches("kB").trim();
+    Some(kb.parse::<usize>().ok()? * 1024)
+}
+
+/// Returns the peak resident set size of the process, in bytes.
+#[cfg(windows)]
+pub fn get_peak_resident() -> Option<usize> {
+    windows::process_memory_counters().map(|pmc| pmc.PeakWorkingSetSize as usize)
+}
+
+#[cfg(windows)]
+mod windows {
     type BOOL = i32;
     type DWORD = u32;
     type HANDLE = *mut u8;
@@ -151,11 +188,11 @@ fn get_resident() -> Option<usize> {
     use std::mem;
     #[repr(C)]
     #[allow(non_snake_case)]
-    struct PROCESS_MEMORY_COUNTERS {
+    pub struct PROCESS_MEMORY_COUNTERS {
         cb: DWORD,
         PageFaultCount: DWORD,
-        PeakWorkingSetSize: size_t,
-        WorkingSetSize: size_t,
+        pub PeakWorkingSetSize: size_t,
+        pub WorkingSetSize: size_t,
         QuotaPeakPagedPoolUsage: size_t,
         QuotaPagedPoolUsage: size_t,
         QuotaPeakNonPagedPoolUsage: size_t,
@@ -171,11 +208,14 @@ fn get_resident() -> Option<usize> {
                                 ppsmemCounters: PPROCESS_MEMORY_COUNTERS,
                                 cb: DWORD) -> BOOL;
     }
-    let mut pmc: PROCESS_MEMORY_COUNTERS = unsafe { mem::zeroed() };
-    pmc.cb = mem::size_of_val(&pmc) as DWORD;
-    match unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut pmc, pmc.cb) } {
-        0 => None,
-        _ => Some(pmc.WorkingSetSize as usize),
+
+    pub fn process_memory_counters() -> Option<PROCESS_MEMORY_COUNTERS> {
+        let mut pmc: PROCESS_MEMORY_COUNTERS = unsafe { mem::zeroed() };
+        pmc.cb = mem::size_of_val(&pmc) as DWORD;
+        match unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut pmc, pmc.cb) } {
+            0 => None,
+            _ => Some(pmc),
+        }
     }
 }
 
@@ -0,0 +1,57 @@
+//! Reading back the size of each symbol in the object files, for
+//! `-Z print-code-sizes`.
+//!
+//! When functions and data each have their own section, the section of a
+//! symbol is named after it, e.g., `.text.<symbol>` or `.rodata.<symbol>`, and
+//! its size is the size of the symbol. This is the size before linking, which
+//! also counts the symbols removed by the linker as unused.
+
+use crate::back::object_file;
+use rustc::session::Session;
+use rustc_codegen_ssa::CodegenResults;
+
+use std::path::Path;
+
+/// The prefixes of the names of the sections holding a single symbol. The
+/// longest prefixes come first.
+const SECTION_PREFIXES: &[&[u8]] = &[
+    b".text.unlikely.",
+    b".text.",
+    b".rodata.",
+    b".data.rel.ro.",
+    b".data.",
+    b".bss.",
+    b".tdata.",
+    b".tbss.",
+];
+
+/// Records the size of the symbols in the object files of the crate, before
+/// they are linked.
+pub fn record_code_sizes(sess: &Session, codegen_results: &CodegenResults) {
+    if !sess.target.target.options.function_sections {
+        sess.warn("`-Z print-code-sizes` requires a target using function sections, \
+                   the sizes of the symbols are unknown");
+        return;
+    }
+
+    for object in codegen_results.modules.iter().filter_map(|module| module.object.as_ref()) {
+        if let Err(e) = record_object_code_sizes(sess, object) {
+            sess.warn(&format!("couldn't read the sizes of the symbols in `{}`: {}",
+                               object.display(), e));
+        }
+    }
+}
+
+fn record_object_code_sizes(sess: &Session, path: &Path) -> Result<(), &'static str> {
+    let of = object_file::open(path)?;
+    object_file::for_each_section(&of, |section| {
+        let symbol = SECTION_PREFIXES.iter()
+            .find(|&&prefix| section.name.starts_with(prefix))
+            .map(|prefix| &section.name[prefix.len()..]);
+        if let Some(symbol) = symbol {
+            sess.code_stats.record_symbol_size(String::from_utf8_lossy(symbol).into_owned(),
+                                               section.size());
+        }
+    });
+    Ok(())
+}
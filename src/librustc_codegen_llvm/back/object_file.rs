@@ -0,0 +1,70 @@
+//! Reading back the sections and symbols of the object files written by LLVM,
+//! for `-Z print-stack-usage` and `-Z print-code-sizes`.
+
+use crate::llvm::{self, False, ObjectFile, SectionIterator, mk_section_iter, mk_symbol_iter};
+use rustc_fs_util::path_to_c_string;
+
+use std::ffi::CStr;
+use std::path::Path;
+use std::slice;
+
+/// A section of an object file, while it is visited by `for_each_section`.
+pub struct Section<'s, 'o> {
+    llsi: &'s SectionIterator<'o>,
+    pub name: &'s [u8],
+}
+
+impl Section<'s, 'o> {
+    pub fn size(&self) -> u64 {
+        unsafe { llvm::LLVMGetSectionSize(self.llsi) as u64 }
+    }
+
+    pub fn contents(&self) -> &'s [u8] {
+        unsafe {
+            slice::from_raw_parts(
+                llvm::LLVMGetSectionContents(self.llsi) as *const u8,
+                self.size() as usize,
+            )
+        }
+    }
+}
+
+pub fn open(path: &Path) -> Result<ObjectFile, &'static str> {
+    unsafe {
+        let buf = path_to_c_string(path);
+        let mb = llvm::LLVMRustCreateMemoryBufferWithContentsOfFile(buf.as_ptr())
+            .ok_or("error reading the file")?;
+        ObjectFile::new(mb).ok_or("not an object file")
+    }
+}
+
+/// Calls `f` with each section of the object file, in order.
+pub fn for_each_section(of: &ObjectFile, mut f: impl FnMut(&Section<'_, '_>)) {
+    unsafe {
+        let si = mk_section_iter(of.llof);
+        while llvm::LLVMIsSectionIteratorAtEnd(of.llof, si.llsi) == False {
+            let mut name_buf = None;
+            let name_len = llvm::LLVMRustGetSectionName(si.llsi, &mut name_buf);
+            let name = name_buf.map_or(
+                &[][..], // We got a NULL ptr, ignore `name_len`.
+                |buf| slice::from_raw_parts(buf.as_ptr() as *const u8, name_len as usize),
+            );
+            f(&Section { llsi: si.llsi, name });
+            llvm::LLVMMoveToNextSection(si.llsi);
+        }
+    }
+}
+
+/// Calls `f` with the name of each symbol of the object file, in order.
+pub fn for_each_symbol(of: &ObjectFile, mut f: impl FnMut(&[u8])) {
+    unsafe {
+        let si = mk_symbol_iter(of.llof);
+        while llvm::LLVMIsSymbolIteratorAtEnd(of.llof, si.llsi) == False {
+            let name = llvm::LLVMGetSymbolName(si.llsi);
+            if !name.is_null() {
+                f(CStr::from_ptr(name).to_bytes());
+            }
+            llvm::LLVMMoveToNextSymbol(si.llsi);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Reading back the stack sizes emitted by LLVM, for `-Z print-stack-usage`.
+//!
+//! LLVM writes the stack size of each function to a `.stack_sizes` section,
+//! as the address of the function followed by its size in ULEB128. In object
+//! files, the address is only a relocation against the section of the
+//! function, which the C API of LLVM doesn't expose. However, when functions
+//! each have their own section, LLVM also emits a `.stack_sizes` section for
+//! each of them, right after their `.text.<symbol>` section, so the stack
+//! sizes are matched with the symbol of the preceding function.
+//!
+//! The symbols of the object files are recorded too: a function of the crate
+//! missing from all of them was inlined into its callers, while one which is
+//! there but whose stack size couldn't be matched has an unknown stack size.
+
+use crate::back::object_file;
+use rustc::session::Session;
+use rustc_codegen_ssa::CodegenResults;
+
+use std::path::Path;
+
+/// Records the stack sizes of the functions in the object files of the crate,
+/// before they are linked.
+pub fn record_stack_sizes(sess: &Session, codegen_results: &CodegenResults) {
+    if !sess.target.target.options.function_sections {
+        sess.warn("`-Z print-stack-usage` requires a target using function sections, \
+                   the stack sizes of the functions are unknown");
+        return;
+    }
+
+    let pointer_size = sess.target.ptr_width as usize / 8;
+    let mut found = false;
+    for object in codegen_results.modules.iter().filter_map(|module| module.object.as_ref()) {
+        match record_object_stack_sizes(sess, object, pointer_size) {
+            Ok(count) => found |= count > 0,
+            Err(e) => {
+                sess.warn(&format!("couldn't read the stack sizes in `{}`: {}",
+                                   object.display(), e));
+            }
+        }
+    }
+    // The stack sizes of all the functions would be unknown.
+    if !found {
+        sess.warn("no stack sizes were found in the object files, \
+                   the stack usage printed by `-Z print-stack-usage` is unknown");
+    }
+}
+
+fn record_object_stack_sizes(
+    sess: &Session,
+    path: &Path,
+    pointer_size: usize,
+) -> Result<usize, &'static str> {
+    let of = object_file::open(path)?;
+
+    let mut function = None;
+    let mut count = 0;
+    object_file::for_each_section(&of, |section| {
+        let name = section.name;
+        if name.starts_with(b".text.") {
+            let symbol = &name[b".text.".len()..];
+            // Cold functions are put in `.text.unlikely.<symbol>`.
+            let symbol = if symbol.starts_with(b"unlikely.") {
+                &symbol[b"unlikely.".len()..]
+            } else {
+                symbol
+            };
+            function = Some(String::from_utf8_lossy(strip_llvm_suffix(symbol)).into_owned());
+        } else if name == b".stack_sizes" {
+            let contents = section.contents();
+            // Sections holding the stack sizes of several functions can't
+            // be matched with their symbols.
+            let size = contents.get(pointer_size..)
+                .and_then(read_uleb128)
+                .filter(|&(_, len)| pointer_size + len == contents.len());
+            if let (Some(symbol), Some((size, _))) = (function.take(), size) {
+                sess.stack_usage.record_stack_size(symbol, size);
+                count += 1;
+            }
+        }
+    });
+
+    object_file::for_each_symbol(&of, |name| {
+        let symbol = strip_llvm_suffix(name);
+        sess.stack_usage.record_object_symbol(String::from_utf8_lossy(symbol).into_owned());
+    });
+    Ok(count)
+}
+
+/// Strips the `.llvm.<hash>` suffix that ThinLTO appends to the local symbols
+/// it promotes, so that they match the symbols of the mono items again.
+fn strip_llvm_suffix(symbol: &[u8]) -> &[u8] {
+    const SUFFIX: &[u8] = b".llvm.";
+    match symbol.windows(SUFFIX.len()).position(|window| window == SUFFIX) {
+        Some(i) => &symbol[..i],
+        None => symbol,
+    }
+}
+
+/// Reads an unsigned LEB128 number, returning it with its length in bytes.
+fn read_uleb128(data: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0;
+    for (i, &byte) in data.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
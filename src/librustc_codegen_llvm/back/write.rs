@@ -164,7 +164,8 @@ pub fn target_machine_factory(sess: &Session, optlvl: config::OptLevel, find_fea
     let abi = SmallCStr::new(&sess.target.target.options.llvm_abiname);
     let is_pie_binary = !find_features && is_pie_binary(sess);
     let trap_unreachable = sess.target.target.options.trap_unreachable;
-    let emit_stack_size_section = sess.opts.debugging_opts.emit_stack_sizes;
+    let emit_stack_size_section = sess.opts.debugging_opts.emit_stack_sizes ||
+        sess.opts.debugging_opts.print_stack_usage;
 
     let asm_comments = sess.asm_comments();
 
@@ -68,7 +68,10 @@ use rustc_codegen_utils::codegen_backend::CodegenBackend;
 mod back {
     pub mod archive;
     pub mod bytecode;
+    pub mod code_sizes;
     pub mod lto;
+    pub mod object_file;
+    pub mod stack_sizes;
     pub mod write;
 }
 
@@ -306,6 +309,18 @@ impl CodegenBackend for LlvmCodegenBackend {
 
         sess.compile_status()?;
 
+        if sess.opts.debugging_opts.print_stack_usage {
+            time(sess, "reading stack sizes", || {
+                back::stack_sizes::record_stack_sizes(sess, &codegen_results)
+            });
+        }
+
+        if sess.opts.debugging_opts.print_code_sizes.is_some() {
+            time(sess, "reading code sizes", || {
+                back::code_sizes::record_code_sizes(sess, &codegen_results)
+            });
+        }
+
         if !sess.opts.output_types.keys().any(|&i| i == OutputType::Exe ||
                                                    i == OutputType::Metadata) {
             return Ok(());
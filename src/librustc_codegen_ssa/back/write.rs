@@ -410,6 +410,7 @@ pub fn start_async_codegen<B: ExtraBackendMethods>(
                 allocator_config.emit_obj = true;
             },
             OutputType::Mir => {}
+            OutputType::MirJson => {}
             OutputType::DepInfo => {}
         }
     }
@@ -563,6 +564,7 @@ fn produce_final_output_artifacts(sess: &Session,
                 copy_if_one_unit(OutputType::Object, true);
             }
             OutputType::Mir |
+            OutputType::MirJson |
             OutputType::Metadata |
             OutputType::Exe |
             OutputType::DepInfo => {}
@@ -67,6 +67,7 @@ use syntax_pos::FileName;
 
 pub mod pretty;
 mod args;
+mod reproducible;
 
 /// Exit status code used for successful compilation and help output.
 pub const EXIT_SUCCESS: i32 = 0;
@@ -201,6 +202,10 @@ pub fn run_compiler(
         return Ok(());
     }
 
+    if sopts.debugging_opts.verify_reproducible {
+        return reproducible::verify_reproducible(&args, &sopts, &matches);
+    }
+
     let (odir, ofile) = make_output(&matches);
     let (input, input_file_path, input_err) = match make_input(&matches.free) {
         Some(v) => v,
@@ -359,6 +364,13 @@ pub fn run_compiler(
 
             queries.global_ctxt()?;
 
+            if sess.opts.prints.contains(&PrintRequest::CrateGraph) {
+                queries.global_ctxt()?.peek_mut().enter(|tcx| {
+                    rustc_metadata::creader::print_crate_graph(tcx)
+                });
+                return early_exit();
+            }
+
             if sess.opts.debugging_opts.no_analysis ||
                sess.opts.debugging_opts.ast_json {
                    return early_exit();
@@ -416,6 +428,14 @@ pub fn run_compiler(
             linker.link()?
         }
 
+        if sess.opts.debugging_opts.print_stack_usage {
+            sess.stack_usage.print_stack_usage();
+        }
+
+        if let Some(format) = sess.opts.debugging_opts.print_code_sizes {
+            sess.code_stats.print_code_sizes(format);
+        }
+
         if sess.opts.debugging_opts.perf_stats {
             sess.print_perf_stats();
         }
@@ -635,9 +655,12 @@ impl RustcDefaultCalls {
                         ofile: &Option<PathBuf>)
                         -> Compilation {
         use rustc::session::config::PrintRequest::*;
-        // PrintRequest::NativeStaticLibs is special - printed during linking
+        // PrintRequest::NativeStaticLibs is special - printed during linking,
+        // and so is PrintRequest::CrateGraph - printed once crates are loaded
         // (empty iterator returns true)
-        if sess.opts.prints.iter().all(|&p| p == PrintRequest::NativeStaticLibs) {
+        if sess.opts.prints.iter().all(|&p| {
+            p == PrintRequest::NativeStaticLibs || p == PrintRequest::CrateGraph
+        }) {
             return Compilation::Continue;
         }
 
@@ -729,8 +752,12 @@ impl RustcDefaultCalls {
                 }
                 // Any output here interferes with Cargo's parsing of other printed output
                 PrintRequest::NativeStaticLibs => {}
+                PrintRequest::CrateGraph => {}
             }
         }
+        if sess.opts.prints.contains(&PrintRequest::CrateGraph) {
+            return Compilation::Continue;
+        }
         return Compilation::Stop;
     }
 }
@@ -1260,10 +1287,14 @@ pub fn main() {
             .collect::<Vec<_>>();
         run_compiler(&args, &mut callbacks, None, None)
     }).and_then(|result| result);
-    let exit_code = match result {
+    let mut exit_code = match result {
         Ok(_) => EXIT_SUCCESS,
         Err(_) => EXIT_FAILURE,
     };
+    // There's nowhere left to report the failure to write the log.
+    if errors::sarif::flush_stderr_log().is_err() {
+        exit_code = EXIT_FAILURE;
+    }
     // The extra `\t` is necessary to align this label with the others.
     set_time_depth(0);
     print_time_passes_entry(callbacks.time_passes, "\ttotal", start.elapsed());
@@ -2,17 +2,24 @@
 
 use rustc::hir;
 use rustc::hir::map as hir_map;
+use rustc::hir::map::DefPathData;
 use rustc::hir::print as pprust_hir;
+use rustc::hir::def::{DefKind, Res};
 use rustc::hir::def_id::LOCAL_CRATE;
 use rustc::session::Session;
 use rustc::session::config::{PpMode, PpSourceMode, Input};
-use rustc::ty::{self, TyCtxt};
+use rustc::ty::{self, DefIdTree, TyCtxt};
 use rustc::util::common::ErrorReported;
+use rustc_data_structures::fx::FxHashMap;
 use rustc_mir::util::{write_mir_pretty, write_mir_graphviz};
 
 use syntax::ast;
+use syntax::mut_visit::{self, MutVisitor};
 use syntax::print::{pprust};
+use syntax::ptr::P;
 use syntax_pos::FileName;
+use syntax_pos::hygiene::SyntaxContext;
+use syntax_pos::symbol::Symbol;
 
 use std::cell::Cell;
 use std::fs::File;
@@ -47,7 +54,7 @@ where
     F: FnOnce(&dyn PrinterSupport) -> A,
 {
     match *ppmode {
-        PpmNormal | PpmEveryBodyLoops | PpmExpanded => {
+        PpmNormal | PpmEveryBodyLoops | PpmExpanded | PpmExpandedCompilable => {
             let annotation = NoAnn {
                 sess,
                 tcx,
@@ -333,6 +340,189 @@ impl<'a> pprust::PpAnn for HygieneAnnotation<'a> {
     }
 }
 
+/// Rewrites the expanded crate for `-Z unpretty=expanded,compilable`, so that
+/// it can be compiled again once printed.
+///
+/// Hygiene can't be represented in source code, so the identifiers of local
+/// variables and labels introduced by macros are renamed uniquely, and the
+/// resolved paths are replaced with absolute paths, starting with `crate::` in
+/// the local crate and `::name::` in other crates, so that they no longer
+/// depend on where the macros were defined or invoked.
+struct CompilableRewriter<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The unique names of the hygienic identifiers, by name and context.
+    hygienic_names: FxHashMap<(Symbol, SyntaxContext), Symbol>,
+}
+
+impl<'tcx> CompilableRewriter<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>) -> Self {
+        CompilableRewriter {
+            tcx,
+            hygienic_names: Default::default(),
+        }
+    }
+
+    fn hir_node(&self, id: ast::NodeId) -> Option<hir::Node<'tcx>> {
+        let hir_id = self.tcx.hir().opt_node_to_hir_id(id)?;
+        self.tcx.hir().find(hir_id)
+    }
+
+    /// Returns the resolution of the path of the given node, unless the path
+    /// is relative to a type or has a qualified `Self` type.
+    fn path_res(&self, id: ast::NodeId) -> Option<Res> {
+        let qpath = match self.hir_node(id)? {
+            hir::Node::Expr(&hir::Expr { kind: hir::ExprKind::Struct(ref qpath, ..), .. }) => {
+                &**qpath
+            }
+            hir::Node::Expr(&hir::Expr { kind: hir::ExprKind::Path(ref qpath), .. }) |
+            hir::Node::Ty(&hir::Ty { kind: hir::TyKind::Path(ref qpath), .. }) |
+            hir::Node::Pat(&hir::Pat { kind: hir::PatKind::Path(ref qpath), .. }) |
+            hir::Node::Pat(&hir::Pat { kind: hir::PatKind::TupleStruct(ref qpath, ..), .. }) |
+            hir::Node::Pat(&hir::Pat { kind: hir::PatKind::Struct(ref qpath, ..), .. }) => qpath,
+            hir::Node::TraitRef(trait_ref) => return Some(trait_ref.path.res),
+            _ => return None,
+        };
+        match *qpath {
+            hir::QPath::Resolved(None, ref path) => Some(path.res),
+            _ => None,
+        }
+    }
+
+    /// Renames the identifier uniquely if it was introduced by a macro.
+    ///
+    /// Local variables and labels are resolved by comparing their names and
+    /// their contexts, without their transparent marks, like this.
+    fn rename_hygienic(&mut self, ident: &mut ast::Ident) {
+        let ctxt = ident.span.ctxt().modern_and_legacy();
+        if ctxt == SyntaxContext::root() {
+            return;
+        }
+        let index = self.hygienic_names.len();
+        ident.name = *self.hygienic_names.entry((ident.name, ctxt)).or_insert_with(|| {
+            Symbol::intern(&format!("{}_h{}", ident.name, index))
+        });
+    }
+
+    /// Returns the absolute path of the definition, for a path written as
+    /// `path`, if it has one.
+    fn absolute_path(&self, res: Res, path: &ast::Path) -> Option<ast::Path> {
+        let def_id = match res {
+            Res::Def(DefKind::TyParam, _) | Res::Def(DefKind::ConstParam, _) => return None,
+            Res::Def(_, def_id) => def_id,
+            _ => return None,
+        };
+
+        // The generic arguments are only kept on the last segment.
+        let (last, rest) = path.segments.split_last()?;
+        if rest.iter().any(|segment| segment.args.is_some()) {
+            return None;
+        }
+
+        // Items of the local crate defined in functions or implementations
+        // have no path.
+        if def_id.is_local() {
+            let mut parent = self.tcx.parent(def_id);
+            while let Some(id) = parent {
+                match self.tcx.def_key(id).disambiguated_data.data {
+                    DefPathData::CrateRoot | DefPathData::TypeNs(_) => {}
+                    _ => return None,
+                }
+                parent = self.tcx.parent(id);
+            }
+        }
+
+        let path_str = ty::print::with_crate_prefix(|| self.tcx.def_path_str(def_id));
+        let span = path.span;
+        let mut segments = Vec::new();
+        if !def_id.is_local() {
+            segments.push(ast::PathSegment::path_root(span));
+        }
+        for name in path_str.split("::") {
+            let ident = ast::Ident::new(Symbol::intern(name), span);
+            segments.push(ast::PathSegment::from_ident(ident));
+        }
+        segments.last_mut()?.args = last.args.clone();
+        Some(ast::Path { span, segments })
+    }
+
+    fn rewrite_path(&mut self, id: ast::NodeId, path: &mut ast::Path) {
+        match self.path_res(id) {
+            Some(Res::Local(_)) => {
+                if let [ref mut segment] = path.segments[..] {
+                    self.rename_hygienic(&mut segment.ident);
+                }
+            }
+            Some(res) => {
+                if let Some(absolute_path) = self.absolute_path(res, path) {
+                    *path = absolute_path;
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+impl MutVisitor for CompilableRewriter<'_> {
+    fn visit_expr(&mut self, expr: &mut P<ast::Expr>) {
+        let id = expr.id;
+        match expr.kind {
+            ast::ExprKind::Path(None, ref mut path) => self.rewrite_path(id, path),
+            ast::ExprKind::Struct(ref mut path, ref mut fields, _) => {
+                self.rewrite_path(id, path);
+                // The expressions of the fields may be renamed.
+                for field in fields {
+                    field.is_shorthand = false;
+                }
+            }
+            _ => {}
+        }
+        mut_visit::noop_visit_expr(expr, self);
+    }
+
+    fn visit_pat(&mut self, pat: &mut P<ast::Pat>) {
+        let id = pat.id;
+        match pat.kind {
+            ast::PatKind::Ident(_, ref mut ident, _) => {
+                if let Some(hir::Node::Binding(_)) = self.hir_node(id) {
+                    self.rename_hygienic(ident);
+                }
+            }
+            ast::PatKind::Path(None, ref mut path) |
+            ast::PatKind::TupleStruct(ref mut path, _) => self.rewrite_path(id, path),
+            ast::PatKind::Struct(ref mut path, ref mut fields, _) => {
+                self.rewrite_path(id, path);
+                // The bindings of the fields may be renamed.
+                for field in fields {
+                    field.is_shorthand = false;
+                }
+            }
+            _ => {}
+        }
+        mut_visit::noop_visit_pat(pat, self);
+    }
+
+    fn visit_ty(&mut self, ty: &mut P<ast::Ty>) {
+        let id = ty.id;
+        if let ast::TyKind::Path(None, ref mut path) = ty.kind {
+            self.rewrite_path(id, path);
+        }
+        mut_visit::noop_visit_ty(ty, self);
+    }
+
+    fn visit_trait_ref(&mut self, trait_ref: &mut ast::TraitRef) {
+        self.rewrite_path(trait_ref.ref_id, &mut trait_ref.path);
+        mut_visit::noop_visit_trait_ref(trait_ref, self);
+    }
+
+    fn visit_label(&mut self, label: &mut ast::Label) {
+        self.rename_hygienic(&mut label.ident);
+    }
+
+    fn visit_mac(&mut self, mac: &mut ast::Mac) {
+        mut_visit::noop_visit_mac(mac, self)
+    }
+}
+
 struct TypedAnnotation<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
     tables: Cell<&'a ty::TypeckTables<'tcx>>,
@@ -460,6 +650,16 @@ pub fn print_after_hir_lowering<'tcx>(
 
     match ppm {
             PpmSource(s) => {
+                let compilable_krate;
+                let krate = if s == PpmExpandedCompilable {
+                    let mut rewritten = krate.clone();
+                    CompilableRewriter::new(tcx).visit_crate(&mut rewritten);
+                    compilable_krate = rewritten;
+                    &compilable_krate
+                } else {
+                    krate
+                };
+
                 // Silently ignores an identified node.
                 let out = &mut out;
                 let src = src.clone();
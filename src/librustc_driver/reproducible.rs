@@ -0,0 +1,475 @@
+//! `-Z verify-reproducible`: compiles the crate twice, in different conditions,
+//! and checks that the artifacts of both builds are identical.
+//!
+//! The builds are run by spawning the compiler again, without the flag:
+//!
+//! - the first build inherits the environment, and uses a single thread;
+//! - the second build gets a copy of the environment, which is passed in
+//!   sorted order rather than in the order of the environment of this process,
+//!   with its own temporary directory, and uses as many threads as there are
+//!   CPUs.
+//!
+//! `-Z threads` only has an effect in parallel compilers, so both builds are
+//! single-threaded otherwise. The number of codegen units isn't varied, since
+//! it determines how the crate is split into object files.
+//!
+//! Both builds write their artifacts to their own temporary output directory,
+//! which is remapped to the actual output directory with
+//! `--remap-path-prefix`. If the artifacts are identical, those of the first
+//! build are copied to the actual output directory. Otherwise, the first
+//! difference is reported, down to the archive member, ELF section and symbol
+//! when possible.
+
+use rustc::session::config::{self, ErrorOutputType};
+use rustc::session::early_error;
+use rustc::util::common::ErrorReported;
+use rustc_interface::interface;
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str;
+
+use crate::getopts;
+
+struct Build {
+    /// The value of `-Z threads`, which is ignored by non-parallel compilers.
+    threads: usize,
+    /// Whether the build gets its own temporary directory, and a copy of the
+    /// environment, in sorted order.
+    isolated: bool,
+}
+
+pub fn verify_reproducible(
+    args: &[String],
+    sopts: &config::Options,
+    matches: &getopts::Matches,
+) -> interface::Result<()> {
+    let error_format = sopts.error_format;
+    if matches.opt_present("o") {
+        early_error(error_format, "`-Z verify-reproducible` can't be used with `-o`, \
+                                   use `--out-dir` instead");
+    }
+    if sopts.incremental.is_some() {
+        early_error(error_format, "`-Z verify-reproducible` can't be used with \
+                                   incremental compilation");
+    }
+    // Only the first build could read the crate from stdin.
+    if matches.free.iter().any(|input| input == "-") {
+        early_error(error_format, "`-Z verify-reproducible` can't read the crate from stdin");
+    }
+
+    let out_dir = matches.opt_str("out-dir").map_or_else(|| PathBuf::from("."), PathBuf::from);
+    let rustc = env::current_exe().unwrap_or_else(|e| {
+        early_error(error_format, &format!("couldn't find the path of the compiler: {}", e))
+    });
+    let args = build_args(&args[1..]);
+
+    let threads = if cfg!(parallel_compiler) { num_cpus::get().max(2) } else { 1 };
+    let builds = [
+        Build { threads: 1, isolated: false },
+        Build { threads, isolated: true },
+    ];
+    let mut out_dirs = Vec::new();
+    for (i, build) in builds.iter().enumerate() {
+        let dir = tempfile::Builder::new().prefix("rustc-verify-reproducible").tempdir()
+            .unwrap_or_else(|e| {
+                early_error(error_format,
+                            &format!("couldn't create a temporary directory: {}", e))
+            });
+        let build_out_dir = dir.path().join("out");
+        let mut remap = build_out_dir.clone().into_os_string();
+        remap.push("=");
+        remap.push(&out_dir);
+
+        let mut cmd = Command::new(&rustc);
+        cmd.args(&args)
+           .arg("--out-dir").arg(&build_out_dir)
+           .arg("--remap-path-prefix").arg(remap)
+           .arg(format!("-Zthreads={}", build.threads));
+        if build.isolated {
+            // Changing a variable makes `Command` pass the environment in
+            // sorted order.
+            let tmp_dir = dir.path().join("tmp");
+            create_dir(&tmp_dir, error_format);
+            cmd.env("TMPDIR", &tmp_dir).env("TMP", &tmp_dir).env("TEMP", &tmp_dir);
+        }
+
+        // Only the diagnostics of the first build are shown, the second build
+        // should emit the same ones.
+        let status = if i == 0 {
+            cmd.status()
+        } else {
+            cmd.stdout(Stdio::null()).stderr(Stdio::piped()).output().map(|output| {
+                if !output.status.success() {
+                    eprint!("{}", String::from_utf8_lossy(&output.stderr));
+                }
+                output.status
+            })
+        };
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(_) if i == 0 => return Err(ErrorReported),
+            Ok(_) => {
+                early_error(error_format, "the build is not reproducible: the second build \
+                                           failed but the first one succeeded");
+            }
+            Err(e) => {
+                early_error(error_format, &format!("couldn't run `{}`: {}", rustc.display(), e))
+            }
+        }
+        out_dirs.push((dir, build_out_dir));
+    }
+
+    let (_, ref first_out_dir) = out_dirs[0];
+    let (_, ref second_out_dir) = out_dirs[1];
+    let first_files = list_files(first_out_dir, error_format);
+    let second_files = list_files(second_out_dir, error_format);
+    if first_files != second_files {
+        let file = first_files.iter().find(|file| !second_files.contains(file))
+            .or_else(|| second_files.iter().find(|file| !first_files.contains(file)))
+            .unwrap();
+        early_error(error_format, &format!("the build is not reproducible: `{}` was only \
+                                            emitted by one of the builds", file.display()));
+    }
+
+    create_dir(&out_dir, error_format);
+    for file in &first_files {
+        let first = read_artifact(first_out_dir, file, &out_dir, error_format);
+        let second = read_artifact(second_out_dir, file, &out_dir, error_format);
+        if first != second {
+            early_error(error_format, &format!("the build is not reproducible: `{}` differs, {}",
+                                               file.display(),
+                                               describe_difference(&first, &second)));
+        }
+    }
+    for file in &first_files {
+        let contents = read_artifact(first_out_dir, file, &out_dir, error_format);
+        let path = out_dir.join(file);
+        if let Some(parent) = path.parent() {
+            create_dir(parent, error_format);
+        }
+        if let Err(e) = fs::write(&path, contents) {
+            early_error(error_format, &format!("couldn't write `{}`: {}", path.display(), e));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the arguments of the builds: the arguments of this compiler, without
+/// `-Z verify-reproducible` and `--out-dir`.
+fn build_args(args: &[String]) -> Vec<String> {
+    let mut build_args = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "-Zverify-reproducible" => {}
+            "-Z" => match args.next() {
+                Some(option) if option == "verify-reproducible" => {}
+                option => {
+                    build_args.push(arg.clone());
+                    build_args.extend(option.cloned());
+                }
+            },
+            "--out-dir" => {
+                args.next();
+            }
+            _ if arg.starts_with("--out-dir=") => {}
+            _ => build_args.push(arg.clone()),
+        }
+    }
+    build_args
+}
+
+fn create_dir(path: &Path, error_format: ErrorOutputType) {
+    if let Err(e) = fs::create_dir_all(path) {
+        early_error(error_format, &format!("couldn't create `{}`: {}", path.display(), e));
+    }
+}
+
+/// Returns the paths of the files in `dir` and its subdirectories, relative to
+/// `dir`, in order.
+fn list_files(dir: &Path, error_format: ErrorOutputType) -> Vec<PathBuf> {
+    fn visit(dir: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let relative = relative.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                visit(&entry.path(), &relative, files)?;
+            } else {
+                files.push(relative);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    if let Err(e) = visit(dir, Path::new(""), &mut files) {
+        early_error(error_format, &format!("couldn't read `{}`: {}", dir.display(), e));
+    }
+    files.sort();
+    files
+}
+
+/// Reads an artifact of a build. Dep-info files name the output files, so the
+/// output directory of the build is replaced with the actual one in those.
+fn read_artifact(
+    build_out_dir: &Path,
+    file: &Path,
+    out_dir: &Path,
+    error_format: ErrorOutputType,
+) -> Vec<u8> {
+    let path = build_out_dir.join(file);
+    let contents = fs::read(&path).unwrap_or_else(|e| {
+        early_error(error_format, &format!("couldn't read `{}`: {}", path.display(), e))
+    });
+    match (file.extension().and_then(OsStr::to_str), str::from_utf8(&contents)) {
+        (Some("d"), Ok(dep_info)) => {
+            let build_out_dir = build_out_dir.to_string_lossy();
+            dep_info.replace(&*build_out_dir, &out_dir.to_string_lossy()).into_bytes()
+        }
+        _ => contents,
+    }
+}
+
+/// Describes where the first difference between two artifacts is.
+fn describe_difference(first: &[u8], second: &[u8]) -> String {
+    if let (Some(first_members), Some(second_members)) =
+        (archive_members(first), archive_members(second))
+    {
+        for (i, (first, second)) in first_members.iter().zip(&second_members).enumerate() {
+            if first.0 != second.0 {
+                return format!("the archive member {} is `{}` in the first build and `{}` in \
+                                the second one", i, first.0, second.0);
+            }
+            if first.1 != second.1 {
+                return format!("in the archive member `{}`, {}",
+                               first.0, describe_difference(first.1, second.1));
+            }
+        }
+        if first_members.len() != second_members.len() {
+            return "the archives have different numbers of members".to_string();
+        }
+    }
+
+    if let (Some(first), Some(second)) = (Elf::parse(first), Elf::parse(second)) {
+        if let Some(difference) = first.describe_difference(&second) {
+            return difference;
+        }
+    }
+    let offset = first_difference(first, second);
+    format!("first at byte {}", offset)
+}
+
+fn first_difference(first: &[u8], second: &[u8]) -> usize {
+    first.iter().zip(second).position(|(a, b)| a != b)
+        .unwrap_or_else(|| first.len().min(second.len()))
+}
+
+/// Returns the names and contents of the members of an `ar` archive, in the
+/// GNU or BSD format, like rlibs and static libraries.
+fn archive_members(data: &[u8]) -> Option<Vec<(String, &[u8])>> {
+    const MAGIC: &[u8] = b"!<arch>\n";
+    const HEADER_LEN: usize = 60;
+
+    if !data.starts_with(MAGIC) {
+        return None;
+    }
+    let field = |header: &[u8], range: std::ops::Range<usize>| {
+        str::from_utf8(&header[range]).ok().map(|field| field.trim_end().to_string())
+    };
+
+    let mut members = Vec::new();
+    let mut long_names: &[u8] = &[];
+    let mut offset = MAGIC.len();
+    while offset < data.len() {
+        let header = data.get(offset..offset + HEADER_LEN)?;
+        let mut name = field(header, 0..16)?;
+        let size: usize = field(header, 48..58)?.parse().ok()?;
+        let mut contents = data.get(offset + HEADER_LEN..offset + HEADER_LEN + size)?;
+        offset += HEADER_LEN + size + size % 2;
+
+        if name.starts_with("#1/") {
+            // BSD: the name is at the start of the contents.
+            let len: usize = name[3..].parse().ok()?;
+            name = str::from_utf8(contents.get(..len)?).ok()?.trim_end_matches('\0').to_string();
+            contents = &contents[len..];
+        } else if name == "//" {
+            // GNU: the table of the names longer than 15 bytes.
+            long_names = contents;
+            continue;
+        } else if name == "/" || name == "/SYM64/" || name == "__.SYMDEF" {
+            name = "<symbol table>".to_string();
+        } else if name.starts_with('/') {
+            let start: usize = name[1..].parse().ok()?;
+            let long_name = long_names.get(start..)?;
+            let end = long_name.iter().position(|&b| b == b'\n').unwrap_or(long_name.len());
+            name = str::from_utf8(&long_name[..end]).ok()?.trim_end_matches('/').to_string();
+        } else {
+            name = name.trim_end_matches('/').to_string();
+        }
+        members.push((name, contents));
+    }
+    Some(members)
+}
+
+/// A minimal reader of the sections and symbols of ELF files.
+struct Elf<'a> {
+    data: &'a [u8],
+    is_64: bool,
+    is_little_endian: bool,
+}
+
+struct ElfSection {
+    name: u32,
+    kind: u32,
+    addr: u64,
+    offset: u64,
+    size: u64,
+    link: u32,
+}
+
+const SHT_SYMTAB: u32 = 2;
+const SHT_NOBITS: u32 = 8;
+
+impl<'a> Elf<'a> {
+    fn parse(data: &'a [u8]) -> Option<Elf<'a>> {
+        if !data.starts_with(b"\x7fELF") {
+            return None;
+        }
+        Some(Elf {
+            data,
+            is_64: *data.get(4)? == 2,
+            is_little_endian: *data.get(5)? == 1,
+        })
+    }
+
+    fn read(&self, offset: u64, len: usize) -> Option<u64> {
+        let start = offset as usize;
+        let bytes = self.data.get(start..start.checked_add(len)?)?;
+        let fold = |value: u64, &byte: &u8| value << 8 | u64::from(byte);
+        Some(if self.is_little_endian {
+            bytes.iter().rev().fold(0, fold)
+        } else {
+            bytes.iter().fold(0, fold)
+        })
+    }
+
+    /// Reads an address-sized value.
+    fn read_addr(&self, offset: u64) -> Option<u64> {
+        self.read(offset, if self.is_64 { 8 } else { 4 })
+    }
+
+    fn sections(&self) -> Option<Vec<ElfSection>> {
+        let (shoff, shentsize, shnum) = if self.is_64 {
+            (self.read(0x28, 8)?, self.read(0x3a, 2)?, self.read(0x3c, 2)?)
+        } else {
+            (self.read(0x20, 4)?, self.read(0x2e, 2)?, self.read(0x30, 2)?)
+        };
+        (0..shnum).map(|i| {
+            let header = shoff + i * shentsize;
+            let (addr, offset, size, link) = if self.is_64 {
+                (header + 16, header + 24, header + 32, header + 40)
+            } else {
+                (header + 12, header + 16, header + 20, header + 24)
+            };
+            Some(ElfSection {
+                name: self.read(header, 4)? as u32,
+                kind: self.read(header + 4, 4)? as u32,
+                addr: self.read_addr(addr)?,
+                offset: self.read_addr(offset)?,
+                size: self.read_addr(size)?,
+                link: self.read(link, 4)? as u32,
+            })
+        }).collect()
+    }
+
+    fn string(&self, table: &ElfSection, index: u32) -> Option<&'a str> {
+        let start = table.offset.checked_add(index as u64)? as usize;
+        let bytes = self.data.get(start..)?;
+        let end = bytes.iter().position(|&b| b == 0)?;
+        str::from_utf8(&bytes[..end]).ok()
+    }
+
+    fn section_names(&self, sections: &[ElfSection]) -> Option<Vec<&'a str>> {
+        let shstrndx = if self.is_64 { self.read(0x3e, 2)? } else { self.read(0x32, 2)? };
+        let names = sections.get(shstrndx as usize)?;
+        sections.iter().map(|section| self.string(names, section.name)).collect()
+    }
+
+    fn contents(&self, section: &ElfSection) -> &'a [u8] {
+        if section.kind == SHT_NOBITS {
+            return &[];
+        }
+        let start = section.offset as usize;
+        self.data.get(start..start.saturating_add(section.size as usize)).unwrap_or(&[])
+    }
+
+    /// Describes the first section which differs in `other`, and the symbol
+    /// in which the sections differ, if any.
+    fn describe_difference(&self, other: &Elf<'_>) -> Option<String> {
+        let sections = self.sections()?;
+        let other_sections = other.sections()?;
+        let names = self.section_names(&sections)?;
+        let other_names = other.section_names(&other_sections)?;
+
+        for (index, section) in sections.iter().enumerate() {
+            let other_section = match other_sections.get(index) {
+                Some(other_section) => other_section,
+                None => return Some(format!("the section `{}` is missing", names[index])),
+            };
+            if names[index] != other_names[index] {
+                return Some(format!("the section {} is `{}` in the first build and `{}` in \
+                                     the second one", index, names[index], other_names[index]));
+            }
+            let contents = self.contents(section);
+            let other_contents = other.contents(other_section);
+            if section.addr == other_section.addr && contents == other_contents {
+                continue;
+            }
+
+            let offset = first_difference(contents, other_contents);
+            return Some(match self.symbol_at(&sections, index, offset as u64) {
+                Some(symbol) => format!("in the section `{}`, in the symbol `{}`, at byte {}",
+                                        names[index], symbol, offset),
+                None => format!("in the section `{}`, at byte {}", names[index], offset),
+            });
+        }
+        if other_sections.len() > sections.len() {
+            return Some(format!("the section `{}` is extra", other_names[sections.len()]));
+        }
+        None
+    }
+
+    /// Returns the name of the symbol defined at the given offset of the
+    /// section at `index`.
+    fn symbol_at(&self, sections: &[ElfSection], index: usize, offset: u64) -> Option<&'a str> {
+        // The values of the symbols are offsets in their section in object
+        // files, and addresses in executables and shared libraries.
+        const ET_REL: u64 = 1;
+        let value = if self.read(0x10, 2)? == ET_REL {
+            offset
+        } else {
+            sections[index].addr + offset
+        };
+
+        let entry_len = if self.is_64 { 24 } else { 16 };
+        sections.iter().filter(|symtab| symtab.kind == SHT_SYMTAB).flat_map(|symtab| {
+            (0..symtab.size / entry_len).map(move |i| (symtab, symtab.offset + i * entry_len))
+        }).find_map(|(symtab, entry)| {
+            let (shndx, start, size) = if self.is_64 {
+                (self.read(entry + 6, 2)?, self.read(entry + 8, 8)?, self.read(entry + 16, 8)?)
+            } else {
+                (self.read(entry + 14, 2)?, self.read(entry + 4, 4)?, self.read(entry + 8, 4)?)
+            };
+            if shndx as usize != index || value < start || value >= start + size {
+                return None;
+            }
+            let strtab = sections.get(symtab.link as usize)?;
+            self.string(strtab, self.read(entry, 4)? as u32)
+        })
+    }
+}
@@ -188,6 +188,11 @@ pub trait Emitter {
     /// other formats can, and will, simply ignore it.
     fn emit_artifact_notification(&mut self, _path: &Path, _artifact_type: &str) {}
 
+    /// Writes out the diagnostics buffered by the emitter, once no more will be
+    /// emitted. This is only needed by the SARIF format, which writes a single
+    /// document.
+    fn flush_diagnostics(&mut self) {}
+
     /// Checks if should show explanations about "rustc --explain"
     fn should_show_explain(&self) -> bool {
         true
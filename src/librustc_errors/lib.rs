@@ -38,6 +38,7 @@ pub mod registry;
 mod styled_buffer;
 mod lock;
 pub mod json;
+pub mod sarif;
 pub use snippet::Style;
 
 pub type PResult<'a, T> = Result<T, DiagnosticBuilder<'a>>;
@@ -677,6 +678,12 @@ impl Handler {
         self.inner.borrow_mut().abort_if_errors()
     }
 
+    /// Writes out the diagnostics buffered by the emitter, see
+    /// `Emitter::flush_diagnostics`.
+    pub fn flush_diagnostics(&self) {
+        self.inner.borrow_mut().emitter.flush_diagnostics()
+    }
+
     pub fn abort_if_errors_and_should_abort(&self) {
         self.inner.borrow_mut().abort_if_errors_and_should_abort()
     }
@@ -0,0 +1,384 @@
+//! A SARIF emitter for errors.
+//!
+//! This emits the diagnostics as a [SARIF 2.1.0] log, the format consumed by
+//! static analysis and code scanning tools. Since a SARIF log is a single JSON
+//! document, the diagnostics are buffered until the log is written:
+//!
+//! * the emitters writing to stderr all share the same log, whatever their
+//!   handler, so that the early diagnostics, those of the session and those
+//!   of rustdoc end up in a single log, written by `flush_stderr_log` once
+//!   the driver is done;
+//! * the other emitters write their log when `flush_diagnostics` is called,
+//!   at the end of the session.
+//!
+//! Diagnostics are mapped to SARIF results as follows:
+//!
+//! * the error code or lint name becomes the rule of the result, and the
+//!   rules are described in the `tool.driver.rules` of the run;
+//! * primary spans become the locations of the result, and secondary spans,
+//!   as well as the spans of the children, become its related locations;
+//! * the messages of the children without a span are appended to the message
+//!   of the result;
+//! * suggestions become the fixes of the result.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use syntax_pos::source_map::{SourceMap, FilePathMapping};
+
+use crate::registry::Registry;
+use crate::{CodeSuggestion, DiagnosticId, Level, SubDiagnostic};
+use crate::emitter::Emitter;
+
+use syntax_pos::{MultiSpan, Span};
+use rustc_data_structures::sync::Lrc;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use lazy_static::lazy_static;
+use rustc_serialize::json::{Json, Object};
+
+#[cfg(test)]
+mod tests;
+
+const SARIF_SCHEMA: &str = "https://schemastore.azurewebsites.net/schemas/json/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const ERROR_INDEX_URI: &str = "https://doc.rust-lang.org/error-index.html";
+
+lazy_static! {
+    /// The log shared by the emitters writing to stderr, if there are any.
+    static ref STDERR_LOG: Mutex<Option<Arc<Mutex<SarifLog>>>> = Mutex::new(None);
+}
+
+/// Writes the log of the emitters writing to stderr, if there are any. Drivers
+/// call this once, after all the diagnostics were emitted.
+pub fn flush_stderr_log() -> io::Result<()> {
+    let log = STDERR_LOG.lock().unwrap().take();
+    match log {
+        Some(log) => log.lock().unwrap().write(&mut io::stderr()),
+        None => Ok(()),
+    }
+}
+
+/// The diagnostics of a SARIF log, until it is written.
+#[derive(Default)]
+struct SarifLog {
+    /// The rules of the results emitted so far, in order of appearance.
+    rules: Vec<Json>,
+    /// The indices of the rules in `rules`, keyed by their identifiers.
+    rule_indices: BTreeMap<String, usize>,
+    results: Vec<Json>,
+}
+
+pub struct SarifEmitter {
+    /// Where `flush_diagnostics` writes the log, or `None` if it is the shared
+    /// stderr log or if it was written already.
+    dst: Option<Box<dyn Write + Send>>,
+    registry: Option<Registry>,
+    sm: Lrc<SourceMap>,
+    log: Arc<Mutex<SarifLog>>,
+}
+
+impl SarifEmitter {
+    pub fn stderr(registry: Option<Registry>, source_map: Lrc<SourceMap>) -> SarifEmitter {
+        let log = STDERR_LOG.lock().unwrap().get_or_insert_with(Default::default).clone();
+        SarifEmitter {
+            dst: None,
+            registry,
+            sm: source_map,
+            log,
+        }
+    }
+
+    pub fn basic() -> SarifEmitter {
+        let file_path_mapping = FilePathMapping::empty();
+        SarifEmitter::stderr(None, Lrc::new(SourceMap::new(file_path_mapping)))
+    }
+
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst: Some(dst),
+            registry,
+            sm: source_map,
+            log: Default::default(),
+        }
+    }
+
+    /// Returns the index of the rule with the given identifier, describing it
+    /// the first time it is used.
+    fn rule_index(&self, log: &mut SarifLog, id: &DiagnosticId) -> usize {
+        let (name, is_lint) = match *id {
+            DiagnosticId::Error(ref name) => (name, false),
+            DiagnosticId::Lint(ref name) => (name, true),
+        };
+        if let Some(&index) = log.rule_indices.get(name) {
+            return index;
+        }
+
+        let mut rule = object(vec![("id", Json::String(name.clone()))]);
+        if !is_lint {
+            rule.insert("helpUri".to_owned(), string(format!("{}#{}", ERROR_INDEX_URI, name)));
+            let explanation = self.registry.as_ref().and_then(|r| r.find_description(name));
+            if let Some(explanation) = explanation {
+                rule.insert("fullDescription".to_owned(), message(explanation.trim()));
+            }
+        }
+        let kind = if is_lint { "lint" } else { "error" };
+        rule.insert("properties".to_owned(), Json::Object(object(vec![
+            ("tags", Json::Array(vec![string(kind)])),
+        ])));
+
+        let index = log.rules.len();
+        log.rules.push(Json::Object(rule));
+        log.rule_indices.insert(name.clone(), index);
+        index
+    }
+
+    fn result(&self, log: &mut SarifLog, diag: &crate::Diagnostic) -> Json {
+        let mut result = Object::new();
+        if let Some(ref code) = diag.code {
+            let name = match *code {
+                DiagnosticId::Error(ref name) | DiagnosticId::Lint(ref name) => name.clone(),
+            };
+            result.insert("ruleIndex".to_owned(), Json::U64(self.rule_index(log, code) as u64));
+            result.insert("ruleId".to_owned(), Json::String(name));
+        }
+        result.insert("level".to_owned(), string(sarif_level(diag.level)));
+
+        // Children without a span of their own can only be part of the message.
+        let mut text = diag.message();
+        for child in diag.children.iter().filter(|child| child_span(child).is_none()) {
+            text.push_str(&format!("\n{}: {}", child.level, child.message()));
+        }
+        result.insert("message".to_owned(), message(&text));
+
+        let mut locations = Vec::new();
+        let mut related_locations = Vec::new();
+        for span_label in diag.span.span_labels() {
+            if let Some(location) = self.location(span_label.span, span_label.label.as_ref()) {
+                if span_label.is_primary {
+                    locations.push(location);
+                } else {
+                    related_locations.push(location);
+                }
+            }
+        }
+        for child in &diag.children {
+            if let Some(span) = child_span(child) {
+                let label = format!("{}: {}", child.level, child.message());
+                related_locations.extend(span.primary_spans().iter().filter_map(|&span| {
+                    self.location(span, Some(&label))
+                }));
+            }
+        }
+        // Related locations are referred to by their `id`, which must be unique.
+        for (id, location) in related_locations.iter_mut().enumerate() {
+            if let Json::Object(ref mut location) = *location {
+                location.insert("id".to_owned(), Json::U64(id as u64));
+            }
+        }
+        result.insert("locations".to_owned(), Json::Array(locations));
+        if !related_locations.is_empty() {
+            result.insert("relatedLocations".to_owned(), Json::Array(related_locations));
+        }
+
+        let fixes: Vec<_> = diag.suggestions.iter().flat_map(|sugg| self.fixes(sugg)).collect();
+        if !fixes.is_empty() {
+            result.insert("fixes".to_owned(), Json::Array(fixes));
+        }
+
+        Json::Object(result)
+    }
+
+    /// Creates the location of a span, or `None` for spans which don't point
+    /// to a source file.
+    fn location(&self, span: Span, label: Option<&String>) -> Option<Json> {
+        let physical_location = object(vec![
+            ("artifactLocation", self.artifact_location(span)?),
+            ("region", self.region(span)),
+        ]);
+        let mut location = object(vec![("physicalLocation", Json::Object(physical_location))]);
+        if let Some(label) = label {
+            location.insert("message".to_owned(), message(label));
+        }
+        Some(Json::Object(location))
+    }
+
+    fn artifact_location(&self, span: Span) -> Option<Json> {
+        if span.is_dummy() {
+            return None;
+        }
+        let file = self.sm.lookup_char_pos(span.lo()).file;
+        if !file.name.is_real() {
+            return None;
+        }
+        Some(Json::Object(object(vec![("uri", string(file_uri(&file.name.to_string())))])))
+    }
+
+    fn region(&self, span: Span) -> Json {
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        let mut region = object(vec![
+            ("startLine", Json::U64(start.line as u64)),
+            ("startColumn", Json::U64(start.col.0 as u64 + 1)),
+            ("endLine", Json::U64(end.line as u64)),
+            ("endColumn", Json::U64(end.col.0 as u64 + 1)),
+        ]);
+        if let Ok(snippet) = self.sm.span_to_snippet(span) {
+            region.insert("snippet".to_owned(), Json::Object(object(vec![
+                ("text", Json::String(snippet)),
+            ])));
+        }
+        Json::Object(region)
+    }
+
+    /// Creates a fix for each of the alternative substitutions of a suggestion.
+    fn fixes(&self, sugg: &CodeSuggestion) -> Vec<Json> {
+        sugg.substitutions.iter().filter_map(|substitution| {
+            // Replacements are grouped by the file they apply to.
+            let mut changes: Vec<(Json, Vec<Json>)> = Vec::new();
+            for part in &substitution.parts {
+                let artifact_location = self.artifact_location(part.span)?;
+                let replacement = Json::Object(object(vec![
+                    ("deletedRegion", self.region(part.span)),
+                    ("insertedContent", Json::Object(object(vec![
+                        ("text", Json::String(part.snippet.clone())),
+                    ]))),
+                ]));
+                match changes.iter_mut().find(|(location, _)| *location == artifact_location) {
+                    Some((_, replacements)) => replacements.push(replacement),
+                    None => changes.push((artifact_location, vec![replacement])),
+                }
+            }
+
+            let artifact_changes = changes.into_iter().map(|(location, replacements)| {
+                Json::Object(object(vec![
+                    ("artifactLocation", location),
+                    ("replacements", Json::Array(replacements)),
+                ]))
+            }).collect();
+            Some(Json::Object(object(vec![
+                ("description", message(&sugg.msg)),
+                ("artifactChanges", Json::Array(artifact_changes)),
+                ("properties", Json::Object(object(vec![
+                    ("applicability", string(format!("{:?}", sugg.applicability))),
+                ]))),
+            ])))
+        }).collect()
+    }
+}
+
+impl SarifLog {
+    /// Writes the log, leaving it empty.
+    fn write(&mut self, dst: &mut dyn Write) -> io::Result<()> {
+        writeln!(dst, "{}", self.to_json().pretty())?;
+        dst.flush()
+    }
+
+    fn to_json(&mut self) -> Json {
+        let mut driver = object(vec![
+            ("name", string("rustc")),
+            ("informationUri", string("https://www.rust-lang.org/")),
+            ("rules", Json::Array(std::mem::replace(&mut self.rules, Vec::new()))),
+        ]);
+        if let Some(version) = option_env!("CFG_VERSION") {
+            driver.insert("version".to_owned(), string(version));
+        }
+        self.rule_indices.clear();
+
+        let run = object(vec![
+            ("tool", Json::Object(object(vec![("driver", Json::Object(driver))]))),
+            // The columns of the regions are counted in characters.
+            ("columnKind", string("unicodeCodePoints")),
+            ("results", Json::Array(std::mem::replace(&mut self.results, Vec::new()))),
+        ]);
+        Json::Object(object(vec![
+            ("$schema", string(SARIF_SCHEMA)),
+            ("version", string(SARIF_VERSION)),
+            ("runs", Json::Array(vec![Json::Object(run)])),
+        ]))
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: &crate::Diagnostic) {
+        // Failure notes, like "aborting due to previous error", only make sense
+        // in the rendered output.
+        if diag.level == Level::FailureNote {
+            return;
+        }
+        let mut log = self.log.lock().unwrap();
+        let result = self.result(&mut log, diag);
+        log.results.push(result);
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+
+    fn flush_diagnostics(&mut self) {
+        if let Some(mut dst) = self.dst.take() {
+            if let Err(e) = self.log.lock().unwrap().write(&mut dst) {
+                // Not reported as a diagnostic, which would only go to the log.
+                let _ = writeln!(io::stderr(), "error: failed to write the SARIF log: {}", e);
+            }
+        }
+    }
+}
+
+/// The span of a child, if it has one.
+fn child_span(child: &SubDiagnostic) -> Option<&MultiSpan> {
+    let span = child.render_span.as_ref().unwrap_or(&child.span);
+    if span.primary_spans().is_empty() { None } else { Some(span) }
+}
+
+fn sarif_level(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::Fatal | Level::Error => "error",
+        Level::Warning => "warning",
+        Level::Note | Level::Help => "note",
+        Level::Cancelled | Level::FailureNote => "none",
+    }
+}
+
+/// Converts the name of a source file to a URI reference: relative paths stay
+/// relative to the working directory, and absolute ones become `file` URIs.
+fn file_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut uri = if path.starts_with('/') {
+        "file://".to_owned()
+    } else if path.as_bytes().get(1) == Some(&b':') {
+        // Windows paths with a drive letter.
+        "file:///".to_owned()
+    } else {
+        String::new()
+    };
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9'
+            | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn object(fields: Vec<(&str, Json)>) -> Object {
+    fields.into_iter().map(|(key, value)| (key.to_owned(), value)).collect()
+}
+
+fn string<S: Into<String>>(s: S) -> Json {
+    Json::String(s.into())
+}
+
+fn message(text: &str) -> Json {
+    Json::Object(object(vec![("text", string(text))]))
+}
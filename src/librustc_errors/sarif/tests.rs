@@ -0,0 +1,125 @@
+use super::*;
+
+use crate::{Applicability, Handler};
+use rustc_serialize::json::Json;
+use syntax_pos::source_map::{FilePathMapping, SourceMap};
+use syntax_pos::{BytePos, Span};
+
+use std::path::Path;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+fn with_default_globals(f: impl FnOnce()) {
+    let globals = syntax_pos::Globals::new(syntax_pos::edition::DEFAULT_EDITION);
+    syntax_pos::GLOBALS.set(&globals, || {
+        syntax_pos::GLOBALS.set(&globals, f)
+    })
+}
+
+/// Emits the diagnostics with the given handler, and returns the SARIF log.
+fn emit(code: &str, f: impl FnOnce(&Handler)) -> Json {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    with_default_globals(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+
+        let se = SarifEmitter::new(Box::new(Shared { data: output.clone() }), None, sm);
+        let handler = Handler::with_emitter(true, None, Box::new(se));
+        f(&handler);
+        handler.flush_diagnostics();
+    });
+
+    let bytes = output.lock().unwrap();
+    Json::from_str(str::from_utf8(&bytes).unwrap()).unwrap()
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+#[test]
+fn empty_log() {
+    let log = emit("", |_| {});
+    assert_eq!(log.find("version"), Some(&Json::String("2.1.0".to_owned())));
+    let results = log.find("runs").unwrap()[0].find("results").unwrap();
+    assert_eq!(results, &Json::Array(vec![]));
+}
+
+#[test]
+fn log_written_once() {
+    let log = emit("", |handler| {
+        handler.struct_warn("first").emit();
+        handler.flush_diagnostics();
+        handler.struct_warn("second").emit();
+    });
+    let results = log.find("runs").unwrap()[0].find("results").unwrap();
+    assert_eq!(results.as_array().map(Vec::len), Some(1));
+}
+
+#[test]
+fn result_locations() {
+    let log = emit("fn main() {\n    let x = 1;\n}\n", |handler| {
+        let mut diag = handler.struct_span_warn(span(20, 21), "unused variable: `x`");
+        diag.code(DiagnosticId::Lint("unused_variables".to_owned()));
+        diag.span_suggestion(
+            span(20, 21),
+            "consider prefixing with an underscore",
+            "_x".to_owned(),
+            Applicability::MachineApplicable,
+        );
+        diag.note("`#[warn(unused_variables)]` on by default");
+        diag.emit();
+    });
+
+    let run = &log.find("runs").unwrap()[0];
+    let rules = run.find_path(&["tool", "driver", "rules"]).unwrap();
+    assert_eq!(rules[0].find("id").and_then(Json::as_string), Some("unused_variables"));
+
+    let result = &run.find("results").unwrap()[0];
+    assert_eq!(result.find("ruleId").and_then(Json::as_string), Some("unused_variables"));
+    assert_eq!(result.find("ruleIndex").and_then(Json::as_u64), Some(0));
+    assert_eq!(result.find("level").and_then(Json::as_string), Some("warning"));
+    assert_eq!(
+        result.find_path(&["message", "text"]).and_then(Json::as_string),
+        Some("unused variable: `x`\nnote: `#[warn(unused_variables)]` on by default"),
+    );
+
+    let location = &result.find("locations").unwrap()[0];
+    let region = location.find_path(&["physicalLocation", "region"]).unwrap();
+    assert_eq!(region.find("startLine").and_then(Json::as_u64), Some(2));
+    assert_eq!(region.find("startColumn").and_then(Json::as_u64), Some(9));
+    assert_eq!(region.find("endColumn").and_then(Json::as_u64), Some(10));
+    assert_eq!(
+        location.find_path(&["physicalLocation", "artifactLocation", "uri"])
+            .and_then(Json::as_string),
+        Some("test.rs"),
+    );
+
+    let fix = &result.find("fixes").unwrap()[0];
+    let replacement = &fix.find("artifactChanges").unwrap()[0].find("replacements").unwrap()[0];
+    assert_eq!(
+        replacement.find_path(&["insertedContent", "text"]).and_then(Json::as_string),
+        Some("_x"),
+    );
+}
+
+#[test]
+fn file_uris() {
+    assert_eq!(file_uri("src/main.rs"), "src/main.rs");
+    assert_eq!(file_uri("/home/user/my crate/lib.rs"), "file:///home/user/my%20crate/lib.rs");
+    assert_eq!(file_uri(r"C:\src\lib.rs"), "file:///C:/src/lib.rs");
+}
@@ -174,9 +174,19 @@ pub fn run_compiler_in_existing_thread_pool<R>(
 
     let _sess_abort_error = OnDrop(|| {
         compiler.sess.diagnostic().print_error_count(registry);
+        compiler.sess.diagnostic().flush_diagnostics();
     });
 
-    f(&compiler)
+    let ret = f(&compiler);
+
+    // Written last, so that the report includes linking.
+    if compiler.sess.time_report() {
+        if let Err(e) = compiler.sess.time_report.write() {
+            compiler.sess.warn(&format!("failed to write time report: {}", e));
+        }
+    }
+
+    ret
 }
 
 pub fn run_compiler<R: Send>(mut config: Config, f: impl FnOnce(&Compiler) -> R + Send) -> R {
@@ -13,6 +13,7 @@ use rustc::middle::{self, reachable, resolve_lifetime, stability};
 use rustc::middle::cstore::{CrateStore, MetadataLoader, MetadataLoaderDyn};
 use rustc::ty::{self, AllArenas, ResolverOutputs, TyCtxt, GlobalCtxt};
 use rustc::ty::steal::Steal;
+use rustc::ty::query::QueryStats;
 use rustc::traits;
 use rustc::util::common::{time, ErrorReported};
 use rustc::session::Session;
@@ -740,6 +741,10 @@ impl<'tcx> QueryContext<'tcx> {
     pub fn print_stats(&self) {
         self.0.queries.print_stats()
     }
+
+    pub fn query_stats(&self) -> Vec<QueryStats> {
+        self.0.queries.query_stats()
+    }
 }
 
 pub fn create_global_ctxt<'tcx>(
@@ -1008,5 +1013,12 @@ pub fn start_codegen<'tcx>(
         }
     }
 
+    if tcx.sess.opts.output_types.contains_key(&OutputType::MirJson) {
+        if let Err(e) = mir::transform::dump_mir::emit_mir_json(tcx, outputs) {
+            tcx.sess.err(&format!("could not emit MIR as JSON: {}", e));
+            tcx.sess.abort_if_errors();
+        }
+    }
+
     codegen
 }
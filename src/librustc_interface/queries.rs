@@ -245,9 +245,15 @@ impl<'tcx> Queries<'tcx> {
             let (krate, boxed_resolver, _) = &*expansion_result.peek();
             let crate_name = self.crate_name()?;
             let crate_name = crate_name.peek();
-            passes::prepare_outputs(
+            let outputs = passes::prepare_outputs(
                 self.session(), self.compiler, &krate, &boxed_resolver, &crate_name
-            )
+            )?;
+            // Recorded now, since the linker takes the outputs away.
+            if self.session().time_report() {
+                let path = outputs.with_extension("time-report.json");
+                self.session().time_report.set_output(path, (*crate_name).clone());
+            }
+            Ok(outputs)
         })
     }
 
@@ -340,6 +346,20 @@ impl Compiler {
             }
         }
 
+        if self.session().time_report() {
+            let report = &self.session().time_report;
+            if let Ok(gcx) = queries.global_ctxt() {
+                for q in gcx.peek().query_stats() {
+                    let cache_hits = if cfg!(debug_assertions) {
+                        Some(q.cache_hits)
+                    } else {
+                        None
+                    };
+                    report.record_query(q.name, q.entry_count, cache_hits);
+                }
+            }
+        }
+
         ret
     }
 
@@ -94,6 +94,95 @@ fn dump_crates(cstore: &CStore) {
     });
 }
 
+/// A crate of the output of `--print crate-graph`.
+#[derive(RustcEncodable)]
+struct CrateNode {
+    cnum: u32,
+    name: String,
+    hash: String,
+    /// How the crate is depended upon, `None` for the local crate.
+    dep_kind: Option<&'static str>,
+    proc_macro: bool,
+    dylib: Option<String>,
+    rlib: Option<String>,
+    rmeta: Option<String>,
+}
+
+#[derive(RustcEncodable)]
+struct CrateEdge {
+    from: u32,
+    to: u32,
+}
+
+#[derive(RustcEncodable)]
+struct CrateGraph {
+    version: u32,
+    crates: Vec<CrateNode>,
+    edges: Vec<CrateEdge>,
+}
+
+/// The `version` of the output of `--print crate-graph`.
+const CRATE_GRAPH_VERSION: u32 = 1;
+
+/// Prints every crate loaded while compiling the local crate, and the
+/// dependencies between them, as JSON for `--print crate-graph`.
+pub fn print_crate_graph(tcx: TyCtxt<'_>) {
+    let cstore = CStore::from_tcx(tcx);
+    let local_is_proc_macro =
+        tcx.sess.crate_types.borrow().contains(&config::CrateType::ProcMacro);
+    let mut crates = vec![CrateNode {
+        cnum: LOCAL_CRATE.as_u32(),
+        name: tcx.crate_name(LOCAL_CRATE).to_string(),
+        hash: tcx.crate_hash(LOCAL_CRATE).to_string(),
+        dep_kind: None,
+        proc_macro: local_is_proc_macro,
+        dylib: None,
+        rlib: None,
+        rmeta: None,
+    }];
+    let mut edges = Vec::new();
+
+    cstore.iter_crate_data(|cnum, data| {
+        let path = |path: &Option<(std::path::PathBuf, PathKind)>| {
+            path.as_ref().map(|(path, _)| path.display().to_string())
+        };
+        let source = data.source();
+        crates.push(CrateNode {
+            cnum: cnum.as_u32(),
+            name: data.name().to_string(),
+            hash: data.hash().to_string(),
+            dep_kind: Some(match data.dep_kind() {
+                DepKind::UnexportedMacrosOnly => "unexported-macros-only",
+                DepKind::MacrosOnly => "macros-only",
+                DepKind::Implicit => "implicit",
+                DepKind::Explicit => "explicit",
+            }),
+            proc_macro: data.is_proc_macro_crate(),
+            dylib: path(&source.dylib),
+            rlib: path(&source.rlib),
+            rmeta: path(&source.rmeta),
+        });
+
+        // Crates without an `extern crate` were injected on behalf of the
+        // local crate, e.g., the panic runtime.
+        if data.extern_crate().map_or(true, |extern_crate| extern_crate.is_direct()) {
+            edges.push(CrateEdge { from: LOCAL_CRATE.as_u32(), to: cnum.as_u32() });
+        }
+        // The dependencies of a crate map the holes in its crate numbers to
+        // the crate itself.
+        let mut deps: Vec<_> = data.dependencies().iter()
+            .filter(|&&dep| dep != cnum)
+            .map(|dep| dep.as_u32())
+            .collect();
+        deps.sort();
+        deps.dedup();
+        edges.extend(deps.into_iter().map(|dep| CrateEdge { from: cnum.as_u32(), to: dep }));
+    });
+
+    let graph = CrateGraph { version: CRATE_GRAPH_VERSION, crates, edges };
+    println!("{}", rustc_serialize::json::as_pretty_json(&graph));
+}
+
 impl CStore {
     crate fn from_tcx(tcx: TyCtxt<'_>) -> &CStore {
         tcx.cstore_as_any().downcast_ref::<CStore>().expect("`tcx.cstore` is not a `CStore`")
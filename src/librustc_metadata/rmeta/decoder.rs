@@ -1542,6 +1542,10 @@ impl<'a, 'tcx> CrateMetadata {
         self.dependencies.borrow_mut().push(cnum);
     }
 
+    crate fn extern_crate(&self) -> Option<ExternCrate> {
+        *self.extern_crate.lock()
+    }
+
     crate fn update_extern_crate(&self, new_extern_crate: ExternCrate) -> bool {
         let mut extern_crate = self.extern_crate.borrow_mut();
         let update = Some(new_extern_crate.rank()) > extern_crate.as_ref().map(ExternCrate::rank);
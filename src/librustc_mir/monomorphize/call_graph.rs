@@ -0,0 +1,592 @@
+//! The call graph of the functions monomorphized in a crate, as written by
+//! `-Z dump-call-graph`.
+//!
+//! Unlike the graph built by the mono item collector, which only records that
+//! an item references another, this records the calls made by each function
+//! and how they are made: directly, through a vtable, or through a function
+//! pointer. Drop glue and the implicit calls to the panic entry points made by
+//! `Assert` terminators are included, and calls to the panic entry points are
+//! marked as such.
+//!
+//! Only the functions monomorphized in the local crate have their calls
+//! recorded; the functions they call in upstream crates are leaves. The
+//! functions of `core` and `alloc` which never return, such as
+//! `core::option::expect_failed`, are considered to be panic entry points.
+//!
+//! The graph is also used by the `panic_reachable` lint, which reports the
+//! functions from which a panic is reachable, with the chain of calls to it.
+
+use rustc::middle::lang_items::{PanicBoundsCheckFnLangItem, PanicFnLangItem};
+use rustc::mir::{self, Location};
+use rustc::mir::interpret::PanicInfo;
+use rustc::mir::mono::MonoItem;
+use rustc::mir::visit::Visitor as MirVisitor;
+use rustc::hir::def::DefKind;
+use rustc::hir::def_id::{DefId, LOCAL_CRATE};
+use rustc::lint::{self, builtin::PANIC_REACHABLE};
+use rustc::session::{CallTarget, FunctionCalls};
+use rustc::ty::subst::SubstsRef;
+use rustc::ty::{self, Instance, Ty, TyCtxt, TypeFoldable};
+use rustc::util::nodemap::{FxHashMap, FxHashSet};
+use syntax::symbol::sym;
+use syntax_pos::Span;
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// The `version` of the JSON call graph, see `-Z dump-call-graph`.
+const CALL_GRAPH_VERSION: u32 = 1;
+
+/// What a call is made to.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Callee<'tcx> {
+    /// A function, a shim, or a method called through a vtable, in which
+    /// case the instance is an `InstanceDef::Virtual`.
+    Instance(Instance<'tcx>),
+    /// Any function with the given function pointer type.
+    FnPtr(Ty<'tcx>),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum CallKind {
+    /// A call to a function known at compile time.
+    Direct,
+    /// A call to the drop glue of a value.
+    Drop,
+    /// A call through the vtable of a trait object.
+    Virtual,
+    /// A call through a function pointer.
+    FnPtr,
+}
+
+impl CallKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            CallKind::Direct => "direct",
+            CallKind::Drop => "drop",
+            CallKind::Virtual => "virtual",
+            CallKind::FnPtr => "fn-ptr",
+        }
+    }
+}
+
+pub struct CallNode<'tcx> {
+    pub callee: Callee<'tcx>,
+    /// Whether this is monomorphized in the local crate, and so has its calls
+    /// recorded.
+    pub local: bool,
+    /// Whether this is one of the entry points of panics.
+    pub panic: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CallEdge {
+    pub caller: usize,
+    pub callee: usize,
+    pub kind: CallKind,
+}
+
+pub struct CallGraph<'tcx> {
+    pub nodes: Vec<CallNode<'tcx>>,
+    /// The calls between the nodes, by their index in `nodes`. Several calls
+    /// from a function to another are recorded once.
+    pub edges: Vec<CallEdge>,
+    /// The first call site of each edge.
+    call_sites: FxHashMap<CallEdge, Span>,
+    indices: FxHashMap<Callee<'tcx>, usize>,
+}
+
+impl<'tcx> CallGraph<'tcx> {
+    /// Builds the call graph of the functions among `items`.
+    pub fn new(tcx: TyCtxt<'tcx>, items: &FxHashSet<MonoItem<'tcx>>) -> CallGraph<'tcx> {
+        let mut instances: Vec<_> = items.iter().filter_map(|item| match *item {
+            MonoItem::Fn(instance) => Some(instance),
+            MonoItem::Static(..) | MonoItem::GlobalAsm(..) => None,
+        }).collect();
+        // Don't let the numbering of the nodes depend on the order of the set.
+        instances.sort_by_cached_key(|&instance| tcx.symbol_name(instance).name.to_string());
+
+        let mut graph = CallGraph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            call_sites: FxHashMap::default(),
+            indices: FxHashMap::default(),
+        };
+        for &instance in &instances {
+            let node = graph.node(tcx, Callee::Instance(instance));
+            graph.nodes[node].local = true;
+        }
+
+        for instance in instances {
+            let caller = graph.indices[&Callee::Instance(instance)];
+            let body = tcx.instance_mir(instance.def);
+            let mut collector = CallCollector {
+                tcx,
+                body,
+                param_substs: instance.substs,
+                calls: Vec::new(),
+            };
+            collector.visit_body(body);
+
+            for (callee, kind, span) in collector.calls {
+                let edge = CallEdge { caller, callee: graph.node(tcx, callee), kind };
+                if !graph.call_sites.contains_key(&edge) {
+                    graph.call_sites.insert(edge, span);
+                    graph.edges.push(edge);
+                }
+            }
+        }
+
+        for node in &mut graph.nodes {
+            if let Callee::Instance(Instance { def: ty::InstanceDef::Item(def_id), .. }) =
+                node.callee
+            {
+                node.panic |= !node.local && is_diverging_core_function(tcx, def_id);
+            }
+        }
+
+        graph
+    }
+
+    fn node(&mut self, tcx: TyCtxt<'tcx>, callee: Callee<'tcx>) -> usize {
+        if let Some(&index) = self.indices.get(&callee) {
+            return index;
+        }
+        let panic = match callee {
+            Callee::Instance(instance) => is_panic_entry_point(tcx, instance.def_id()),
+            Callee::FnPtr(_) => false,
+        };
+        let index = self.nodes.len();
+        self.nodes.push(CallNode { callee, local: false, panic });
+        self.indices.insert(callee, index);
+        index
+    }
+
+    pub fn node_name(&self, tcx: TyCtxt<'tcx>, node: usize) -> String {
+        match self.nodes[node].callee {
+            Callee::Instance(instance) => match instance.def {
+                ty::InstanceDef::Virtual(def_id, _) => {
+                    tcx.def_path_str_with_substs(def_id, instance.substs)
+                }
+                _ => instance.to_string(),
+            },
+            Callee::FnPtr(ty) => ty.to_string(),
+        }
+    }
+
+    /// The symbol of a node, for functions which are not called through a
+    /// vtable or a function pointer.
+    pub fn node_symbol(&self, tcx: TyCtxt<'tcx>, node: usize) -> Option<String> {
+        match self.nodes[node].callee {
+            Callee::Instance(instance) => match instance.def {
+                ty::InstanceDef::Virtual(..) => None,
+                _ => Some(tcx.symbol_name(instance).name.to_string()),
+            },
+            Callee::FnPtr(_) => None,
+        }
+    }
+
+    fn node_kind(&self, node: usize) -> &'static str {
+        match self.nodes[node].callee {
+            Callee::Instance(Instance { def: ty::InstanceDef::Virtual(..), .. }) => "virtual",
+            Callee::Instance(_) => "fn",
+            Callee::FnPtr(_) => "fn-ptr",
+        }
+    }
+
+    /// Writes the graph to `path`, with the `dot` extension for the GraphViz
+    /// version and `json` for the JSON one.
+    pub fn dump(&self, tcx: TyCtxt<'tcx>, path: &Path) {
+        let result = self.write_graphviz(tcx, &path.with_extension("dot"))
+            .and_then(|()| self.write_json(tcx, &path.with_extension("json")));
+        if let Err(e) = result {
+            tcx.sess.err(&format!("could not write call graph to `{}`: {}", path.display(), e));
+        }
+    }
+
+    /// Records the graph for `-Z print-stack-usage`, along with the entry
+    /// points of the crate: its entry function and the functions it exports.
+    pub fn record_stack_usage(&self, tcx: TyCtxt<'tcx>) {
+        let mut calls = vec![Vec::new(); self.nodes.len()];
+        for edge in &self.edges {
+            let target = match (edge.kind, self.node_symbol(tcx, edge.callee)) {
+                (CallKind::Direct, Some(symbol)) | (CallKind::Drop, Some(symbol)) => {
+                    CallTarget::Function(symbol)
+                }
+                (CallKind::FnPtr, _) => CallTarget::Unknown(format!(
+                    "call through a `{}` pointer",
+                    self.node_name(tcx, edge.callee),
+                )),
+                _ => CallTarget::Unknown(format!(
+                    "virtual call to `{}`",
+                    self.node_name(tcx, edge.callee),
+                )),
+            };
+            if !calls[edge.caller].contains(&target) {
+                calls[edge.caller].push(target);
+            }
+        }
+
+        let entry_fn = tcx.entry_fn(LOCAL_CRATE).map(|(def_id, _)| def_id);
+        let exported = tcx.reachable_non_generics(LOCAL_CRATE);
+        let stack_usage = &tcx.sess.stack_usage;
+        for (node, calls) in calls.into_iter().enumerate() {
+            let symbol = match self.node_symbol(tcx, node) {
+                Some(symbol) => symbol,
+                None => continue,
+            };
+            let name = self.node_name(tcx, node);
+            if !self.nodes[node].local {
+                stack_usage.record_name(symbol, name);
+                continue;
+            }
+
+            if let Callee::Instance(Instance { def: ty::InstanceDef::Item(def_id), .. }) =
+                self.nodes[node].callee
+            {
+                if entry_fn == Some(def_id) || exported.contains_key(&def_id) {
+                    stack_usage.record_entry_point(symbol.clone());
+                }
+            }
+            stack_usage.record_function(FunctionCalls { symbol, name, calls });
+        }
+    }
+
+    /// Emits the `panic_reachable` lint for each function of the crate from
+    /// which a panic entry point can be reached through direct calls and drops,
+    /// naming the shortest chain of calls to it.
+    pub fn lint_panic_reachability(&self, tcx: TyCtxt<'tcx>) {
+        let mut callers = vec![Vec::new(); self.nodes.len()];
+        for &edge in &self.edges {
+            match edge.kind {
+                CallKind::Direct | CallKind::Drop => callers[edge.callee].push(edge),
+                CallKind::Virtual | CallKind::FnPtr => {}
+            }
+        }
+
+        // The first call on the shortest chain from each node to a panic entry
+        // point, found by walking the calls backwards from the entry points.
+        let mut next_call: Vec<Option<CallEdge>> = vec![None; self.nodes.len()];
+        let mut queue: VecDeque<_> =
+            (0..self.nodes.len()).filter(|&node| self.nodes[node].panic).collect();
+        while let Some(node) = queue.pop_front() {
+            for &edge in &callers[node] {
+                if !self.nodes[edge.caller].panic && next_call[edge.caller].is_none() {
+                    next_call[edge.caller] = Some(edge);
+                    queue.push_back(edge.caller);
+                }
+            }
+        }
+
+        // The instances of a generic function are reported once.
+        let mut reported = FxHashSet::default();
+        for (node, &call) in next_call.iter().enumerate() {
+            let (def_id, call) = match (self.nodes[node].callee, call) {
+                (Callee::Instance(Instance { def: ty::InstanceDef::Item(def_id), .. }),
+                 Some(call)) if def_id.is_local() => (def_id, call),
+                _ => continue,
+            };
+            if !reported.insert(def_id) {
+                continue;
+            }
+
+            let mut chain = vec![format!("`{}`", self.node_name(tcx, node))];
+            let mut next = Some(call);
+            while let Some(call) = next {
+                chain.push(format!("`{}`", self.node_name(tcx, call.callee)));
+                next = next_call[call.callee];
+            }
+
+            let hir_id = tcx.hir().as_local_hir_id(def_id).unwrap();
+            let mut err = tcx.struct_span_lint_hir(
+                PANIC_REACHABLE,
+                hir_id,
+                tcx.def_span(def_id),
+                &format!("a panic is reachable from {}", chain[0]),
+            );
+            let label = if self.nodes[call.callee].panic {
+                "this can panic"
+            } else {
+                "a panic is reachable through this call"
+            };
+            err.span_label(self.call_sites[&call], label);
+            err.note(&format!("through {}", chain.join(" -> ")));
+            err.emit();
+        }
+    }
+
+    fn write_json(&self, tcx: TyCtxt<'tcx>, path: &Path) -> io::Result<()> {
+        #[derive(RustcEncodable)]
+        struct Node {
+            id: usize,
+            name: String,
+            symbol: Option<String>,
+            kind: &'static str,
+            local: bool,
+            panic: bool,
+        }
+
+        #[derive(RustcEncodable)]
+        struct Edge {
+            from: usize,
+            to: usize,
+            kind: &'static str,
+            panic: bool,
+        }
+
+        #[derive(RustcEncodable)]
+        struct Graph {
+            version: u32,
+            crate_name: String,
+            nodes: Vec<Node>,
+            edges: Vec<Edge>,
+        }
+
+        let nodes = self.nodes.iter().enumerate().map(|(id, node)| Node {
+            id,
+            name: self.node_name(tcx, id),
+            symbol: self.node_symbol(tcx, id),
+            kind: self.node_kind(id),
+            local: node.local,
+            panic: node.panic,
+        }).collect();
+        let edges = self.edges.iter().map(|edge| Edge {
+            from: edge.caller,
+            to: edge.callee,
+            kind: edge.kind.as_str(),
+            panic: self.nodes[edge.callee].panic,
+        }).collect();
+        let graph = Graph {
+            version: CALL_GRAPH_VERSION,
+            crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+            nodes,
+            edges,
+        };
+
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{}", rustc_serialize::json::as_pretty_json(&graph))
+    }
+
+    fn write_graphviz(&self, tcx: TyCtxt<'tcx>, path: &Path) -> io::Result<()> {
+        let graphviz = GraphvizCallGraph {
+            graph: self,
+            names: (0..self.nodes.len()).map(|node| self.node_name(tcx, node)).collect(),
+        };
+        let mut file = BufWriter::new(File::create(path)?);
+        dot::render(&graphviz, &mut file)
+    }
+}
+
+/// Whether calling this function starts a panic.
+fn is_panic_entry_point(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    let lang_items = tcx.lang_items();
+    let lang_item_entry_points = [
+        lang_items.panic_fn(),
+        lang_items.panic_bounds_check_fn(),
+        lang_items.begin_panic_fn(),
+        lang_items.panic_impl(),
+    ];
+    if lang_item_entry_points.contains(&Some(def_id)) {
+        return true;
+    }
+
+    // The entry points of panics with a formatted message are not lang items.
+    let name = match tcx.def_key(def_id).disambiguated_data.data.get_opt_name() {
+        Some(name) => name,
+        None => return false,
+    };
+    match tcx.crate_name(def_id.krate) {
+        sym::core => name.as_str() == "panic_fmt",
+        sym::std => name.as_str() == "begin_panic_fmt",
+        _ => false,
+    }
+}
+
+/// Whether the `panic_reachable` lint is enabled for any of the functions of
+/// the crate among `items`, in which case the call graph has to be built.
+pub fn panic_reachable_lint_enabled<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+) -> bool {
+    // The lint is allowed by default, so unless its level is set somewhere
+    // there's no need to look up the level of every function.
+    if PANIC_REACHABLE.default_level(tcx.sess) == lint::Allow &&
+        !tcx.lint_levels(LOCAL_CRATE).is_level_set_anywhere(PANIC_REACHABLE)
+    {
+        return false;
+    }
+
+    items.iter().any(|item| match *item {
+        MonoItem::Fn(Instance { def: ty::InstanceDef::Item(def_id), .. }) => {
+            tcx.hir().as_local_hir_id(def_id).map_or(false, |hir_id| {
+                tcx.lint_level_at_node(PANIC_REACHABLE, hir_id).0 != lint::Allow
+            })
+        }
+        _ => false,
+    })
+}
+
+/// Whether this is a function of `core` or `alloc` which never returns. The
+/// calls made by upstream functions are unknown, but those functions, e.g.,
+/// `core::slice::slice_index_len_fail`, only exist to start a panic.
+fn is_diverging_core_function(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    match tcx.crate_name(def_id.krate) {
+        sym::core | sym::alloc => {}
+        _ => return false,
+    }
+    match tcx.def_kind(def_id) {
+        Some(DefKind::Fn) | Some(DefKind::Method) => {
+            tcx.fn_sig(def_id).output().skip_binder().is_never()
+        }
+        _ => false,
+    }
+}
+
+struct CallCollector<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a mir::Body<'tcx>,
+    param_substs: SubstsRef<'tcx>,
+    calls: Vec<(Callee<'tcx>, CallKind, Span)>,
+}
+
+impl<'a, 'tcx> CallCollector<'a, 'tcx> {
+    fn monomorphize<T: TypeFoldable<'tcx>>(&self, value: &T) -> T {
+        self.tcx.subst_and_normalize_erasing_regions(
+            self.param_substs,
+            ty::ParamEnv::reveal_all(),
+            value,
+        )
+    }
+}
+
+impl<'a, 'tcx> MirVisitor<'tcx> for CallCollector<'a, 'tcx> {
+    fn visit_terminator_kind(&mut self,
+                             kind: &mir::TerminatorKind<'tcx>,
+                             location: Location) {
+        let tcx = self.tcx;
+        let span = self.body.source_info(location).span;
+        match *kind {
+            mir::TerminatorKind::Call { ref func, .. } => {
+                let callee_ty = self.monomorphize(&func.ty(self.body, tcx));
+                match callee_ty.kind {
+                    ty::FnDef(def_id, substs) => {
+                        let instance = Instance::resolve(
+                            tcx,
+                            ty::ParamEnv::reveal_all(),
+                            def_id,
+                            substs,
+                        ).unwrap();
+                        let kind = match instance.def {
+                            // Intrinsics are expanded in place by codegen.
+                            ty::InstanceDef::Intrinsic(..) => None,
+                            ty::InstanceDef::Virtual(..) => Some(CallKind::Virtual),
+                            _ => Some(CallKind::Direct),
+                        };
+                        if let Some(kind) = kind {
+                            self.calls.push((Callee::Instance(instance), kind, span));
+                        }
+                    }
+                    ty::FnPtr(..) => {
+                        self.calls.push((Callee::FnPtr(callee_ty), CallKind::FnPtr, span))
+                    }
+                    _ => bug!("call of a non-function type `{:?}`", callee_ty),
+                }
+            }
+            mir::TerminatorKind::Drop { location: ref place, .. } |
+            mir::TerminatorKind::DropAndReplace { location: ref place, .. } => {
+                let ty = self.monomorphize(&place.ty(self.body, tcx).ty);
+                let instance = Instance::resolve_drop_in_place(tcx, ty);
+                match instance.def {
+                    // Nothing to drop.
+                    ty::InstanceDef::DropGlue(_, None) => {}
+                    // Trait objects are dropped through their vtable.
+                    _ if ty.is_trait() => {
+                        self.calls.push((Callee::Instance(instance), CallKind::Virtual, span));
+                    }
+                    _ => self.calls.push((Callee::Instance(instance), CallKind::Drop, span)),
+                }
+            }
+            mir::TerminatorKind::Assert { ref msg, .. } => {
+                let lang_item = match msg {
+                    PanicInfo::BoundsCheck { .. } => PanicBoundsCheckFnLangItem,
+                    _ => PanicFnLangItem,
+                };
+                let def_id = tcx.lang_items().require(lang_item)
+                    .unwrap_or_else(|e| tcx.sess.fatal(&e));
+                let callee = Callee::Instance(Instance::mono(tcx, def_id));
+                self.calls.push((callee, CallKind::Direct, span));
+            }
+            _ => {}
+        }
+
+        self.super_terminator_kind(kind, location);
+    }
+}
+
+struct GraphvizCallGraph<'a, 'tcx> {
+    graph: &'a CallGraph<'tcx>,
+    names: Vec<String>,
+}
+
+impl<'a, 'this, 'tcx> dot::Labeller<'this> for GraphvizCallGraph<'a, 'tcx> {
+    type Node = usize;
+    type Edge = CallEdge;
+
+    fn graph_id(&'this self) -> dot::Id<'this> {
+        dot::Id::new("CallGraph").unwrap()
+    }
+    fn node_id(&'this self, n: &usize) -> dot::Id<'this> {
+        dot::Id::new(format!("n{}", n)).unwrap()
+    }
+    fn node_shape(&'this self, n: &usize) -> Option<dot::LabelText<'this>> {
+        let shape = match self.graph.nodes[*n].callee {
+            Callee::Instance(Instance { def: ty::InstanceDef::Virtual(..), .. }) |
+            Callee::FnPtr(_) => "diamond",
+            Callee::Instance(_) if self.graph.nodes[*n].local => "box",
+            Callee::Instance(_) => "ellipse",
+        };
+        Some(dot::LabelText::LabelStr(Cow::Borrowed(shape)))
+    }
+    fn node_label(&'this self, n: &usize) -> dot::LabelText<'this> {
+        dot::LabelText::LabelStr(Cow::Borrowed(&self.names[*n]))
+    }
+    fn node_style(&'this self, n: &usize) -> dot::Style {
+        if self.graph.nodes[*n].panic { dot::Style::Filled } else { dot::Style::None }
+    }
+    fn edge_label(&'this self, e: &CallEdge) -> dot::LabelText<'this> {
+        let label = match e.kind {
+            CallKind::Direct => "",
+            kind => kind.as_str(),
+        };
+        dot::LabelText::LabelStr(Cow::Borrowed(label))
+    }
+    fn edge_style(&'this self, e: &CallEdge) -> dot::Style {
+        match e.kind {
+            _ if self.graph.nodes[e.callee].panic => dot::Style::Bold,
+            CallKind::Direct | CallKind::Drop => dot::Style::None,
+            CallKind::Virtual => dot::Style::Dashed,
+            CallKind::FnPtr => dot::Style::Dotted,
+        }
+    }
+}
+
+impl<'a, 'this, 'tcx> dot::GraphWalk<'this> for GraphvizCallGraph<'a, 'tcx> {
+    type Node = usize;
+    type Edge = CallEdge;
+
+    fn nodes(&'this self) -> dot::Nodes<'this, usize> {
+        (0..self.graph.nodes.len()).collect()
+    }
+    fn edges(&'this self) -> dot::Edges<'this, CallEdge> {
+        Cow::Borrowed(&self.graph.edges)
+    }
+    fn source(&'this self, edge: &CallEdge) -> usize {
+        edge.caller
+    }
+    fn target(&'this self, edge: &CallEdge) -> usize {
+        edge.callee
+    }
+}
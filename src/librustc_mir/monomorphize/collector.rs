@@ -196,6 +196,7 @@ use rustc::util::common::time;
 use rustc_index::bit_set::GrowableBitSet;
 use rustc_data_structures::sync::{MTRef, MTLock, ParallelIterator, par_iter};
 
+use std::collections::VecDeque;
 use std::iter;
 
 #[derive(PartialEq)]
@@ -294,6 +295,11 @@ pub fn collect_crate_mono_items(
 
     debug!("building mono item graph, beginning at roots");
 
+    // The roots are only kept when they are needed to explain the mono items.
+    let explain_mono_item = tcx.sess.opts.debugging_opts.explain_mono_item.as_ref();
+    let explained_roots: Option<FxHashSet<_>> =
+        explain_mono_item.map(|_| roots.iter().cloned().collect());
+
     let mut visited = MTLock::new(FxHashSet::default());
     let mut inlining_map = MTLock::new(InliningMap::new());
 
@@ -316,7 +322,145 @@ pub fn collect_crate_mono_items(
         });
     }
 
-    (visited.into_inner(), inlining_map.into_inner())
+    let (visited, inlining_map) = (visited.into_inner(), inlining_map.into_inner());
+
+    if let (Some(path), Some(roots)) = (explain_mono_item, explained_roots) {
+        explain_mono_items(tcx, path, &roots, &visited, &inlining_map);
+    }
+
+    (visited, inlining_map)
+}
+
+/// Prints, for each mono item matching `path`, the shortest chain of uses
+/// leading to it from a root, for `-Z explain-mono-item`.
+///
+/// A path without generic arguments matches all the instances of the item,
+/// and a path with generic arguments only matches the instance it names.
+fn explain_mono_items<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    path: &str,
+    roots: &FxHashSet<MonoItem<'tcx>>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    inlining_map: &InliningMap<'tcx>,
+) {
+    let mut users: FxHashMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>> = FxHashMap::default();
+    inlining_map.iter_accesses(|user, used_items| {
+        for &used in used_items {
+            let item_users = users.entry(used).or_default();
+            if !item_users.contains(&user) {
+                item_users.push(user);
+            }
+        }
+    });
+
+    let has_generic_args = path.contains("::<");
+    let mut explained: Vec<_> = items.iter().filter_map(|&item| {
+        let item_path = match item {
+            MonoItem::Fn(instance) => {
+                tcx.def_path_str_with_substs(instance.def_id(), instance.substs)
+            }
+            MonoItem::Static(def_id) => tcx.def_path_str(def_id),
+            MonoItem::GlobalAsm(..) => return None,
+        };
+        let matches = if has_generic_args {
+            item_path == path
+        } else {
+            strip_generic_args(&item_path) == path
+        };
+        if matches { Some((mono_item_name(tcx, item)?, item)) } else { None }
+    }).collect();
+
+    if explained.is_empty() {
+        tcx.sess.warn(&format!("`-Z explain-mono-item`: no mono item matches `{}`", path));
+        return;
+    }
+    explained.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (name, item) in explained {
+        if roots.contains(&item) {
+            println!("explain-mono-item `{}`: a root", name);
+            continue;
+        }
+
+        // Walk the uses backwards, breadth-first, until a root is reached.
+        let mut towards_item = FxHashMap::default();
+        let mut queue: VecDeque<_> = iter::once(item).collect();
+        let root = loop {
+            let used = match queue.pop_front() {
+                Some(used) => used,
+                None => break None,
+            };
+            if roots.contains(&used) {
+                break Some(used);
+            }
+            for &user in users.get(&used).into_iter().flatten() {
+                if user != item && !towards_item.contains_key(&user) {
+                    towards_item.insert(user, used);
+                    queue.push_back(user);
+                }
+            }
+        };
+
+        let mut chain = Vec::new();
+        let mut current = root;
+        while let Some(link) = current {
+            chain.push(mono_item_name(tcx, link).unwrap_or_else(|| "global_asm".to_string()));
+            current = towards_item.get(&link).cloned();
+        }
+        if chain.is_empty() {
+            println!("explain-mono-item `{}`: not used by any root", name);
+        } else {
+            println!("explain-mono-item `{}`: {}", name, chain.join(" -> "));
+        }
+
+        // The direct users of the item which are not on the chain.
+        let direct_user = chain.len().checked_sub(2).map(|i| chain[i].clone());
+        let mut other_users: Vec<_> = users.get(&item).into_iter().flatten()
+            .filter_map(|&user| mono_item_name(tcx, user))
+            .filter(|user| Some(user) != direct_user.as_ref())
+            .map(|user| format!("`{}`", user))
+            .collect();
+        if !other_users.is_empty() {
+            other_users.sort();
+            println!("explain-mono-item     also used by: {}", other_users.join(", "));
+        }
+    }
+}
+
+/// The name of a mono item as printed by `-Z explain-mono-item`, which is its
+/// path with its generic arguments, and the kind of shim it is, if any. Global
+/// assembly has no name.
+fn mono_item_name(tcx: TyCtxt<'_>, item: MonoItem<'_>) -> Option<String> {
+    match item {
+        MonoItem::Fn(instance) => Some(instance.to_string()),
+        MonoItem::Static(def_id) => Some(tcx.def_path_str(def_id)),
+        MonoItem::GlobalAsm(..) => None,
+    }
+}
+
+/// Removes the generic arguments of the segments of a path, e.g.,
+/// `Vec::<u8>::push` becomes `Vec::push`. The types of qualified paths, as in
+/// `<Vec<u8> as Drop>::drop`, are kept.
+fn strip_generic_args(path: &str) -> String {
+    let mut stripped = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(start) = rest.find("::<") {
+        stripped.push_str(&rest[..start]);
+        let (mut depth, mut prev) = (0, ' ');
+        let end = rest[start + 2..].char_indices().find(|&(_, c)| {
+            match c {
+                '<' => depth += 1,
+                // Not the `->` of a function type.
+                '>' if prev != '-' => depth -= 1,
+                _ => {}
+            }
+            prev = c;
+            depth == 0
+        }).map_or(rest.len(), |(i, _)| start + 2 + i + 1);
+        rest = &rest[end..];
+    }
+    stripped.push_str(rest);
+    stripped
 }
 
 // Find all non-generic items by walking the HIR. These items serve as roots to
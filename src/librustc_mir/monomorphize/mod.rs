@@ -2,6 +2,7 @@ use rustc::traits;
 use rustc::ty::adjustment::CustomCoerceUnsized;
 use rustc::ty::{self, Ty, TyCtxt};
 
+pub mod call_graph;
 pub mod collector;
 pub mod partitioning;
 
@@ -103,12 +103,14 @@ use rustc::hir::def_id::{CrateNum, DefId, LOCAL_CRATE, CRATE_DEF_INDEX};
 use rustc::mir::mono::{Linkage, Visibility, CodegenUnitNameBuilder, CodegenUnit};
 use rustc::middle::exported_symbols::SymbolExportLevel;
 use rustc::ty::{self, DefIdTree, TyCtxt, InstanceDef};
-use rustc::ty::print::characteristic_def_id_of_type;
+use rustc::session::SymbolDefinition;
+use rustc::ty::print::{characteristic_def_id_of_type, with_crate_prefix};
 use rustc::ty::query::Providers;
 use rustc::util::common::time;
 use rustc::util::nodemap::{DefIdSet, FxHashMap, FxHashSet};
 use rustc::mir::mono::{MonoItem, InstantiationMode};
 
+use crate::monomorphize::call_graph::{self, CallGraph};
 use crate::monomorphize::collector::InliningMap;
 use crate::monomorphize::collector::{self, MonoItemCollectionMode};
 
@@ -187,6 +189,16 @@ where
 
     result.sort_by_cached_key(|cgu| cgu.name().as_str());
 
+    if tcx.sess.time_report() {
+        for cgu in &result {
+            tcx.sess.time_report.record_codegen_unit(
+                cgu.name().to_string(),
+                cgu.items().len(),
+                cgu.size_estimate(),
+            );
+        }
+    }
+
     result
 }
 
@@ -852,6 +864,31 @@ where
     }
 }
 
+/// Records the item each mono item is an instance of, by symbol, for
+/// `-Z print-code-sizes`.
+fn record_symbol_definitions<'tcx>(tcx: TyCtxt<'tcx>, items: &FxHashSet<MonoItem<'tcx>>) {
+    for &item in items {
+        let def_id = match item {
+            MonoItem::Fn(instance) => instance.def_id(),
+            MonoItem::Static(def_id) => def_id,
+            MonoItem::GlobalAsm(..) => continue,
+        };
+
+        // The innermost module, or the crate root.
+        let mut module = def_id;
+        while module.index != CRATE_DEF_INDEX && tcx.def_kind(module) != Some(DefKind::Mod) {
+            module = tcx.parent(module).unwrap();
+        }
+
+        let symbol = item.symbol_name(tcx).name.to_string();
+        tcx.sess.code_stats.record_symbol_definition(symbol, SymbolDefinition {
+            krate: tcx.crate_name(def_id.krate).to_string(),
+            module: with_crate_prefix(|| tcx.def_path_str(module)),
+            definition: with_crate_prefix(|| tcx.def_path_str(def_id)),
+        });
+    }
+}
+
 fn collect_and_partition_mono_items(
     tcx: TyCtxt<'_>,
     cnum: CrateNum,
@@ -893,6 +930,31 @@ fn collect_and_partition_mono_items(
 
     assert_symbols_are_distinct(tcx, items.iter());
 
+    let dump_call_graph = tcx.sess.opts.debugging_opts.dump_call_graph.as_ref();
+    let lint_panics = call_graph::panic_reachable_lint_enabled(tcx, &items);
+    if dump_call_graph.is_some() || tcx.sess.opts.debugging_opts.print_stack_usage ||
+        lint_panics
+    {
+        time(tcx.sess, "building call graph", || {
+            let graph = CallGraph::new(tcx, &items);
+            if let Some(path) = dump_call_graph {
+                graph.dump(tcx, path);
+            }
+            if tcx.sess.opts.debugging_opts.print_stack_usage {
+                graph.record_stack_usage(tcx);
+            }
+            if lint_panics {
+                graph.lint_panic_reachability(tcx);
+            }
+        });
+    }
+
+    if tcx.sess.opts.debugging_opts.print_code_sizes.is_some() {
+        time(tcx.sess, "recording symbol definitions", || {
+            record_symbol_definitions(tcx, &items)
+        });
+    }
+
     let strategy = if tcx.sess.opts.incremental.is_some() {
         PartitioningStrategy::PerModule
     } else {
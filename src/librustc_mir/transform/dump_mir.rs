@@ -58,3 +58,10 @@ pub fn emit_mir(tcx: TyCtxt<'_>, outputs: &OutputFilenames) -> io::Result<()> {
     mir_util::write_mir_pretty(tcx, None, &mut f)?;
     Ok(())
 }
+
+pub fn emit_mir_json(tcx: TyCtxt<'_>, outputs: &OutputFilenames) -> io::Result<()> {
+    let path = outputs.path(OutputType::MirJson);
+    let mut f = io::BufWriter::new(File::create(&path)?);
+    mir_util::write_mir_json(tcx, None, &mut f)?;
+    Ok(())
+}
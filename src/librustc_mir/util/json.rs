@@ -0,0 +1,663 @@
+//! The JSON serialization of MIR emitted by `--emit mir-json`.
+//!
+//! Unlike the output of `--emit mir`, this format is documented in the rustc
+//! book and versioned: new fields and new values of the `kind` fields may be
+//! added at any time, but any other change increments `MIR_JSON_VERSION`.
+//!
+//! Types, and the `rendered` form of constants, are the output of the pretty
+//! printer, which isn't covered by the version: they are meant to be displayed
+//! rather than parsed.
+
+use rustc::hir::def::DefKind;
+use rustc::hir::def_id::{DefId, LOCAL_CRATE};
+use rustc::mir::{self, LocalKind, PlaceBase, ProjectionElem, StaticKind, StatementKind};
+use rustc::mir::TerminatorKind;
+use rustc::mir::interpret::PanicInfo;
+use rustc::ty::{self, TyCtxt};
+use rustc::ty::adjustment::PointerCast;
+use rustc_index::vec::Idx;
+use std::io::{self, Write};
+
+use super::pretty::dump_mir_def_ids;
+
+pub const MIR_JSON_VERSION: u32 = 1;
+
+#[derive(RustcEncodable)]
+struct Crate {
+    version: u32,
+    crate_name: String,
+    bodies: Vec<Body>,
+}
+
+#[derive(RustcEncodable)]
+struct Body {
+    def_path: String,
+    kind: &'static str,
+    promoted: Option<usize>,
+    span: Span,
+    arg_count: usize,
+    return_ty: String,
+    locals: Vec<Local>,
+    variables: Vec<Variable>,
+    basic_blocks: Vec<BasicBlock>,
+}
+
+#[derive(RustcEncodable, Default)]
+struct Span {
+    file: String,
+    byte_start: u32,
+    byte_end: u32,
+    line_start: usize,
+    column_start: usize,
+    line_end: usize,
+    column_end: usize,
+}
+
+#[derive(RustcEncodable)]
+struct Local {
+    index: usize,
+    kind: &'static str,
+    ty: String,
+    mutable: bool,
+    span: Span,
+}
+
+#[derive(RustcEncodable)]
+struct Variable {
+    name: String,
+    place: Place,
+    span: Span,
+}
+
+#[derive(RustcEncodable)]
+struct BasicBlock {
+    index: usize,
+    cleanup: bool,
+    statements: Vec<Statement>,
+    terminator: Terminator,
+}
+
+#[derive(RustcEncodable, Default)]
+struct Statement {
+    kind: &'static str,
+    span: Span,
+    place: Option<Place>,
+    rvalue: Option<Rvalue>,
+    local: Option<usize>,
+    variant: Option<usize>,
+}
+
+#[derive(RustcEncodable, Default)]
+struct Terminator {
+    kind: &'static str,
+    span: Span,
+    successors: Vec<usize>,
+    target: Option<usize>,
+    unwind: Option<usize>,
+    place: Option<Place>,
+    operand: Option<Operand>,
+    func: Option<Operand>,
+    args: Vec<Operand>,
+    values: Vec<String>,
+    targets: Vec<usize>,
+    expected: Option<bool>,
+    message: Option<String>,
+}
+
+#[derive(RustcEncodable, Default)]
+struct Rvalue {
+    kind: &'static str,
+    ty: String,
+    operator: Option<&'static str>,
+    operands: Vec<Operand>,
+    place: Option<Place>,
+    count: Option<u64>,
+    variant: Option<usize>,
+}
+
+#[derive(RustcEncodable)]
+struct Operand {
+    kind: &'static str,
+    ty: String,
+    place: Option<Place>,
+    constant: Option<Constant>,
+}
+
+#[derive(RustcEncodable)]
+struct Constant {
+    /// The bits of a scalar, in decimal since they may not fit in a JSON number.
+    bits: Option<String>,
+    /// The path of the function of a function item.
+    function: Option<String>,
+    rendered: String,
+}
+
+#[derive(RustcEncodable)]
+struct Place {
+    local: Option<usize>,
+    static_item: Option<String>,
+    promoted: Option<usize>,
+    ty: String,
+    projection: Vec<Projection>,
+}
+
+#[derive(RustcEncodable, Default)]
+struct Projection {
+    kind: &'static str,
+    ty: String,
+    field: Option<usize>,
+    local: Option<usize>,
+    offset: Option<u32>,
+    min_length: Option<u32>,
+    from_end: Option<bool>,
+    from: Option<u32>,
+    to: Option<u32>,
+    variant: Option<usize>,
+}
+
+/// Write the MIR of the crate, or of a single item, as JSON.
+pub fn write_mir_json<W>(
+    tcx: TyCtxt<'_>,
+    single: Option<DefId>,
+    w: &mut W,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let mut bodies = Vec::new();
+    for def_id in dump_mir_def_ids(tcx, single) {
+        let body = tcx.optimized_mir(def_id);
+        bodies.push(BodySerializer { tcx, body }.body(def_id, None));
+
+        for (promoted, body) in tcx.promoted_mir(def_id).iter_enumerated() {
+            bodies.push(BodySerializer { tcx, body }.body(def_id, Some(promoted.index())));
+        }
+    }
+
+    let krate = Crate {
+        version: MIR_JSON_VERSION,
+        crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+        bodies,
+    };
+    writeln!(w, "{}", rustc_serialize::json::as_pretty_json(&krate))
+}
+
+struct BodySerializer<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a mir::Body<'tcx>,
+}
+
+impl<'a, 'tcx> BodySerializer<'a, 'tcx> {
+    fn body(&self, def_id: DefId, promoted: Option<usize>) -> Body {
+        let tcx = self.tcx;
+        let body = self.body;
+        let kind = match tcx.def_kind(def_id) {
+            _ if promoted.is_some() => "promoted",
+            Some(DefKind::Fn) | Some(DefKind::Method) => "fn",
+            Some(DefKind::Ctor(..)) => "ctor",
+            Some(DefKind::Const) | Some(DefKind::AssocConst) => "const",
+            Some(DefKind::Static) => "static",
+            _ if body.generator_kind.is_some() => "generator",
+            _ if tcx.is_closure(def_id) => "closure",
+            _ => "anon-const",
+        };
+
+        let locals = body.local_decls.iter_enumerated().map(|(local, decl)| Local {
+            index: local.index(),
+            kind: match body.local_kind(local) {
+                LocalKind::ReturnPointer => "return",
+                LocalKind::Arg => "arg",
+                LocalKind::Var => "var",
+                LocalKind::Temp => "temp",
+            },
+            ty: decl.ty.to_string(),
+            mutable: decl.mutability == mir::Mutability::Mut,
+            span: self.span(decl.source_info.span),
+        }).collect();
+
+        let variables = body.var_debug_info.iter().map(|var_debug_info| Variable {
+            name: var_debug_info.name.to_string(),
+            place: self.place(&var_debug_info.place),
+            span: self.span(var_debug_info.source_info.span),
+        }).collect();
+
+        let basic_blocks = body.basic_blocks().iter_enumerated().map(|(bb, data)| BasicBlock {
+            index: bb.index(),
+            cleanup: data.is_cleanup,
+            statements: data.statements.iter().map(|statement| self.statement(statement)).collect(),
+            terminator: self.terminator(data.terminator()),
+        }).collect();
+
+        Body {
+            def_path: ty::print::with_forced_impl_filename_line(|| tcx.def_path_str(def_id)),
+            kind,
+            promoted,
+            span: self.span(body.span),
+            arg_count: body.arg_count,
+            return_ty: body.return_ty().to_string(),
+            locals,
+            variables,
+            basic_blocks,
+        }
+    }
+
+    fn span(&self, span: syntax_pos::Span) -> Span {
+        let source_map = self.tcx.sess.source_map();
+        let lo = source_map.lookup_char_pos(span.lo());
+        let hi = source_map.lookup_char_pos(span.hi());
+        Span {
+            file: lo.file.name.to_string(),
+            byte_start: (span.lo() - lo.file.start_pos).0,
+            byte_end: (span.hi() - lo.file.start_pos).0,
+            line_start: lo.line,
+            column_start: lo.col.0 + 1,
+            line_end: hi.line,
+            column_end: hi.col.0 + 1,
+        }
+    }
+
+    fn statement(&self, statement: &mir::Statement<'tcx>) -> Statement {
+        let span = self.span(statement.source_info.span);
+        match statement.kind {
+            StatementKind::Assign(box (ref place, ref rvalue)) => Statement {
+                kind: "assign",
+                span,
+                place: Some(self.place(place)),
+                rvalue: Some(self.rvalue(rvalue)),
+                ..Default::default()
+            },
+            StatementKind::FakeRead(_, ref place) => Statement {
+                kind: "fake-read",
+                span,
+                place: Some(self.place(place)),
+                ..Default::default()
+            },
+            StatementKind::SetDiscriminant { ref place, variant_index } => Statement {
+                kind: "set-discriminant",
+                span,
+                place: Some(self.place(place)),
+                variant: Some(variant_index.index()),
+                ..Default::default()
+            },
+            StatementKind::StorageLive(local) => Statement {
+                kind: "storage-live",
+                span,
+                local: Some(local.index()),
+                ..Default::default()
+            },
+            StatementKind::StorageDead(local) => Statement {
+                kind: "storage-dead",
+                span,
+                local: Some(local.index()),
+                ..Default::default()
+            },
+            StatementKind::InlineAsm(_) => Statement {
+                kind: "inline-asm",
+                span,
+                ..Default::default()
+            },
+            StatementKind::Retag(_, ref place) => Statement {
+                kind: "retag",
+                span,
+                place: Some(self.place(place)),
+                ..Default::default()
+            },
+            StatementKind::AscribeUserType(box (ref place, _), _) => Statement {
+                kind: "ascribe-user-type",
+                span,
+                place: Some(self.place(place)),
+                ..Default::default()
+            },
+            StatementKind::Nop => Statement {
+                kind: "nop",
+                span,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn terminator(&self, terminator: &mir::Terminator<'tcx>) -> Terminator {
+        let span = self.span(terminator.source_info.span);
+        let successors = terminator.successors().map(|bb| bb.index()).collect();
+        let index = |bb: mir::BasicBlock| bb.index();
+        match terminator.kind {
+            TerminatorKind::Goto { target } => Terminator {
+                kind: "goto",
+                span,
+                successors,
+                target: Some(target.index()),
+                ..Default::default()
+            },
+            TerminatorKind::SwitchInt { ref discr, ref values, ref targets, .. } => Terminator {
+                kind: "switch-int",
+                span,
+                successors,
+                operand: Some(self.operand(discr)),
+                values: values.iter().map(|value| value.to_string()).collect(),
+                targets: targets.iter().cloned().map(index).collect(),
+                ..Default::default()
+            },
+            TerminatorKind::Resume => Terminator { kind: "resume", span, ..Default::default() },
+            TerminatorKind::Abort => Terminator { kind: "abort", span, ..Default::default() },
+            TerminatorKind::Return => Terminator { kind: "return", span, ..Default::default() },
+            TerminatorKind::Unreachable => {
+                Terminator { kind: "unreachable", span, ..Default::default() }
+            }
+            TerminatorKind::Drop { ref location, target, unwind } => Terminator {
+                kind: "drop",
+                span,
+                successors,
+                target: Some(target.index()),
+                unwind: unwind.map(index),
+                place: Some(self.place(location)),
+                ..Default::default()
+            },
+            TerminatorKind::DropAndReplace { ref location, ref value, target, unwind } => {
+                Terminator {
+                    kind: "drop-and-replace",
+                    span,
+                    successors,
+                    target: Some(target.index()),
+                    unwind: unwind.map(index),
+                    place: Some(self.place(location)),
+                    operand: Some(self.operand(value)),
+                    ..Default::default()
+                }
+            }
+            TerminatorKind::Call { ref func, ref args, ref destination, cleanup, .. } => {
+                Terminator {
+                    kind: "call",
+                    span,
+                    successors,
+                    target: destination.as_ref().map(|&(_, target)| target.index()),
+                    unwind: cleanup.map(index),
+                    place: destination.as_ref().map(|(place, _)| self.place(place)),
+                    func: Some(self.operand(func)),
+                    args: args.iter().map(|arg| self.operand(arg)).collect(),
+                    ..Default::default()
+                }
+            }
+            TerminatorKind::Assert { ref cond, expected, ref msg, target, cleanup } => {
+                let message = match *msg {
+                    PanicInfo::Panic { msg, .. } => msg.to_string(),
+                    PanicInfo::BoundsCheck { .. } => "index out of bounds".to_string(),
+                    _ => msg.description().to_string(),
+                };
+                Terminator {
+                    kind: "assert",
+                    span,
+                    successors,
+                    target: Some(target.index()),
+                    unwind: cleanup.map(index),
+                    operand: Some(self.operand(cond)),
+                    expected: Some(expected),
+                    message: Some(message),
+                    ..Default::default()
+                }
+            }
+            TerminatorKind::Yield { ref value, resume, .. } => Terminator {
+                kind: "yield",
+                span,
+                successors,
+                target: Some(resume.index()),
+                operand: Some(self.operand(value)),
+                ..Default::default()
+            },
+            TerminatorKind::GeneratorDrop => {
+                Terminator { kind: "generator-drop", span, ..Default::default() }
+            }
+            TerminatorKind::FalseEdges { real_target, .. } => Terminator {
+                kind: "false-edges",
+                span,
+                successors,
+                target: Some(real_target.index()),
+                ..Default::default()
+            },
+            TerminatorKind::FalseUnwind { real_target, unwind } => Terminator {
+                kind: "false-unwind",
+                span,
+                successors,
+                target: Some(real_target.index()),
+                unwind: unwind.map(index),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn rvalue(&self, rvalue: &mir::Rvalue<'tcx>) -> Rvalue {
+        let ty = rvalue.ty(self.body, self.tcx).to_string();
+        match *rvalue {
+            mir::Rvalue::Use(ref operand) => Rvalue {
+                kind: "use",
+                ty,
+                operands: vec![self.operand(operand)],
+                ..Default::default()
+            },
+            mir::Rvalue::Repeat(ref operand, count) => Rvalue {
+                kind: "repeat",
+                ty,
+                operands: vec![self.operand(operand)],
+                count: Some(count),
+                ..Default::default()
+            },
+            mir::Rvalue::Ref(_, borrow_kind, ref place) => Rvalue {
+                kind: "ref",
+                ty,
+                operator: Some(match borrow_kind {
+                    mir::BorrowKind::Shared => "shared",
+                    mir::BorrowKind::Shallow => "shallow",
+                    mir::BorrowKind::Unique => "unique",
+                    mir::BorrowKind::Mut { .. } => "mut",
+                }),
+                place: Some(self.place(place)),
+                ..Default::default()
+            },
+            mir::Rvalue::Len(ref place) => Rvalue {
+                kind: "len",
+                ty,
+                place: Some(self.place(place)),
+                ..Default::default()
+            },
+            mir::Rvalue::Cast(cast_kind, ref operand, _) => Rvalue {
+                kind: "cast",
+                ty,
+                operator: Some(match cast_kind {
+                    mir::CastKind::Misc => "misc",
+                    mir::CastKind::Pointer(PointerCast::ReifyFnPointer) => "reify-fn-pointer",
+                    mir::CastKind::Pointer(PointerCast::UnsafeFnPointer) => "unsafe-fn-pointer",
+                    mir::CastKind::Pointer(PointerCast::ClosureFnPointer(_)) => {
+                        "closure-fn-pointer"
+                    }
+                    mir::CastKind::Pointer(PointerCast::MutToConstPointer) => {
+                        "mut-to-const-pointer"
+                    }
+                    mir::CastKind::Pointer(PointerCast::ArrayToPointer) => "array-to-pointer",
+                    mir::CastKind::Pointer(PointerCast::Unsize) => "unsize",
+                }),
+                operands: vec![self.operand(operand)],
+                ..Default::default()
+            },
+            mir::Rvalue::BinaryOp(op, ref lhs, ref rhs) => Rvalue {
+                kind: "binary-op",
+                ty,
+                operator: Some(bin_op_name(op)),
+                operands: vec![self.operand(lhs), self.operand(rhs)],
+                ..Default::default()
+            },
+            mir::Rvalue::CheckedBinaryOp(op, ref lhs, ref rhs) => Rvalue {
+                kind: "checked-binary-op",
+                ty,
+                operator: Some(bin_op_name(op)),
+                operands: vec![self.operand(lhs), self.operand(rhs)],
+                ..Default::default()
+            },
+            mir::Rvalue::NullaryOp(op, _) => Rvalue {
+                kind: "nullary-op",
+                ty,
+                operator: Some(match op {
+                    mir::NullOp::SizeOf => "size-of",
+                    mir::NullOp::Box => "box",
+                }),
+                ..Default::default()
+            },
+            mir::Rvalue::UnaryOp(op, ref operand) => Rvalue {
+                kind: "unary-op",
+                ty,
+                operator: Some(match op {
+                    mir::UnOp::Not => "not",
+                    mir::UnOp::Neg => "neg",
+                }),
+                operands: vec![self.operand(operand)],
+                ..Default::default()
+            },
+            mir::Rvalue::Discriminant(ref place) => Rvalue {
+                kind: "discriminant",
+                ty,
+                place: Some(self.place(place)),
+                ..Default::default()
+            },
+            mir::Rvalue::Aggregate(ref aggregate_kind, ref operands) => {
+                let (operator, variant) = match **aggregate_kind {
+                    mir::AggregateKind::Array(_) => ("array", None),
+                    mir::AggregateKind::Tuple => ("tuple", None),
+                    mir::AggregateKind::Adt(_, variant, ..) => ("adt", Some(variant.index())),
+                    mir::AggregateKind::Closure(..) => ("closure", None),
+                    mir::AggregateKind::Generator(..) => ("generator", None),
+                };
+                Rvalue {
+                    kind: "aggregate",
+                    ty,
+                    operator: Some(operator),
+                    operands: operands.iter().map(|operand| self.operand(operand)).collect(),
+                    variant,
+                    ..Default::default()
+                }
+            }
+        }
+    }
+
+    fn operand(&self, operand: &mir::Operand<'tcx>) -> Operand {
+        let ty = operand.ty(self.body, self.tcx).to_string();
+        match *operand {
+            mir::Operand::Copy(ref place) => Operand {
+                kind: "copy",
+                ty,
+                place: Some(self.place(place)),
+                constant: None,
+            },
+            mir::Operand::Move(ref place) => Operand {
+                kind: "move",
+                ty,
+                place: Some(self.place(place)),
+                constant: None,
+            },
+            mir::Operand::Constant(ref constant) => Operand {
+                kind: "constant",
+                ty,
+                place: None,
+                constant: Some(self.constant(constant.literal)),
+            },
+        }
+    }
+
+    fn constant(&self, literal: &ty::Const<'tcx>) -> Constant {
+        let (bits, function) = match literal.ty.kind {
+            ty::Bool | ty::Char | ty::Int(_) | ty::Uint(_) | ty::Float(_) | ty::RawPtr(_) => {
+                let layout = self.tcx.layout_of(ty::ParamEnv::reveal_all().and(literal.ty));
+                let bits = layout.ok().and_then(|layout| literal.val.try_to_bits(layout.size));
+                (bits.map(|bits| bits.to_string()), None)
+            }
+            ty::FnDef(def_id, _) => (None, Some(self.tcx.def_path_str(def_id))),
+            _ => (None, None),
+        };
+        Constant {
+            bits,
+            function,
+            rendered: literal.to_string(),
+        }
+    }
+
+    fn place(&self, place: &mir::Place<'tcx>) -> Place {
+        let (local, static_item, promoted) = match place.base {
+            PlaceBase::Local(local) => (Some(local.index()), None, None),
+            PlaceBase::Static(box mir::Static { kind: StaticKind::Static, def_id, .. }) => {
+                (None, Some(self.tcx.def_path_str(def_id)), None)
+            }
+            PlaceBase::Static(box mir::Static {
+                kind: StaticKind::Promoted(promoted, _), ..
+            }) => (None, None, Some(promoted.index())),
+        };
+
+        let mut place_ty = place.base.ty(self.body);
+        let projection = place.projection.iter().map(|elem| {
+            place_ty = place_ty.projection_ty(self.tcx, elem);
+            let ty = place_ty.ty.to_string();
+            match *elem {
+                ProjectionElem::Deref => Projection { kind: "deref", ty, ..Default::default() },
+                ProjectionElem::Field(field, _) => Projection {
+                    kind: "field",
+                    ty,
+                    field: Some(field.index()),
+                    ..Default::default()
+                },
+                ProjectionElem::Index(local) => Projection {
+                    kind: "index",
+                    ty,
+                    local: Some(local.index()),
+                    ..Default::default()
+                },
+                ProjectionElem::ConstantIndex { offset, min_length, from_end } => Projection {
+                    kind: "constant-index",
+                    ty,
+                    offset: Some(offset),
+                    min_length: Some(min_length),
+                    from_end: Some(from_end),
+                    ..Default::default()
+                },
+                ProjectionElem::Subslice { from, to } => Projection {
+                    kind: "subslice",
+                    ty,
+                    from: Some(from),
+                    to: Some(to),
+                    ..Default::default()
+                },
+                ProjectionElem::Downcast(_, variant) => Projection {
+                    kind: "downcast",
+                    ty,
+                    variant: Some(variant.index()),
+                    ..Default::default()
+                },
+            }
+        }).collect();
+
+        Place {
+            local,
+            static_item,
+            promoted,
+            ty: place_ty.ty.to_string(),
+            projection,
+        }
+    }
+}
+
+fn bin_op_name(op: mir::BinOp) -> &'static str {
+    match op {
+        mir::BinOp::Add => "add",
+        mir::BinOp::Sub => "sub",
+        mir::BinOp::Mul => "mul",
+        mir::BinOp::Div => "div",
+        mir::BinOp::Rem => "rem",
+        mir::BinOp::BitXor => "bit-xor",
+        mir::BinOp::BitAnd => "bit-and",
+        mir::BinOp::BitOr => "bit-or",
+        mir::BinOp::Shl => "shl",
+        mir::BinOp::Shr => "shr",
+        mir::BinOp::Eq => "eq",
+        mir::BinOp::Lt => "lt",
+        mir::BinOp::Le => "le",
+        mir::BinOp::Ne => "ne",
+        mir::BinOp::Ge => "ge",
+        mir::BinOp::Gt => "gt",
+        mir::BinOp::Offset => "offset",
+    }
+}
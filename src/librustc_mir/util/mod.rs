@@ -6,6 +6,7 @@ pub mod patch;
 
 mod alignment;
 mod graphviz;
+mod json;
 pub(crate) mod pretty;
 pub mod liveness;
 pub mod collect_writes;
@@ -15,3 +16,4 @@ pub use self::alignment::is_disaligned;
 pub use self::pretty::{dump_enabled, dump_mir, write_mir_pretty, PassWhere};
 pub use self::graphviz::{graphviz_safe_def_name, write_mir_graphviz};
 pub use self::graphviz::write_node_label as write_graphviz_node_label;
+pub use self::json::{write_mir_json, MIR_JSON_VERSION};
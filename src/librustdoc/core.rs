@@ -19,6 +19,7 @@ use syntax::ast::CRATE_NODE_ID;
 use syntax::source_map;
 use syntax::attr;
 use errors::json::JsonEmitter;
+use errors::sarif::SarifEmitter;
 use syntax::symbol::sym;
 use syntax_pos::DUMMY_SP;
 use errors::emitter::{Emitter, EmitterWriter};
@@ -171,8 +172,8 @@ impl<'tcx> DocContext<'tcx> {
 
 /// Creates a new diagnostic `Handler` that can be used to emit warnings and errors.
 ///
-/// If the given `error_format` is `ErrorOutputType::Json` and no `SourceMap` is given, a new one
-/// will be created for the handler.
+/// If the given `error_format` is `ErrorOutputType::Json` or `ErrorOutputType::Sarif` and no
+/// `SourceMap` is given, a new one will be created for the handler.
 pub fn new_handler(error_format: ErrorOutputType,
                    source_map: Option<Lrc<source_map::SourceMap>>,
                    treat_err_as_bug: Option<usize>,
@@ -208,6 +209,11 @@ pub fn new_handler(error_format: ErrorOutputType,
                 ).ui_testing(ui_testing)
             )
         },
+        ErrorOutputType::Sarif => {
+            let source_map = source_map.unwrap_or_else(
+                || Lrc::new(source_map::SourceMap::new(sessopts.file_path_mapping())));
+            Box::new(SarifEmitter::stderr(None, source_map))
+        },
     };
 
     errors::Handler::with_emitter_and_flags(
@@ -93,9 +93,12 @@ pub fn main() {
     };
     rustc_driver::set_sigpipe_handler();
     env_logger::init_from_env("RUSTDOC_LOG");
-    let res = std::thread::Builder::new().stack_size(thread_stack_size).spawn(move || {
+    let mut res = std::thread::Builder::new().stack_size(thread_stack_size).spawn(move || {
         get_args().map(|args| main_args(&args)).unwrap_or(1)
     }).unwrap().join().unwrap_or(rustc_driver::EXIT_FAILURE);
+    if errors::sarif::flush_stderr_log().is_err() {
+        res = rustc_driver::EXIT_FAILURE;
+    }
     process::exit(res);
 }
 
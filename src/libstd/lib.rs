@@ -306,6 +306,7 @@
 #![feature(stmt_expr_attributes)]
 #![feature(str_internals)]
 #![feature(test)]
+#![cfg_attr(not(bootstrap), feature(test_fixtures))]
 #![feature(thread_local)]
 #![feature(toowned_clone_into)]
 #![feature(trace_macros)]
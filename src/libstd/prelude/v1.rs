@@ -53,6 +53,15 @@ pub use core::prelude::v1::{
     PartialEq, PartialOrd, RustcDecodable, RustcEncodable,
 };
 
+#[unstable(
+    feature = "test_fixtures",
+    issue = "0",
+    reason = "test fixtures are an experimental feature of the test harness"
+)]
+#[doc(hidden)]
+#[cfg(not(bootstrap))]
+pub use core::prelude::v1::{test_setup, test_teardown};
+
 // The file so far is equivalent to src/libcore/prelude/v1.rs,
 // and below to src/liballoc/prelude.rs.
 // Those files are duplicated rather than using glob imports
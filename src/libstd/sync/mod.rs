@@ -162,6 +162,8 @@ pub use self::barrier::{Barrier, BarrierWaitResult};
 pub use self::condvar::{Condvar, WaitTimeoutResult};
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use self::mutex::{Mutex, MutexGuard};
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+pub use self::mutex::MappedMutexGuard;
 #[stable(feature = "rust1", since = "1.0.0")]
 #[allow(deprecated)]
 pub use self::once::{Once, OnceState, ONCE_INIT};
@@ -169,11 +171,18 @@ pub use self::once::{Once, OnceState, ONCE_INIT};
 pub use crate::sys_common::poison::{PoisonError, TryLockError, TryLockResult, LockResult};
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use self::rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+pub use self::rwlock::{MappedRwLockReadGuard, MappedRwLockWriteGuard};
+#[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+pub use self::rwlock::RwLockUpgradableReadGuard;
+#[unstable(feature = "once_cell", issue = "0")]
+pub use self::lazy::{SyncLazy, SyncOnceCell};
 
 pub mod mpsc;
 
 mod barrier;
 mod condvar;
+mod lazy;
 mod mutex;
 mod once;
 mod rwlock;
@@ -43,6 +43,12 @@ impl SignalToken {
         wake
     }
 
+    /// Returns whether both tokens wake up the same thread. Used to find a
+    /// token again when a selection is aborted.
+    pub fn same_as(&self, other: &SignalToken) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
     /// Converts to an unsafe usize value. Useful for storing in a pipe's state
     /// flag.
     #[inline]
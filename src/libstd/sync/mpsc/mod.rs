@@ -27,12 +27,22 @@
 //!    that a bound of 0 is allowed, causing the channel to become a "rendezvous"
 //!    channel where each sender atomically hands off a message to a receiver.
 //!
+//! When a single consumer is not enough, such as for a pool of worker threads,
+//! the unstable [`mpmc_channel`] function returns a bounded channel whose
+//! receiving half can be cloned as well. Each message sent on it is received
+//! by exactly one of the receivers.
+//!
+//! The unstable [`Select`] type waits on several receivers at once, optionally
+//! with a timeout, and reports which of them is ready to receive.
+//!
 //! [`Sender`]: ../../../std/sync/mpsc/struct.Sender.html
 //! [`SyncSender`]: ../../../std/sync/mpsc/struct.SyncSender.html
 //! [`Receiver`]: ../../../std/sync/mpsc/struct.Receiver.html
 //! [`send`]: ../../../std/sync/mpsc/struct.Sender.html#method.send
 //! [`channel`]: ../../../std/sync/mpsc/fn.channel.html
 //! [`sync_channel`]: ../../../std/sync/mpsc/fn.sync_channel.html
+//! [`mpmc_channel`]: ../../../std/sync/mpsc/fn.mpmc_channel.html
+//! [`Select`]: ../../../std/sync/mpsc/struct.Select.html
 //!
 //! ## Disconnection
 //!
@@ -262,7 +272,8 @@
 // believe that there is anything fundamental that needs to change about these
 // channels, however, in order to support a more efficient select().
 //
-// FIXME: Select is now removed, so these factors are ready to be cleaned up!
+// Selection is provided again by the `Select` type in the `select` module,
+// which waits on several receivers with a single signal token.
 //
 // # Conclusion
 //
@@ -276,9 +287,17 @@ use crate::mem;
 use crate::sync::Arc;
 use crate::time::{Duration, Instant};
 
+#[unstable(feature = "mpsc_select", issue = "0")]
+pub use self::select::Select;
+
+use self::blocking::SignalToken;
+use self::select::StartResult;
+
 mod blocking;
+mod mpmc;
 mod mpsc_queue;
 mod oneshot;
+mod select;
 mod shared;
 mod spsc_queue;
 mod stream;
@@ -542,6 +561,83 @@ pub struct SyncSender<T> {
 #[stable(feature = "rust1", since = "1.0.0")]
 unsafe impl<T: Send> Send for SyncSender<T> {}
 
+/// The sending half of an [`mpmc_channel`].
+///
+/// Messages can be sent through this channel with [`send`]. Like
+/// [`SyncSender`], this half can be cloned, and sending blocks while the
+/// buffer of the channel is full.
+///
+/// Unlike [`SyncSender`], this half can also be shared between threads by
+/// reference.
+///
+/// [`mpmc_channel`]: fn.mpmc_channel.html
+/// [`send`]: struct.MultiSender.html#method.send
+/// [`SyncSender`]: struct.SyncSender.html
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(mpmc_channel)]
+/// use std::sync::mpsc::mpmc_channel;
+/// use std::thread;
+///
+/// let (sender, receiver) = mpmc_channel(2);
+/// let sender2 = sender.clone();
+///
+/// thread::spawn(move || sender.send(1).unwrap());
+/// thread::spawn(move || sender2.send(2).unwrap());
+///
+/// let sum = receiver.recv().unwrap() + receiver.recv().unwrap();
+/// assert_eq!(sum, 3);
+/// ```
+#[unstable(feature = "mpmc_channel", issue = "0")]
+pub struct MultiSender<T> {
+    inner: Arc<mpmc::Packet<T>>,
+}
+
+/// The receiving half of an [`mpmc_channel`].
+///
+/// Messages sent to the channel can be retrieved using [`recv`]. This half can
+/// be cloned, and shared between threads by reference. Every message is
+/// received by exactly one of the receivers, which makes it suitable for
+/// handing out jobs to a pool of worker threads.
+///
+/// [`mpmc_channel`]: fn.mpmc_channel.html
+/// [`recv`]: struct.MultiReceiver.html#method.recv
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(mpmc_channel)]
+/// use std::sync::mpsc::mpmc_channel;
+/// use std::thread;
+///
+/// let (jobs, receiver) = mpmc_channel::<u32>(4);
+///
+/// let workers: Vec<_> = (0..4).map(|_| {
+///     let receiver = receiver.clone();
+///     thread::spawn(move || {
+///         let mut done = 0;
+///         while let Ok(_job) = receiver.recv() {
+///             done += 1;
+///         }
+///         done
+///     })
+/// }).collect();
+///
+/// for job in 0..100 {
+///     jobs.send(job).unwrap();
+/// }
+/// drop(jobs);
+///
+/// let done: u32 = workers.into_iter().map(|w| w.join().unwrap()).sum();
+/// assert_eq!(done, 100);
+/// ```
+#[unstable(feature = "mpmc_channel", issue = "0")]
+pub struct MultiReceiver<T> {
+    inner: Arc<mpmc::Packet<T>>,
+}
+
 /// An error returned from the [`Sender::send`] or [`SyncSender::send`]
 /// function on **channel**s.
 ///
@@ -766,6 +862,53 @@ pub fn sync_channel<T>(bound: usize) -> (SyncSender<T>, Receiver<T>) {
     (SyncSender::new(a.clone()), Receiver::new(Flavor::Sync(a)))
 }
 
+/// Creates a new bounded channel which supports multiple consumers as well as
+/// multiple producers.
+///
+/// Like a [`sync_channel`], this channel has an internal buffer of `bound`
+/// messages, and [`send`] will *block* while the buffer is full. Both the
+/// returned [`MultiSender`] and [`MultiReceiver`] can be cloned, and every
+/// message is delivered to exactly one of the receivers.
+///
+/// The channel disconnects once all senders, or all receivers, are dropped.
+/// Receivers can still drain the messages left in the buffer after the last
+/// sender is gone, while messages buffered when the last receiver is dropped
+/// are destroyed.
+///
+/// [`sync_channel`]: fn.sync_channel.html
+/// [`send`]: struct.MultiSender.html#method.send
+/// [`MultiSender`]: struct.MultiSender.html
+/// [`MultiReceiver`]: struct.MultiReceiver.html
+///
+/// # Panics
+///
+/// Panics if `bound` is zero. Rendezvous channels are only available with a
+/// single receiver, through [`sync_channel`].
+///
+/// # Examples
+///
+/// ```
+/// #![feature(mpmc_channel)]
+/// use std::sync::mpsc::mpmc_channel;
+/// use std::thread;
+///
+/// let (sender, receiver) = mpmc_channel(1);
+/// let receiver2 = receiver.clone();
+///
+/// sender.send(1).unwrap();
+/// let t = thread::spawn(move || receiver2.recv().unwrap());
+/// // this will block until one of the receivers makes room
+/// sender.send(2).unwrap();
+///
+/// let sum = receiver.recv().unwrap() + t.join().unwrap();
+/// assert_eq!(sum, 3);
+/// ```
+#[unstable(feature = "mpmc_channel", issue = "0")]
+pub fn mpmc_channel<T>(bound: usize) -> (MultiSender<T>, MultiReceiver<T>) {
+    let a = Arc::new(mpmc::Packet::new(bound));
+    (MultiSender::new(a.clone()), MultiReceiver::new(a))
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Sender
 ////////////////////////////////////////////////////////////////////////////////
@@ -1527,6 +1670,51 @@ impl<T> Drop for Receiver<T> {
     }
 }
 
+impl<T> select::Packet for Receiver<T> {
+    fn start_selection(&self, mut token: SignalToken) -> StartResult {
+        loop {
+            let (t, new_port) = match *unsafe { self.inner() } {
+                Flavor::Oneshot(ref p) => match p.start_selection(token) {
+                    oneshot::SelSuccess => return select::Installed,
+                    oneshot::SelCanceled => return select::Abort,
+                    oneshot::SelUpgraded(t, rx) => (t, rx),
+                },
+                Flavor::Stream(ref p) => match p.start_selection(token) {
+                    stream::SelSuccess => return select::Installed,
+                    stream::SelCanceled => return select::Abort,
+                    stream::SelUpgraded(t, rx) => (t, rx),
+                },
+                Flavor::Shared(ref p) => return p.start_selection(token),
+                Flavor::Sync(ref p) => return p.start_selection(token),
+            };
+            token = t;
+            unsafe {
+                mem::swap(self.inner_mut(), new_port.inner_mut());
+            }
+        }
+    }
+
+    fn abort_selection(&self, _token: &SignalToken) -> bool {
+        let mut was_upgrade = false;
+        loop {
+            let result = match *unsafe { self.inner() } {
+                Flavor::Oneshot(ref p) => p.abort_selection(),
+                Flavor::Stream(ref p) => p.abort_selection(was_upgrade),
+                Flavor::Shared(ref p) => return p.abort_selection(was_upgrade),
+                Flavor::Sync(ref p) => return p.abort_selection(),
+            };
+            let new_port = match result {
+                Ok(b) => return b,
+                Err(p) => p,
+            };
+            was_upgrade = true;
+            unsafe {
+                mem::swap(self.inner_mut(), new_port.inner_mut());
+            }
+        }
+    }
+}
+
 #[stable(feature = "mpsc_debug", since = "1.8.0")]
 impl<T> fmt::Debug for Receiver<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1534,6 +1722,249 @@ impl<T> fmt::Debug for Receiver<T> {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// MultiSender
+////////////////////////////////////////////////////////////////////////////////
+
+impl<T> MultiSender<T> {
+    fn new(inner: Arc<mpmc::Packet<T>>) -> MultiSender<T> {
+        MultiSender { inner }
+    }
+
+    /// Sends a value on this channel.
+    ///
+    /// This function will *block* until space in the internal buffer becomes
+    /// available. A successful send only means that the message was buffered;
+    /// it will be picked up by one of the receivers at a later time.
+    ///
+    /// This function will never panic, but it may return [`Err`] if all
+    /// [`MultiReceiver`]s have disconnected and the message can no longer be
+    /// received.
+    ///
+    /// [`Err`]: ../../../std/result/enum.Result.html#variant.Err
+    /// [`MultiReceiver`]: struct.MultiReceiver.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(mpmc_channel)]
+    /// use std::sync::mpsc::mpmc_channel;
+    ///
+    /// let (sender, receiver) = mpmc_channel(1);
+    /// assert!(sender.send(1).is_ok());
+    ///
+    /// drop(receiver);
+    /// assert_eq!(sender.send(2).unwrap_err().0, 2);
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn send(&self, t: T) -> Result<(), SendError<T>> {
+        self.inner.send(t).map_err(SendError)
+    }
+
+    /// Attempts to send a value on this channel without blocking.
+    ///
+    /// This method differs from [`send`] by returning immediately if the
+    /// channel's buffer is full. Compared with [`send`], this function has two
+    /// failure cases instead of one (one for disconnection, one for a full
+    /// buffer).
+    ///
+    /// [`send`]: struct.MultiSender.html#method.send
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(mpmc_channel)]
+    /// use std::sync::mpsc::{mpmc_channel, TrySendError};
+    ///
+    /// let (sender, _receiver) = mpmc_channel(1);
+    /// assert_eq!(sender.try_send(1), Ok(()));
+    /// assert_eq!(sender.try_send(2), Err(TrySendError::Full(2)));
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn try_send(&self, t: T) -> Result<(), TrySendError<T>> {
+        self.inner.try_send(t)
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> Clone for MultiSender<T> {
+    fn clone(&self) -> MultiSender<T> {
+        self.inner.clone_chan();
+        MultiSender::new(self.inner.clone())
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> Drop for MultiSender<T> {
+    fn drop(&mut self) {
+        self.inner.drop_chan();
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> fmt::Debug for MultiSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiSender").finish()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// MultiReceiver
+////////////////////////////////////////////////////////////////////////////////
+
+impl<T> MultiReceiver<T> {
+    fn new(inner: Arc<mpmc::Packet<T>>) -> MultiReceiver<T> {
+        MultiReceiver { inner }
+    }
+
+    /// Attempts to return a pending value on this receiver without blocking.
+    ///
+    /// This behaves like [`Receiver::try_recv`]. Note that other receivers of
+    /// the same channel may take pending values at any time.
+    ///
+    /// [`Receiver::try_recv`]: struct.Receiver.html#method.try_recv
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(mpmc_channel)]
+    /// use std::sync::mpsc::{mpmc_channel, TryRecvError};
+    ///
+    /// let (sender, receiver) = mpmc_channel(1);
+    /// assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// sender.send(1).unwrap();
+    /// drop(sender);
+    /// assert_eq!(receiver.try_recv(), Ok(1));
+    /// assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        match self.inner.try_recv() {
+            Ok(t) => Ok(t),
+            Err(mpmc::Empty) => Err(TryRecvError::Empty),
+            Err(mpmc::Disconnected) => Err(TryRecvError::Disconnected),
+        }
+    }
+
+    /// Attempts to wait for a value on this receiver, returning an error if all
+    /// [`MultiSender`]s have hung up.
+    ///
+    /// This function will always block the current thread if there is no data
+    /// available and it's possible for more data to be sent. Messages buffered
+    /// before the last sender disconnected are still received.
+    ///
+    /// [`MultiSender`]: struct.MultiSender.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(mpmc_channel)]
+    /// use std::sync::mpsc::{mpmc_channel, RecvError};
+    /// use std::thread;
+    ///
+    /// let (sender, receiver) = mpmc_channel(1);
+    /// thread::spawn(move || sender.send(1).unwrap());
+    ///
+    /// assert_eq!(receiver.recv(), Ok(1));
+    /// assert_eq!(receiver.recv(), Err(RecvError));
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn recv(&self) -> Result<T, RecvError> {
+        match self.inner.recv(None) {
+            Ok(t) => Ok(t),
+            Err(mpmc::Empty) => unreachable!(),
+            Err(mpmc::Disconnected) => Err(RecvError),
+        }
+    }
+
+    /// Attempts to wait for a value on this receiver, returning an error if all
+    /// [`MultiSender`]s have hung up, or if it waits more than `timeout`.
+    ///
+    /// [`MultiSender`]: struct.MultiSender.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(mpmc_channel)]
+    /// use std::sync::mpsc::{mpmc_channel, RecvTimeoutError};
+    /// use std::time::Duration;
+    ///
+    /// let (_sender, receiver) = mpmc_channel::<i32>(1);
+    /// assert_eq!(
+    ///     receiver.recv_timeout(Duration::from_millis(10)),
+    ///     Err(RecvTimeoutError::Timeout)
+    /// );
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.recv_deadline(deadline),
+            // So far in the future that it's practically the same as waiting indefinitely.
+            None => self.recv().map_err(RecvTimeoutError::from),
+        }
+    }
+
+    /// Attempts to wait for a value on this receiver, returning an error if all
+    /// [`MultiSender`]s have hung up, or if `deadline` is reached.
+    ///
+    /// [`MultiSender`]: struct.MultiSender.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(mpmc_channel)]
+    /// use std::sync::mpsc::mpmc_channel;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let (sender, receiver) = mpmc_channel(1);
+    /// sender.send('a').unwrap();
+    ///
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    /// assert_eq!(receiver.recv_deadline(deadline), Ok('a'));
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        match self.inner.recv(Some(deadline)) {
+            Ok(t) => Ok(t),
+            Err(mpmc::Empty) => Err(RecvTimeoutError::Timeout),
+            Err(mpmc::Disconnected) => Err(RecvTimeoutError::Disconnected),
+        }
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> Clone for MultiReceiver<T> {
+    fn clone(&self) -> MultiReceiver<T> {
+        self.inner.clone_port();
+        MultiReceiver::new(self.inner.clone())
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> Drop for MultiReceiver<T> {
+    fn drop(&mut self) {
+        self.inner.drop_port();
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> fmt::Debug for MultiReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiReceiver").finish()
+    }
+}
+
+impl<T> select::Packet for MultiReceiver<T> {
+    fn start_selection(&self, token: SignalToken) -> StartResult {
+        self.inner.start_selection(token)
+    }
+
+    fn abort_selection(&self, token: &SignalToken) -> bool {
+        self.inner.abort_selection(token)
+    }
+}
+
 #[stable(feature = "rust1", since = "1.0.0")]
 impl<T> fmt::Debug for SendError<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -3036,3 +3467,173 @@ mod sync_tests {
         }
     }
 }
+
+#[cfg(all(test, not(target_os = "emscripten")))]
+mod mpmc_tests {
+    use super::*;
+    use crate::thread;
+    use crate::time::Duration;
+
+    #[test]
+    fn smoke() {
+        let (tx, rx) = mpmc_channel::<i32>(1);
+        tx.send(1).unwrap();
+        assert_eq!(rx.recv().unwrap(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_bound() {
+        mpmc_channel::<i32>(0);
+    }
+
+    #[test]
+    fn drop_full() {
+        let (tx, _rx) = mpmc_channel::<Box<isize>>(1);
+        tx.send(box 1).unwrap();
+    }
+
+    #[test]
+    fn smoke_shared() {
+        let (tx, rx) = mpmc_channel::<i32>(2);
+        let tx2 = tx.clone();
+        let rx2 = rx.clone();
+        tx.send(1).unwrap();
+        tx2.send(2).unwrap();
+        assert_eq!(rx2.recv().unwrap(), 1);
+        assert_eq!(rx.recv().unwrap(), 2);
+    }
+
+    #[test]
+    fn port_gone() {
+        let (tx, rx) = mpmc_channel::<i32>(1);
+        let rx2 = rx.clone();
+        drop(rx);
+        assert!(tx.send(1).is_ok());
+        drop(rx2);
+        assert!(tx.send(2).is_err());
+    }
+
+    #[test]
+    fn chan_gone() {
+        let (tx, rx) = mpmc_channel::<i32>(2);
+        let tx2 = tx.clone();
+        tx.send(1).unwrap();
+        drop(tx);
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        drop(tx2);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+        assert_eq!(rx.recv(), Err(RecvError));
+    }
+
+    #[test]
+    fn try_send() {
+        let (tx, rx) = mpmc_channel::<i32>(1);
+        assert_eq!(tx.try_send(1), Ok(()));
+        assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+        drop(rx);
+        assert_eq!(tx.try_send(3), Err(TrySendError::Disconnected(3)));
+    }
+
+    #[test]
+    fn recv_timeout() {
+        let (tx, rx) = mpmc_channel::<i32>(1);
+        assert_eq!(rx.recv_timeout(Duration::from_millis(1)), Err(RecvTimeoutError::Timeout));
+        tx.send(1).unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_millis(1)), Ok(1));
+        drop(tx);
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(1)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn send_blocks_until_space() {
+        let (tx, rx) = mpmc_channel::<i32>(1);
+        tx.send(1).unwrap();
+        let t = thread::spawn(move || {
+            tx.send(2).unwrap();
+        });
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.recv(), Ok(2));
+        t.join().unwrap();
+    }
+
+    #[test]
+    fn blocked_sender_sees_disconnect() {
+        let (tx, rx) = mpmc_channel::<i32>(1);
+        tx.send(1).unwrap();
+        let t = thread::spawn(move || tx.send(2));
+        thread::sleep(Duration::from_millis(10));
+        drop(rx);
+        assert_eq!(t.join().unwrap(), Err(SendError(2)));
+    }
+
+    #[test]
+    fn blocked_receivers_see_disconnect() {
+        let (tx, rx) = mpmc_channel::<i32>(1);
+        let ts: Vec<_> = (0..4)
+            .map(|_| {
+                let rx = rx.clone();
+                thread::spawn(move || rx.recv())
+            })
+            .collect();
+        thread::sleep(Duration::from_millis(10));
+        drop(tx);
+        for t in ts {
+            assert_eq!(t.join().unwrap(), Err(RecvError));
+        }
+    }
+
+    #[test]
+    fn shared_by_reference() {
+        fn is_sync<T: Sync>(_: &T) {}
+        let (tx, rx) = mpmc_channel::<i32>(1);
+        is_sync(&tx);
+        is_sync(&rx);
+    }
+
+    #[test]
+    fn stress() {
+        const AMT: u32 = 10000;
+        const NTHREADS: u32 = 8;
+        let (tx, rx) = mpmc_channel::<u32>(4);
+        let (total_tx, total_rx) = channel::<u32>();
+
+        let consumers: Vec<_> = (0..NTHREADS)
+            .map(|_| {
+                let rx = rx.clone();
+                let total_tx = total_tx.clone();
+                thread::spawn(move || {
+                    let mut sum = 0;
+                    while let Ok(msg) = rx.recv() {
+                        sum += msg;
+                    }
+                    total_tx.send(sum).unwrap();
+                })
+            })
+            .collect();
+        drop(rx);
+        drop(total_tx);
+
+        let producers: Vec<_> = (0..NTHREADS)
+            .map(|_| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for _ in 0..AMT {
+                        tx.send(1).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        for t in producers.into_iter().chain(consumers) {
+            t.join().unwrap();
+        }
+        assert_eq!(total_rx.iter().sum::<u32>(), AMT * NTHREADS);
+    }
+}
@@ -0,0 +1,206 @@
+/// Multi-consumer channels
+///
+/// This is the flavor behind `mpmc_channel`: a bounded buffer which, unlike
+/// all the other flavors, may be shared by any number of receivers as well as
+/// senders. Each message is delivered to exactly one of the receivers.
+///
+/// The implementation is a mutex around a ring buffer plus two condition
+/// variables, one for receivers waiting for data and one for senders waiting
+/// for space. Threads selecting over this channel are not blocked on either
+/// condition variable; instead their signal tokens are kept in a list and all
+/// of them are woken whenever new data arrives or the channel disconnects.
+/// Like in the sync flavor, no user code is ever run while the lock is held.
+pub use self::Failure::*;
+
+use core::mem;
+
+use crate::collections::VecDeque;
+use crate::sync::mpsc::blocking::SignalToken;
+use crate::sync::mpsc::select::StartResult::{self, *};
+use crate::sync::{Condvar, Mutex, MutexGuard};
+use crate::time::Instant;
+
+pub struct Packet<T> {
+    lock: Mutex<State<T>>,
+    not_empty: Condvar, // signaled for receivers when data arrives
+    not_full: Condvar,  // signaled for senders when space frees up
+}
+
+struct State<T> {
+    buf: VecDeque<T>, // storage for buffered messages
+    cap: usize,       // capacity of this channel, never 0
+    senders: usize,   // number of live senders
+    receivers: usize, // number of live receivers
+
+    /// Threads currently selecting over this channel.
+    selecting: Vec<SignalToken>,
+}
+
+pub enum Failure {
+    Empty,
+    Disconnected,
+}
+
+impl<T> State<T> {
+    fn can_recv(&self) -> bool {
+        !self.buf.is_empty() || self.senders == 0
+    }
+}
+
+impl<T> Packet<T> {
+    pub fn new(capacity: usize) -> Packet<T> {
+        assert!(capacity > 0, "mpmc channels need a capacity of at least one");
+        Packet {
+            lock: Mutex::new(State {
+                buf: VecDeque::with_capacity(capacity),
+                cap: capacity,
+                senders: 1,
+                receivers: 1,
+                selecting: Vec::new(),
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    pub fn send(&self, t: T) -> Result<(), T> {
+        let mut guard = self.lock.lock().unwrap();
+        loop {
+            if guard.receivers == 0 {
+                return Err(t);
+            }
+            if guard.buf.len() < guard.cap {
+                break;
+            }
+            guard = self.not_full.wait(guard).unwrap();
+        }
+        guard.buf.push_back(t);
+        self.wakeup_receivers(guard);
+        Ok(())
+    }
+
+    pub fn try_send(&self, t: T) -> Result<(), super::TrySendError<T>> {
+        let mut guard = self.lock.lock().unwrap();
+        if guard.receivers == 0 {
+            Err(super::TrySendError::Disconnected(t))
+        } else if guard.buf.len() == guard.cap {
+            Err(super::TrySendError::Full(t))
+        } else {
+            guard.buf.push_back(t);
+            self.wakeup_receivers(guard);
+            Ok(())
+        }
+    }
+
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, Failure> {
+        let mut guard = self.lock.lock().unwrap();
+        loop {
+            // N.B., there may be data left over after the last sender hung up,
+            // so the order of these checks is important.
+            if let Some(t) = guard.buf.pop_front() {
+                drop(guard);
+                self.not_full.notify_one();
+                return Ok(t);
+            }
+            if guard.senders == 0 {
+                return Err(Disconnected);
+            }
+            guard = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(Empty);
+                    }
+                    self.not_empty.wait_timeout(guard, deadline - now).unwrap().0
+                }
+                None => self.not_empty.wait(guard).unwrap(),
+            };
+        }
+    }
+
+    pub fn try_recv(&self) -> Result<T, Failure> {
+        let mut guard = self.lock.lock().unwrap();
+        match guard.buf.pop_front() {
+            Some(t) => {
+                drop(guard);
+                self.not_full.notify_one();
+                Ok(t)
+            }
+            None if guard.senders == 0 => Err(Disconnected),
+            None => Err(Empty),
+        }
+    }
+
+    // Wakes up one blocked receiver and every selecting thread after some data
+    // has been sent, dropping the lock before anyone is woken.
+    fn wakeup_receivers(&self, mut guard: MutexGuard<'_, State<T>>) {
+        let selecting = mem::take(&mut guard.selecting);
+        drop(guard);
+        self.not_empty.notify_one();
+        for token in selecting {
+            token.signal();
+        }
+    }
+
+    pub fn clone_chan(&self) {
+        self.lock.lock().unwrap().senders += 1;
+    }
+
+    pub fn drop_chan(&self) {
+        let mut guard = self.lock.lock().unwrap();
+        guard.senders -= 1;
+        if guard.senders > 0 {
+            return;
+        }
+
+        // The channel is now disconnected, so every receiver which is blocked
+        // or selecting has something to act upon.
+        let selecting = mem::take(&mut guard.selecting);
+        drop(guard);
+        self.not_empty.notify_all();
+        for token in selecting {
+            token.signal();
+        }
+    }
+
+    pub fn clone_port(&self) {
+        self.lock.lock().unwrap().receivers += 1;
+    }
+
+    pub fn drop_port(&self) {
+        let mut guard = self.lock.lock().unwrap();
+        guard.receivers -= 1;
+        if guard.receivers > 0 {
+            return;
+        }
+
+        // Nobody is left to receive the buffered data, so destroy it now rather
+        // than when the last sender goes away. As in the sync flavor, this is
+        // done outside of the lock.
+        let _data = mem::take(&mut guard.buf);
+        drop(guard);
+        self.not_full.notify_all();
+    }
+
+    ////////////////////////////////////////////////////////////////////////////
+    // select implementation
+    ////////////////////////////////////////////////////////////////////////////
+
+    pub fn start_selection(&self, token: SignalToken) -> StartResult {
+        let mut guard = self.lock.lock().unwrap();
+        if guard.can_recv() {
+            Abort
+        } else {
+            guard.selecting.push(token);
+            Installed
+        }
+    }
+
+    // Removes `token` from the selecting threads if it is still there, and
+    // returns whether there's data (or a disconnection) on this port.
+    pub fn abort_selection(&self, token: &SignalToken) -> bool {
+        let mut guard = self.lock.lock().unwrap();
+        guard.selecting.retain(|t| !t.same_as(token));
+        guard.can_recv()
+    }
+}
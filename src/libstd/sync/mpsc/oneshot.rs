@@ -23,6 +23,7 @@
 /// it must check for data because there is no "data plus upgrade" state.
 pub use self::Failure::*;
 use self::MyUpgrade::*;
+pub use self::SelectionResult::*;
 pub use self::UpgradeResult::*;
 
 use crate::cell::UnsafeCell;
@@ -64,6 +65,12 @@ pub enum UpgradeResult {
     UpWoke(SignalToken),
 }
 
+pub enum SelectionResult<T> {
+    SelCanceled,
+    SelUpgraded(SignalToken, Receiver<T>),
+    SelSuccess,
+}
+
 enum MyUpgrade<T> {
     NothingSent,
     SendUsed,
@@ -257,6 +264,42 @@ impl<T> Packet<T> {
     // select implementation
     ////////////////////////////////////////////////////////////////////////////
 
+    // Attempts to start selection on this port. This can either succeed, fail
+    // because there is data, or fail because there is an upgrade pending.
+    pub fn start_selection(&self, token: SignalToken) -> SelectionResult<T> {
+        let ptr = unsafe { token.cast_to_usize() };
+        match self.state.compare_and_swap(EMPTY, ptr, Ordering::SeqCst) {
+            EMPTY => SelSuccess,
+            DATA => {
+                drop(unsafe { SignalToken::cast_from_usize(ptr) });
+                SelCanceled
+            }
+            DISCONNECTED if unsafe { (*self.data.get()).is_some() } => {
+                drop(unsafe { SignalToken::cast_from_usize(ptr) });
+                SelCanceled
+            }
+            DISCONNECTED => match unsafe { ptr::replace(self.upgrade.get(), SendUsed) } {
+                // The other end sent us an upgrade, so we need to propagate
+                // upwards whether the upgrade can receive data or not.
+                GoUp(upgrade) => {
+                    SelUpgraded(unsafe { SignalToken::cast_from_usize(ptr) }, upgrade)
+                }
+
+                // If the other end disconnected without sending an upgrade,
+                // then we have data to receive (the channel is disconnected).
+                up => {
+                    unsafe { ptr::write(self.upgrade.get(), up) };
+                    drop(unsafe { SignalToken::cast_from_usize(ptr) });
+                    SelCanceled
+                }
+            },
+
+            // We are the sole receiver; there cannot be a blocking
+            // receiver already.
+            _ => unreachable!(),
+        }
+    }
+
     // Remove a previous selecting thread from this port. This ensures that the
     // blocked thread will no longer be visible to any other threads.
     //
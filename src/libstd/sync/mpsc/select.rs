@@ -0,0 +1,607 @@
+//! Selection over a set of receivers
+//!
+//! This module contains the implementation of waiting on several receivers at
+//! once, returning as soon as one of them is ready to receive without blocking.
+//!
+//! Selection is built on the same blocking protocol as a plain `recv`: one
+//! signal token is installed on every receiver in the set, the thread goes to
+//! sleep, and whichever channel sees activity first wakes it up. Afterwards
+//! the token is removed from every receiver again ("aborting" the selection),
+//! and each receiver reports whether it has data along the way.
+//!
+//! # Examples
+//!
+//! ```
+//! #![feature(mpsc_select)]
+//! use std::sync::mpsc::{channel, Select};
+//! use std::thread;
+//!
+//! let (_tx1, rx1) = channel::<i32>();
+//! let (tx2, rx2) = channel::<&str>();
+//!
+//! thread::spawn(move || tx2.send("hello").unwrap());
+//!
+//! let mut sel = Select::new();
+//! let r1 = sel.recv(&rx1);
+//! let r2 = sel.recv(&rx2);
+//!
+//! let ready = sel.ready();
+//! if ready == r1 {
+//!     println!("rx1 produced {:?}", rx1.try_recv());
+//! } else if ready == r2 {
+//!     println!("rx2 produced {:?}", rx2.try_recv());
+//! }
+//! ```
+
+pub use self::StartResult::*;
+
+use crate::fmt;
+use crate::sync::mpsc::blocking::{self, SignalToken};
+use crate::sync::mpsc::{MultiReceiver, Receiver};
+use crate::time::{Duration, Instant};
+
+/// The result of installing a signal token on a receiver.
+#[derive(PartialEq, Eq)]
+pub enum StartResult {
+    Installed,
+    Abort,
+}
+
+/// A receiver which can take part in a selection.
+pub trait Packet {
+    /// Installs `token` on this receiver, to be signaled as soon as the
+    /// receiver has data or is disconnected. Returns `Abort` if that is already
+    /// the case, in which case the token was not installed.
+    fn start_selection(&self, token: SignalToken) -> StartResult;
+
+    /// Removes a token previously installed with `start_selection`, returning
+    /// whether the receiver is now ready.
+    fn abort_selection(&self, token: &SignalToken) -> bool;
+}
+
+/// A set of receivers to wait on at the same time.
+///
+/// Receivers are added with [`recv`] (or [`recv_multi`] for the receiving
+/// half of an [`mpmc_channel`]), each of which returns the index under which
+/// the receiver was added. The [`ready`] family of methods then blocks until
+/// one of the receivers is *ready*, and returns its index.
+///
+/// A receiver is ready when a [`try_recv`] on it would not return
+/// [`TryRecvError::Empty`]: either a message is waiting, or all senders are
+/// gone and the channel is disconnected. Only the readiness of the receiver is
+/// reported, no message is taken off the channel, so the caller is expected
+/// to follow up with a receive on the receiver that was picked.
+///
+/// If several receivers are ready at the same time, they are picked in a
+/// rotating order, so that a busy receiver added first cannot starve the
+/// others.
+///
+/// [`recv`]: #method.recv
+/// [`recv_multi`]: #method.recv_multi
+/// [`ready`]: #method.ready
+/// [`mpmc_channel`]: fn.mpmc_channel.html
+/// [`try_recv`]: struct.Receiver.html#method.try_recv
+/// [`TryRecvError::Empty`]: enum.TryRecvError.html#variant.Empty
+///
+/// # Examples
+///
+/// Waiting for work from several channels, with a timeout:
+///
+/// ```
+/// #![feature(mpsc_select)]
+/// use std::sync::mpsc::{channel, Select};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let (jobs_tx, jobs) = channel::<u32>();
+/// let (_control_tx, control) = channel::<()>();
+///
+/// thread::spawn(move || {
+///     for i in 0..3 {
+///         jobs_tx.send(i).unwrap();
+///     }
+/// });
+///
+/// let mut sel = Select::new();
+/// let jobs_idx = sel.recv(&jobs);
+/// let control_idx = sel.recv(&control);
+///
+/// let mut done = 0;
+/// while let Some(i) = sel.ready_timeout(Duration::from_secs(1)) {
+///     if i == jobs_idx {
+///         match jobs.try_recv() {
+///             Ok(_job) => done += 1,
+///             // all job senders are gone
+///             Err(_) => break,
+///         }
+///     } else if i == control_idx {
+///         break;
+///     }
+/// }
+/// assert_eq!(done, 3);
+/// ```
+#[unstable(feature = "mpsc_select", issue = "0")]
+pub struct Select<'a> {
+    packets: Vec<&'a dyn Packet>,
+    // where the next selection starts looking, for fairness
+    next: usize,
+}
+
+impl<'a> Select<'a> {
+    /// Creates a new, empty set of receivers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::Select;
+    ///
+    /// let mut sel = Select::new();
+    /// assert_eq!(sel.try_ready(), None);
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "0")]
+    pub fn new() -> Select<'a> {
+        Select { packets: Vec::new(), next: 0 }
+    }
+
+    /// Adds a receiver to this set, returning the index which identifies it in
+    /// the results of [`ready`] and friends.
+    ///
+    /// Indices are handed out in order, starting at 0. Adding a receiver which
+    /// is already in the set returns the index it was first added under.
+    ///
+    /// [`ready`]: #method.ready
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::{channel, Select};
+    ///
+    /// let (_tx1, rx1) = channel::<i32>();
+    /// let (_tx2, rx2) = channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// assert_eq!(sel.recv(&rx1), 0);
+    /// assert_eq!(sel.recv(&rx2), 1);
+    /// assert_eq!(sel.recv(&rx1), 0);
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "0")]
+    pub fn recv<T>(&mut self, rx: &'a Receiver<T>) -> usize {
+        self.add(rx)
+    }
+
+    /// Adds the receiving half of an [`mpmc_channel`] to this set, returning
+    /// the index which identifies it in the results of [`ready`] and friends.
+    ///
+    /// Other clones of the receiver keep receiving while this thread selects,
+    /// so by the time the caller gets around to receiving, another consumer
+    /// may already have taken the message which made the receiver ready. A
+    /// [`try_recv`] after a successful selection may therefore still report
+    /// that the channel is empty.
+    ///
+    /// [`mpmc_channel`]: fn.mpmc_channel.html
+    /// [`ready`]: #method.ready
+    /// [`try_recv`]: struct.MultiReceiver.html#method.try_recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select, mpmc_channel)]
+    /// use std::sync::mpsc::{channel, mpmc_channel, Select};
+    ///
+    /// let (_tx1, rx1) = channel::<i32>();
+    /// let (tx2, rx2) = mpmc_channel::<i32>(1);
+    /// tx2.send(2).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&rx1);
+    /// let idx = sel.recv_multi(&rx2);
+    /// assert_eq!(sel.ready(), idx);
+    /// assert_eq!(rx2.try_recv(), Ok(2));
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "0")]
+    pub fn recv_multi<T>(&mut self, rx: &'a MultiReceiver<T>) -> usize {
+        self.add(rx)
+    }
+
+    fn add(&mut self, packet: &'a dyn Packet) -> usize {
+        // A receiver only has room for a single token, so it must not be
+        // selected on twice.
+        let addr = |p: &dyn Packet| p as *const _ as *const ();
+        if let Some(i) = self.packets.iter().position(|&p| addr(p) == addr(packet)) {
+            return i;
+        }
+        self.packets.push(packet);
+        self.packets.len() - 1
+    }
+
+    /// Returns the index of a ready receiver, without blocking.
+    ///
+    /// Returns `None` if none of the receivers is ready.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::{channel, Select};
+    ///
+    /// let (tx, rx) = channel();
+    ///
+    /// let mut sel = Select::new();
+    /// let idx = sel.recv(&rx);
+    /// assert_eq!(sel.try_ready(), None);
+    ///
+    /// tx.send(1).unwrap();
+    /// assert_eq!(sel.try_ready(), Some(idx));
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "0")]
+    pub fn try_ready(&mut self) -> Option<usize> {
+        self.wait(Some(Instant::now()))
+    }
+
+    /// Blocks until one of the receivers is ready, and returns its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no receivers have been added, as this would block forever.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::{channel, Select};
+    /// use std::thread;
+    ///
+    /// let (_tx1, rx1) = channel::<i32>();
+    /// let (tx2, rx2) = channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&rx1);
+    /// let idx = sel.recv(&rx2);
+    ///
+    /// thread::spawn(move || tx2.send(2).unwrap());
+    /// assert_eq!(sel.ready(), idx);
+    /// assert_eq!(rx2.recv(), Ok(2));
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "0")]
+    pub fn ready(&mut self) -> usize {
+        assert!(!self.packets.is_empty(), "no receivers to select on");
+        match self.wait(None) {
+            Some(i) => i,
+            None => unreachable!(),
+        }
+    }
+
+    /// Blocks until one of the receivers is ready, or until `timeout` has
+    /// elapsed.
+    ///
+    /// Returns the index of the ready receiver, or `None` if the timeout was
+    /// reached first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::{channel, Select};
+    /// use std::time::Duration;
+    ///
+    /// let (_tx, rx) = channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&rx);
+    /// assert_eq!(sel.ready_timeout(Duration::from_millis(10)), None);
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "0")]
+    pub fn ready_timeout(&mut self, timeout: Duration) -> Option<usize> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.ready_deadline(deadline),
+            // Nothing to wait for, like with a shorter timeout.
+            None if self.packets.is_empty() => None,
+            // So far in the future that it's practically the same as waiting indefinitely.
+            None => Some(self.ready()),
+        }
+    }
+
+    /// Blocks until one of the receivers is ready, or until `deadline` is
+    /// reached.
+    ///
+    /// Returns the index of the ready receiver, or `None` if the deadline was
+    /// reached first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::{channel, Select};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let (tx, rx) = channel();
+    /// tx.send(1).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// let idx = sel.recv(&rx);
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    /// assert_eq!(sel.ready_deadline(deadline), Some(idx));
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "0")]
+    pub fn ready_deadline(&mut self, deadline: Instant) -> Option<usize> {
+        self.wait(Some(deadline))
+    }
+
+    fn wait(&mut self, deadline: Option<Instant>) -> Option<usize> {
+        let len = self.packets.len();
+        let start = self.next;
+        loop {
+            let (wait_token, signal_token) = blocking::tokens();
+
+            // Install the token on every receiver, stopping early if one of
+            // them turns out to be ready already.
+            let mut ready = None;
+            let mut started = 0;
+            for i in (0..len).map(|k| (start + k) % len) {
+                match self.packets[i].start_selection(signal_token.clone()) {
+                    Installed => started += 1,
+                    Abort => {
+                        ready = Some(i);
+                        break;
+                    }
+                }
+            }
+
+            if ready.is_none() {
+                match deadline {
+                    Some(deadline) => {
+                        wait_token.wait_max_until(deadline);
+                    }
+                    None => wait_token.wait(),
+                }
+            }
+
+            // Every receiver which has the token installed needs to give it
+            // back, even once a ready receiver has been found.
+            for i in (0..started).map(|k| (start + k) % len) {
+                if self.packets[i].abort_selection(&signal_token) && ready.is_none() {
+                    ready = Some(i);
+                }
+            }
+
+            if let Some(i) = ready {
+                self.next = (i + 1) % len;
+                return Some(i);
+            }
+            if let Some(deadline) = deadline {
+                if Instant::now() >= deadline {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+#[unstable(feature = "mpsc_select", issue = "0")]
+impl<'a> Default for Select<'a> {
+    fn default() -> Select<'a> {
+        Select::new()
+    }
+}
+
+#[unstable(feature = "mpsc_select", issue = "0")]
+impl<'a> fmt::Debug for Select<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Select").field("receivers", &self.packets.len()).finish()
+    }
+}
+
+#[cfg(all(test, not(target_os = "emscripten")))]
+mod tests {
+    use super::*;
+    use crate::sync::mpsc::*;
+    use crate::thread;
+    use crate::time::{Duration, Instant};
+
+    #[test]
+    fn smoke() {
+        let (tx1, rx1) = channel::<i32>();
+        let (tx2, rx2) = channel::<i32>();
+        let mut sel = Select::new();
+        let a = sel.recv(&rx1);
+        let b = sel.recv(&rx2);
+
+        tx1.send(1).unwrap();
+        assert_eq!(sel.ready(), a);
+        assert_eq!(rx1.try_recv(), Ok(1));
+
+        tx2.send(2).unwrap();
+        assert_eq!(sel.ready(), b);
+        assert_eq!(rx2.try_recv(), Ok(2));
+
+        drop(tx1);
+        assert_eq!(sel.ready(), a);
+        assert_eq!(rx1.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn empty() {
+        let mut sel = Select::new();
+        assert_eq!(sel.try_ready(), None);
+        assert_eq!(sel.ready_timeout(Duration::from_millis(1)), None);
+        assert_eq!(sel.ready_timeout(Duration::from_secs(u64::max_value())), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_ready() {
+        Select::new().ready();
+    }
+
+    #[test]
+    fn duplicates() {
+        let (tx1, rx1) = channel::<i32>();
+        let (tx2, rx2) = sync_channel::<i32>(1);
+        let (_tx3, rx3) = mpmc_channel::<i32>(1);
+        let mut sel = Select::new();
+        let a = sel.recv(&rx1);
+        let b = sel.recv(&rx2);
+        let c = sel.recv_multi(&rx3);
+        assert_eq!(sel.recv(&rx1), a);
+        assert_eq!(sel.recv(&rx2), b);
+        assert_eq!(sel.recv_multi(&rx3), c);
+
+        let t = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            tx1.send(1).unwrap();
+            thread::sleep(Duration::from_millis(10));
+            tx2.send(2).unwrap();
+        });
+        assert_eq!(sel.ready(), a);
+        assert_eq!(rx1.recv(), Ok(1));
+        assert_eq!(sel.ready(), b);
+        assert_eq!(rx2.recv(), Ok(2));
+        t.join().unwrap();
+    }
+
+    #[test]
+    fn try_ready() {
+        let (tx, rx) = channel::<i32>();
+        let (stx, srx) = sync_channel::<i32>(1);
+        let mut sel = Select::new();
+        let a = sel.recv(&rx);
+        let b = sel.recv(&srx);
+        assert_eq!(sel.try_ready(), None);
+
+        stx.send(1).unwrap();
+        assert_eq!(sel.try_ready(), Some(b));
+        assert_eq!(srx.recv(), Ok(1));
+        assert_eq!(sel.try_ready(), None);
+
+        drop(tx);
+        assert_eq!(sel.try_ready(), Some(a));
+    }
+
+    #[test]
+    fn timeout() {
+        let (_tx1, rx1) = channel::<i32>();
+        let (_tx2, rx2) = sync_channel::<i32>(0);
+        let mut sel = Select::new();
+        sel.recv(&rx1);
+        sel.recv(&rx2);
+
+        let start = Instant::now();
+        assert_eq!(sel.ready_timeout(Duration::from_millis(50)), None);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn blocking() {
+        let (tx1, rx1) = channel::<i32>();
+        let (tx2, rx2) = sync_channel::<i32>(0);
+        let (tx3, rx3) = mpmc_channel::<i32>(1);
+        let mut sel = Select::new();
+        let a = sel.recv(&rx1);
+        let b = sel.recv(&rx2);
+        let c = sel.recv_multi(&rx3);
+
+        let t = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            tx2.send(2).unwrap();
+            thread::sleep(Duration::from_millis(10));
+            tx3.send(3).unwrap();
+            thread::sleep(Duration::from_millis(10));
+            tx1.send(1).unwrap();
+        });
+
+        assert_eq!(sel.ready(), b);
+        assert_eq!(rx2.recv(), Ok(2));
+        assert_eq!(sel.ready(), c);
+        assert_eq!(rx3.recv(), Ok(3));
+        assert_eq!(sel.ready(), a);
+        assert_eq!(rx1.recv(), Ok(1));
+        t.join().unwrap();
+    }
+
+    #[test]
+    fn fairness() {
+        let (tx1, rx1) = channel::<i32>();
+        let (tx2, rx2) = channel::<i32>();
+        for _ in 0..3 {
+            tx1.send(1).unwrap();
+            tx2.send(2).unwrap();
+        }
+
+        let mut sel = Select::new();
+        let a = sel.recv(&rx1);
+        let b = sel.recv(&rx2);
+        assert_eq!(sel.ready(), a);
+        assert_eq!(sel.ready(), b);
+        assert_eq!(sel.ready(), a);
+        assert_eq!(sel.ready(), b);
+    }
+
+    #[test]
+    fn upgrades() {
+        // The receiver starts out as a oneshot, is upgraded to a stream by the
+        // second send and to a shared channel when the sender is cloned, all
+        // while a selection may be in progress.
+        let (tx, rx) = channel::<i32>();
+        let (_tx2, rx2) = channel::<i32>();
+        let mut sel = Select::new();
+        let a = sel.recv(&rx);
+        sel.recv(&rx2);
+
+        let t = thread::spawn(move || {
+            for i in 0..3 {
+                thread::sleep(Duration::from_millis(10));
+                tx.send(i).unwrap();
+            }
+            let tx3 = tx.clone();
+            thread::sleep(Duration::from_millis(10));
+            tx3.send(3).unwrap();
+        });
+
+        for i in 0..4 {
+            assert_eq!(sel.ready(), a);
+            assert_eq!(rx.recv(), Ok(i));
+        }
+        t.join().unwrap();
+        assert_eq!(sel.ready(), a);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn stress() {
+        const AMT: i32 = 10000;
+        let (tx1, rx1) = channel::<i32>();
+        let (tx2, rx2) = sync_channel::<i32>(4);
+        let t = thread::spawn(move || {
+            for i in 0..AMT {
+                if i % 2 == 0 {
+                    tx1.send(i).unwrap();
+                } else {
+                    tx2.send(i).unwrap();
+                }
+            }
+        });
+
+        let mut sel = Select::new();
+        let a = sel.recv(&rx1);
+        let b = sel.recv(&rx2);
+        let mut sum = 0;
+        let mut received = 0;
+        while received < AMT {
+            let i = sel.ready();
+            let msg = if i == a {
+                rx1.try_recv()
+            } else {
+                assert_eq!(i, b);
+                rx2.try_recv()
+            };
+            if let Ok(msg) = msg {
+                sum += msg as i64;
+                received += 1;
+            }
+        }
+        t.join().unwrap();
+        assert_eq!(sum, (0..AMT as i64).sum::<i64>());
+    }
+}
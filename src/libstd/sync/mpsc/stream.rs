@@ -8,6 +8,7 @@
 /// module.
 pub use self::Failure::*;
 use self::Message::*;
+pub use self::SelectionResult::*;
 pub use self::UpgradeResult::*;
 
 use core::cmp;
@@ -57,6 +58,12 @@ pub enum UpgradeResult {
     UpWoke(SignalToken),
 }
 
+pub enum SelectionResult<T> {
+    SelSuccess,
+    SelCanceled,
+    SelUpgraded(SignalToken, Receiver<T>),
+}
+
 // Any message could contain an "upgrade request" to a new shared port, so the
 // internal queue it's a queue of T, but rather Message<T>
 enum Message<T> {
@@ -357,6 +364,29 @@ impl<T> Packet<T> {
         }
     }
 
+    // Attempts to start selecting on this port. Like a oneshot, this can fail
+    // immediately because of an upgrade.
+    pub fn start_selection(&self, token: SignalToken) -> SelectionResult<T> {
+        match self.decrement(token) {
+            Ok(()) => SelSuccess,
+            Err(token) => {
+                let ret = match self.queue.peek() {
+                    Some(&mut GoUp(..)) => match self.queue.pop() {
+                        Some(GoUp(port)) => SelUpgraded(token, port),
+                        _ => unreachable!(),
+                    },
+                    Some(..) | None => SelCanceled,
+                };
+
+                // Undo our decrement above, and we should be guaranteed that the
+                // previous value is positive because we're not going to sleep
+                let prev = self.bump(1);
+                assert!(prev == DISCONNECTED || prev >= 0);
+                ret
+            }
+        }
+    }
+
     // Removes a previous thread from being blocked in this port
     pub fn abort_selection(&self, was_upgrade: bool) -> Result<bool, Receiver<T>> {
         // If we're aborting selection after upgrading from a oneshot, then
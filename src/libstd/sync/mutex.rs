@@ -1,5 +1,6 @@
 use crate::cell::UnsafeCell;
 use crate::fmt;
+use crate::marker::PhantomData;
 use crate::mem;
 use crate::ops::{Deref, DerefMut};
 use crate::ptr;
@@ -154,6 +155,35 @@ impl<T: ?Sized> !Send for MutexGuard<'_, T> { }
 #[stable(feature = "mutexguard", since = "1.19.0")]
 unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> { }
 
+/// An RAII mutex guard returned by [`MutexGuard::map`], which can point to a
+/// subfield of the protected data. When this structure is dropped (falls out
+/// of scope), the lock will be unlocked.
+///
+/// The main difference between `MappedMutexGuard` and [`MutexGuard`] is that
+/// the former cannot be used with a [`Condvar`], since that could introduce
+/// soundness issues if the locked object is modified by another thread while
+/// the mutex is unlocked.
+///
+/// [`MutexGuard::map`]: struct.MutexGuard.html#method.map
+/// [`MutexGuard`]: struct.MutexGuard.html
+/// [`Condvar`]: struct.Condvar.html
+#[must_use = "if unused the Mutex will immediately unlock"]
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+pub struct MappedMutexGuard<'a, T: ?Sized + 'a> {
+    // The data pointer is only valid while the lock is held, and the guard
+    // can't outlive the mutex, which the lifetime in `_variance` enforces.
+    data: *mut T,
+    inner: &'a sys::Mutex,
+    poison_flag: &'a poison::Flag,
+    poison: poison::Guard,
+    _variance: PhantomData<&'a mut T>,
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized> !Send for MappedMutexGuard<'_, T> { }
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+unsafe impl<T: ?Sized + Sync> Sync for MappedMutexGuard<'_, T> { }
+
 impl<T> Mutex<T> {
     /// Creates a new mutex in an unlocked state ready for use.
     ///
@@ -422,6 +452,102 @@ impl<'mutex, T: ?Sized> MutexGuard<'mutex, T> {
             }
         })
     }
+
+    /// Makes a [`MappedMutexGuard`] for a component of the borrowed data,
+    /// e.g. an enum variant.
+    ///
+    /// The `Mutex` is already locked, so this cannot fail.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `MutexGuard::map(...)`. A method would interfere with methods of the
+    /// same name on the contents of the `MutexGuard` used through `Deref`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mapped_lock_guards)]
+    /// use std::sync::{Mutex, MutexGuard};
+    ///
+    /// let mutex = Mutex::new((0, String::from("hello")));
+    /// {
+    ///     let mut name = MutexGuard::map(mutex.lock().unwrap(), |pair| &mut pair.1);
+    ///     name.push_str(", world");
+    /// }
+    /// assert_eq!(mutex.lock().unwrap().1, "hello, world");
+    /// ```
+    ///
+    /// [`MappedMutexGuard`]: struct.MappedMutexGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn map<U: ?Sized, F>(orig: Self, f: F) -> MappedMutexGuard<'mutex, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        // If `f` panics, `orig` is dropped as usual, which poisons the mutex
+        // and unlocks it.
+        let data = f(unsafe { &mut *orig.__lock.data.get() }) as *mut U;
+        let lock = orig.__lock;
+        let poison = unsafe { ptr::read(&orig.__poison) };
+        mem::forget(orig);
+        MappedMutexGuard {
+            data,
+            inner: &lock.inner,
+            poison_flag: &lock.poison,
+            poison,
+            _variance: PhantomData,
+        }
+    }
+
+    /// Makes a [`MappedMutexGuard`] for a component of the borrowed data. The
+    /// original guard is returned as an `Err(...)` if the closure returns
+    /// `None`.
+    ///
+    /// The `Mutex` is already locked, so this cannot fail.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `MutexGuard::try_map(...)`. A method would interfere with methods of
+    /// the same name on the contents of the `MutexGuard` used through `Deref`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mapped_lock_guards)]
+    /// use std::sync::{Mutex, MutexGuard};
+    ///
+    /// let mutex = Mutex::new(vec![1, 2, 3]);
+    /// let guard = mutex.lock().unwrap();
+    /// let guard = match MutexGuard::try_map(guard, |v| v.get_mut(5)) {
+    ///     Ok(_) => unreachable!(),
+    ///     Err(guard) => guard,
+    /// };
+    /// let mut first = MutexGuard::try_map(guard, |v| v.first_mut()).unwrap();
+    /// *first = 10;
+    /// drop(first);
+    /// assert_eq!(*mutex.lock().unwrap(), [10, 2, 3]);
+    /// ```
+    ///
+    /// [`MappedMutexGuard`]: struct.MappedMutexGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn try_map<U: ?Sized, F>(orig: Self, f: F) -> Result<MappedMutexGuard<'mutex, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        match f(unsafe { &mut *orig.__lock.data.get() }) {
+            Some(data) => {
+                let data = data as *mut U;
+                let lock = orig.__lock;
+                let poison = unsafe { ptr::read(&orig.__poison) };
+                mem::forget(orig);
+                Ok(MappedMutexGuard {
+                    data,
+                    inner: &lock.inner,
+                    poison_flag: &lock.poison,
+                    poison,
+                    _variance: PhantomData,
+                })
+            }
+            None => Err(orig),
+        }
+    }
 }
 
 #[stable(feature = "rust1", since = "1.0.0")]
@@ -465,6 +591,100 @@ impl<T: ?Sized + fmt::Display> fmt::Display for MutexGuard<'_, T> {
     }
 }
 
+impl<'a, T: ?Sized> MappedMutexGuard<'a, T> {
+    /// Makes a [`MappedMutexGuard`] for a component of the borrowed data,
+    /// e.g. an enum variant.
+    ///
+    /// The `Mutex` is already locked, so this cannot fail.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `MappedMutexGuard::map(...)`. A method would interfere with methods of
+    /// the same name on the contents of the guard used through `Deref`.
+    ///
+    /// [`MappedMutexGuard`]: struct.MappedMutexGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn map<U: ?Sized, F>(orig: Self, f: F) -> MappedMutexGuard<'a, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let data = f(unsafe { &mut *orig.data }) as *mut U;
+        let (inner, poison_flag) = (orig.inner, orig.poison_flag);
+        let poison = unsafe { ptr::read(&orig.poison) };
+        mem::forget(orig);
+        MappedMutexGuard { data, inner, poison_flag, poison, _variance: PhantomData }
+    }
+
+    /// Makes a [`MappedMutexGuard`] for a component of the borrowed data. The
+    /// original guard is returned as an `Err(...)` if the closure returns
+    /// `None`.
+    ///
+    /// The `Mutex` is already locked, so this cannot fail.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `MappedMutexGuard::try_map(...)`. A method would interfere with
+    /// methods of the same name on the contents of the guard used through
+    /// `Deref`.
+    ///
+    /// [`MappedMutexGuard`]: struct.MappedMutexGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn try_map<U: ?Sized, F>(orig: Self, f: F) -> Result<MappedMutexGuard<'a, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        match f(unsafe { &mut *orig.data }) {
+            Some(data) => {
+                let data = data as *mut U;
+                let (inner, poison_flag) = (orig.inner, orig.poison_flag);
+                let poison = unsafe { ptr::read(&orig.poison) };
+                mem::forget(orig);
+                Ok(MappedMutexGuard { data, inner, poison_flag, poison, _variance: PhantomData })
+            }
+            None => Err(orig),
+        }
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized> Deref for MappedMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.data }
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized> DerefMut for MappedMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.data }
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized> Drop for MappedMutexGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            self.poison_flag.done(&self.poison);
+            self.inner.raw_unlock();
+        }
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MappedMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized + fmt::Display> fmt::Display for MappedMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
 pub fn guard_lock<'a, T: ?Sized>(guard: &MutexGuard<'a, T>) -> &'a sys::Mutex {
     &guard.__lock.inner
 }
@@ -476,7 +696,7 @@ pub fn guard_poison<'a, T: ?Sized>(guard: &MutexGuard<'a, T>) -> &'a poison::Fla
 #[cfg(all(test, not(target_os = "emscripten")))]
 mod tests {
     use crate::sync::mpsc::channel;
-    use crate::sync::{Arc, Mutex, Condvar};
+    use crate::sync::{Arc, Mutex, MutexGuard, MappedMutexGuard, Condvar};
     use crate::sync::atomic::{AtomicUsize, Ordering};
     use crate::thread;
 
@@ -703,4 +923,38 @@ mod tests {
         let comp: &[i32] = &[4, 2, 5];
         assert_eq!(&*mutex.lock().unwrap(), comp);
     }
+
+    #[test]
+    fn test_mutex_guard_map() {
+        let mutex = Mutex::new((1, NonCopy(2)));
+        {
+            let guard = MutexGuard::map(mutex.lock().unwrap(), |pair| &mut pair.1);
+            let mut guard = MappedMutexGuard::map(guard, |nc| &mut nc.0);
+            *guard = 10;
+            assert!(mutex.try_lock().is_err());
+        }
+        assert_eq!(mutex.lock().unwrap().1, NonCopy(10));
+    }
+
+    #[test]
+    fn test_mutex_guard_try_map() {
+        let mutex = Mutex::new(vec![1, 2, 3]);
+        let guard = mutex.lock().unwrap();
+        let guard = MutexGuard::try_map(guard, |v| v.get_mut(3)).unwrap_err();
+        let mut last = MutexGuard::try_map(guard, |v| v.last_mut()).unwrap();
+        *last = 4;
+        drop(last);
+        assert_eq!(*mutex.lock().unwrap(), [1, 2, 4]);
+    }
+
+    #[test]
+    fn test_mapped_mutex_guard_poison() {
+        let arc = Arc::new(Mutex::new((1, 2)));
+        let arc2 = arc.clone();
+        let _ = thread::spawn(move || {
+            let _guard = MutexGuard::map(arc2.lock().unwrap(), |pair| &mut pair.0);
+            panic!("test panic in inner thread to poison mutex");
+        }).join();
+        assert!(arc.is_poisoned());
+    }
 }
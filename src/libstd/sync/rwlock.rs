@@ -1,8 +1,11 @@
 use crate::cell::UnsafeCell;
 use crate::fmt;
+use crate::marker::PhantomData;
 use crate::mem;
 use crate::ops::{Deref, DerefMut};
 use crate::ptr;
+use crate::sync::atomic::{AtomicPtr, Ordering};
+use crate::sys_common::mutex as sys_mutex;
 use crate::sys_common::poison::{self, LockResult, TryLockError, TryLockResult};
 use crate::sys_common::rwlock as sys;
 
@@ -66,6 +69,12 @@ use crate::sys_common::rwlock as sys;
 #[stable(feature = "rust1", since = "1.0.0")]
 pub struct RwLock<T: ?Sized> {
     inner: Box<sys::RWLock>,
+    // Held by the upgradable reader, if any. It is only created by the first
+    // upgradable read, so that locks which don't use those are unaffected.
+    // Writers which find it held once they have acquired `inner` release
+    // `inner` again, so no writer can get in between an upgradable reader
+    // releasing its read access and acquiring write access.
+    upgradable: AtomicPtr<sys_mutex::Mutex>,
     poison: poison::Flag,
     data: UnsafeCell<T>,
 }
@@ -118,6 +127,80 @@ impl<T: ?Sized> !Send for RwLockWriteGuard<'_, T> {}
 #[stable(feature = "rwlock_guard_sync", since = "1.23.0")]
 unsafe impl<T: ?Sized + Sync> Sync for RwLockWriteGuard<'_, T> {}
 
+/// RAII structure used to release the upgradable read access of a lock when
+/// dropped.
+///
+/// An upgradable read access is a shared read access which excludes writers
+/// and other upgradable readers, but not plain readers. It can be atomically
+/// [upgraded] to an exclusive write access.
+///
+/// This structure is created by the [`upgradable_read`] and
+/// [`try_upgradable_read`] methods on [`RwLock`].
+///
+/// [upgraded]: struct.RwLockUpgradableReadGuard.html#method.upgrade
+/// [`upgradable_read`]: struct.RwLock.html#method.upgradable_read
+/// [`try_upgradable_read`]: struct.RwLock.html#method.try_upgradable_read
+/// [`RwLock`]: struct.RwLock.html
+#[must_use = "if unused the RwLock will immediately unlock"]
+#[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+pub struct RwLockUpgradableReadGuard<'a, T: ?Sized + 'a> {
+    __lock: &'a RwLock<T>,
+}
+
+#[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+impl<T: ?Sized> !Send for RwLockUpgradableReadGuard<'_, T> {}
+
+#[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+unsafe impl<T: ?Sized + Sync> Sync for RwLockUpgradableReadGuard<'_, T> {}
+
+/// RAII structure used to release the shared read access of a lock when
+/// dropped, which can point to a subfield of the protected data.
+///
+/// This structure is created by the [`map`] and [`try_map`] methods on
+/// [`RwLockReadGuard`].
+///
+/// [`map`]: struct.RwLockReadGuard.html#method.map
+/// [`try_map`]: struct.RwLockReadGuard.html#method.try_map
+/// [`RwLockReadGuard`]: struct.RwLockReadGuard.html
+#[must_use = "if unused the RwLock will immediately unlock"]
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+pub struct MappedRwLockReadGuard<'a, T: ?Sized + 'a> {
+    data: *const T,
+    inner: &'a sys::RWLock,
+    _variance: PhantomData<&'a T>,
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized> !Send for MappedRwLockReadGuard<'_, T> {}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+unsafe impl<T: ?Sized + Sync> Sync for MappedRwLockReadGuard<'_, T> {}
+
+/// RAII structure used to release the exclusive write access of a lock when
+/// dropped, which can point to a subfield of the protected data.
+///
+/// This structure is created by the [`map`] and [`try_map`] methods on
+/// [`RwLockWriteGuard`].
+///
+/// [`map`]: struct.RwLockWriteGuard.html#method.map
+/// [`try_map`]: struct.RwLockWriteGuard.html#method.try_map
+/// [`RwLockWriteGuard`]: struct.RwLockWriteGuard.html
+#[must_use = "if unused the RwLock will immediately unlock"]
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+pub struct MappedRwLockWriteGuard<'a, T: ?Sized + 'a> {
+    data: *mut T,
+    inner: &'a sys::RWLock,
+    poison_flag: &'a poison::Flag,
+    poison: poison::Guard,
+    _variance: PhantomData<&'a mut T>,
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized> !Send for MappedRwLockWriteGuard<'_, T> {}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+unsafe impl<T: ?Sized + Sync> Sync for MappedRwLockWriteGuard<'_, T> {}
+
 impl<T> RwLock<T> {
     /// Creates a new instance of an `RwLock<T>` which is unlocked.
     ///
@@ -132,6 +215,7 @@ impl<T> RwLock<T> {
     pub fn new(t: T) -> RwLock<T> {
         RwLock {
             inner: box sys::RWLock::new(),
+            upgradable: AtomicPtr::new(ptr::null_mut()),
             poison: poison::Flag::new(),
             data: UnsafeCell::new(t),
         }
@@ -264,8 +348,18 @@ impl<T: ?Sized> RwLock<T> {
     #[stable(feature = "rust1", since = "1.0.0")]
     pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, T>> {
         unsafe {
-            self.inner.write();
-            RwLockWriteGuard::new(self)
+            loop {
+                self.inner.write();
+                let upgradable = match self.held_upgradable_mutex() {
+                    Some(upgradable) => upgradable,
+                    None => return RwLockWriteGuard::new(self),
+                };
+                // Let the upgradable reader upgrade or release its access
+                // before trying again.
+                self.inner.write_unlock();
+                upgradable.raw_lock();
+                upgradable.raw_unlock();
+            }
         }
     }
 
@@ -303,14 +397,156 @@ impl<T: ?Sized> RwLock<T> {
     #[stable(feature = "rust1", since = "1.0.0")]
     pub fn try_write(&self) -> TryLockResult<RwLockWriteGuard<'_, T>> {
         unsafe {
-            if self.inner.try_write() {
-                Ok(RwLockWriteGuard::new(self)?)
+            if !self.inner.try_write() {
+                return Err(TryLockError::WouldBlock);
+            }
+            if self.held_upgradable_mutex().is_some() {
+                self.inner.write_unlock();
+                return Err(TryLockError::WouldBlock);
+            }
+            Ok(RwLockWriteGuard::new(self)?)
+        }
+    }
+
+    /// Locks this rwlock with upgradable read access, blocking the current
+    /// thread until it can be acquired.
+    ///
+    /// An upgradable read access is shared with plain readers, but not with
+    /// writers or other upgradable readers. Unlike a read access, it can be
+    /// [upgraded] to a write access without letting any writer modify the
+    /// data in between.
+    ///
+    /// Returns an RAII guard which will release this thread's upgradable
+    /// access once it is dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the RwLock is poisoned. An RwLock
+    /// is poisoned whenever a writer panics while holding an exclusive lock.
+    /// The failure will occur immediately after the lock has been acquired.
+    ///
+    /// # Panics
+    ///
+    /// This function might panic when called if the lock is already held by the current thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(rwlock_upgradable_read)]
+    /// use std::sync::{RwLock, RwLockUpgradableReadGuard};
+    ///
+    /// let lock = RwLock::new(vec![1, 2]);
+    ///
+    /// let n = lock.upgradable_read().unwrap();
+    /// // Plain readers can still access the data.
+    /// assert_eq!(*lock.read().unwrap(), [1, 2]);
+    ///
+    /// if !n.contains(&3) {
+    ///     let mut n = RwLockUpgradableReadGuard::upgrade(n).unwrap();
+    ///     n.push(3);
+    /// }
+    /// assert_eq!(*lock.read().unwrap(), [1, 2, 3]);
+    /// ```
+    ///
+    /// [upgraded]: struct.RwLockUpgradableReadGuard.html#method.upgrade
+    #[inline]
+    #[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+    pub fn upgradable_read(&self) -> LockResult<RwLockUpgradableReadGuard<'_, T>> {
+        unsafe {
+            let upgradable = self.upgradable_mutex().lock();
+            self.inner.read();
+            // Released by the `RwLockUpgradableReadGuard` from now on.
+            mem::forget(upgradable);
+            RwLockUpgradableReadGuard::new(self)
+        }
+    }
+
+    /// Attempts to acquire this rwlock with upgradable read access.
+    ///
+    /// If the access could not be granted at this time, then `Err` is returned.
+    /// Otherwise, an RAII guard is returned which will release the upgradable
+    /// access when it is dropped.
+    ///
+    /// This function does not block.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the RwLock is poisoned. An RwLock
+    /// is poisoned whenever a writer panics while holding an exclusive lock. An
+    /// error will only be returned if the lock would have otherwise been
+    /// acquired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(rwlock_upgradable_read)]
+    /// use std::sync::RwLock;
+    ///
+    /// let lock = RwLock::new(1);
+    ///
+    /// let n = lock.upgradable_read().unwrap();
+    /// assert!(lock.try_upgradable_read().is_err());
+    /// drop(n);
+    /// assert!(lock.try_upgradable_read().is_ok());
+    /// ```
+    #[inline]
+    #[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+    pub fn try_upgradable_read(&self) -> TryLockResult<RwLockUpgradableReadGuard<'_, T>> {
+        unsafe {
+            let upgradable = self.upgradable_mutex();
+            if !upgradable.try_lock() {
+                return Err(TryLockError::WouldBlock);
+            }
+            if self.inner.try_read() {
+                Ok(RwLockUpgradableReadGuard::new(self)?)
             } else {
+                upgradable.raw_unlock();
                 Err(TryLockError::WouldBlock)
             }
         }
     }
 
+    /// Returns the mutex held by the upgradable reader, creating it if this
+    /// is the first upgradable read access.
+    fn upgradable_mutex(&self) -> &sys_mutex::Mutex {
+        let upgradable = self.upgradable.load(Ordering::Acquire);
+        if !upgradable.is_null() {
+            return unsafe { &*upgradable };
+        }
+        unsafe {
+            let mut new = box sys_mutex::Mutex::new();
+            new.init();
+            let new = Box::into_raw(new);
+            let old = self.upgradable.compare_and_swap(ptr::null_mut(), new, Ordering::AcqRel);
+            if old.is_null() {
+                &*new
+            } else {
+                // Another thread created it first.
+                destroy_upgradable_mutex(new);
+                &*old
+            }
+        }
+    }
+
+    /// Called by writers which hold `inner`: returns the mutex held by the
+    /// upgradable reader if there is one, in which case `inner` has to be
+    /// released to let it upgrade its access.
+    unsafe fn held_upgradable_mutex(&self) -> Option<&sys_mutex::Mutex> {
+        let upgradable = self.upgradable.load(Ordering::Acquire).as_ref()?;
+        if upgradable.try_lock() {
+            upgradable.raw_unlock();
+            None
+        } else {
+            Some(upgradable)
+        }
+    }
+
+    /// Releases the mutex held by the upgradable reader, which therefore
+    /// exists.
+    unsafe fn unlock_upgradable_mutex(&self) {
+        (*self.upgradable.load(Ordering::Relaxed)).raw_unlock();
+    }
+
     /// Determines whether the lock is poisoned.
     ///
     /// If another thread is active, the lock can still become poisoned at any
@@ -368,13 +604,15 @@ impl<T: ?Sized> RwLock<T> {
         // but because `RwLock` impl-s `Drop`, we can't move out of it, so
         // we'll have to destructure it manually instead.
         unsafe {
-            // Like `let RwLock { inner, poison, data } = self`.
-            let (inner, poison, data) = {
-                let RwLock { ref inner, ref poison, ref data } = self;
-                (ptr::read(inner), ptr::read(poison), ptr::read(data))
+            // Like `let RwLock { inner, upgradable, poison, data } = self`.
+            let (inner, upgradable, poison, data) = {
+                let RwLock { ref inner, ref upgradable, ref poison, ref data } = self;
+                (ptr::read(inner), ptr::read(upgradable), ptr::read(poison), ptr::read(data))
             };
             mem::forget(self);
-            inner.destroy(); // Keep in sync with the `Drop` impl.
+            // Keep in sync with the `Drop` impl.
+            inner.destroy();
+            destroy_upgradable_mutex(upgradable.into_inner());
             drop(inner);
 
             poison::map_result(poison.borrow(), |_| data.into_inner())
@@ -415,7 +653,19 @@ impl<T: ?Sized> RwLock<T> {
 unsafe impl<#[may_dangle] T: ?Sized> Drop for RwLock<T> {
     fn drop(&mut self) {
         // IMPORTANT: This code needs to be kept in sync with `RwLock::into_inner`.
-        unsafe { self.inner.destroy() }
+        unsafe {
+            self.inner.destroy();
+            destroy_upgradable_mutex(*self.upgradable.get_mut());
+        }
+    }
+}
+
+/// Destroys and frees the mutex of the upgradable readers of a lock, if it
+/// was created.
+unsafe fn destroy_upgradable_mutex(upgradable: *mut sys_mutex::Mutex) {
+    if !upgradable.is_null() {
+        let upgradable = Box::from_raw(upgradable);
+        upgradable.destroy();
     }
 }
 
@@ -469,6 +719,79 @@ impl<'rwlock, T: ?Sized> RwLockReadGuard<'rwlock, T> {
             }
         })
     }
+
+    /// Makes a [`MappedRwLockReadGuard`] for a component of the borrowed
+    /// data, e.g. an enum variant.
+    ///
+    /// The `RwLock` is already locked for reading, so this cannot fail.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RwLockReadGuard::map(...)`. A method would interfere with methods of
+    /// the same name on the contents of the `RwLockReadGuard` used through
+    /// `Deref`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mapped_lock_guards)]
+    /// use std::sync::{RwLock, RwLockReadGuard};
+    ///
+    /// let lock = RwLock::new((0, String::from("hello")));
+    /// let name = RwLockReadGuard::map(lock.read().unwrap(), |pair| &pair.1);
+    /// assert_eq!(*name, "hello");
+    /// ```
+    ///
+    /// [`MappedRwLockReadGuard`]: struct.MappedRwLockReadGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn map<U: ?Sized, F>(orig: Self, f: F) -> MappedRwLockReadGuard<'rwlock, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        let data = f(unsafe { &*orig.__lock.data.get() }) as *const U;
+        let inner = &*orig.__lock.inner;
+        mem::forget(orig);
+        MappedRwLockReadGuard { data, inner, _variance: PhantomData }
+    }
+
+    /// Makes a [`MappedRwLockReadGuard`] for a component of the borrowed
+    /// data. The original guard is returned as an `Err(...)` if the closure
+    /// returns `None`.
+    ///
+    /// The `RwLock` is already locked for reading, so this cannot fail.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RwLockReadGuard::try_map(...)`. A method would interfere with methods
+    /// of the same name on the contents of the `RwLockReadGuard` used through
+    /// `Deref`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mapped_lock_guards)]
+    /// use std::sync::{RwLock, RwLockReadGuard};
+    ///
+    /// let lock = RwLock::new(vec![1, 2, 3]);
+    /// let first = RwLockReadGuard::try_map(lock.read().unwrap(), |v| v.first()).unwrap();
+    /// assert_eq!(*first, 1);
+    /// ```
+    ///
+    /// [`MappedRwLockReadGuard`]: struct.MappedRwLockReadGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn try_map<U: ?Sized, F>(orig: Self, f: F)
+                                 -> Result<MappedRwLockReadGuard<'rwlock, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        match f(unsafe { &*orig.__lock.data.get() }) {
+            Some(data) => {
+                let data = data as *const U;
+                let inner = &*orig.__lock.inner;
+                mem::forget(orig);
+                Ok(MappedRwLockReadGuard { data, inner, _variance: PhantomData })
+            }
+            None => Err(orig),
+        }
+    }
 }
 
 impl<'rwlock, T: ?Sized> RwLockWriteGuard<'rwlock, T> {
@@ -481,6 +804,279 @@ impl<'rwlock, T: ?Sized> RwLockWriteGuard<'rwlock, T> {
             }
         })
     }
+
+    /// Makes a [`MappedRwLockWriteGuard`] for a component of the borrowed
+    /// data, e.g. an enum variant.
+    ///
+    /// The `RwLock` is already locked for writing, so this cannot fail.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RwLockWriteGuard::map(...)`. A method would interfere with methods of
+    /// the same name on the contents of the `RwLockWriteGuard` used through
+    /// `Deref`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mapped_lock_guards)]
+    /// use std::sync::{RwLock, RwLockWriteGuard};
+    ///
+    /// let lock = RwLock::new((0, String::from("hello")));
+    /// {
+    ///     let mut name = RwLockWriteGuard::map(lock.write().unwrap(), |pair| &mut pair.1);
+    ///     name.push_str(", world");
+    /// }
+    /// assert_eq!(lock.read().unwrap().1, "hello, world");
+    /// ```
+    ///
+    /// [`MappedRwLockWriteGuard`]: struct.MappedRwLockWriteGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn map<U: ?Sized, F>(orig: Self, f: F) -> MappedRwLockWriteGuard<'rwlock, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        // If `f` panics, `orig` is dropped as usual, which poisons the lock
+        // and unlocks it.
+        let data = f(unsafe { &mut *orig.__lock.data.get() }) as *mut U;
+        let lock = orig.__lock;
+        let poison = unsafe { ptr::read(&orig.__poison) };
+        mem::forget(orig);
+        MappedRwLockWriteGuard {
+            data,
+            inner: &lock.inner,
+            poison_flag: &lock.poison,
+            poison,
+            _variance: PhantomData,
+        }
+    }
+
+    /// Makes a [`MappedRwLockWriteGuard`] for a component of the borrowed
+    /// data. The original guard is returned as an `Err(...)` if the closure
+    /// returns `None`.
+    ///
+    /// The `RwLock` is already locked for writing, so this cannot fail.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RwLockWriteGuard::try_map(...)`. A method would interfere with
+    /// methods of the same name on the contents of the `RwLockWriteGuard`
+    /// used through `Deref`.
+    ///
+    /// [`MappedRwLockWriteGuard`]: struct.MappedRwLockWriteGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn try_map<U: ?Sized, F>(orig: Self, f: F)
+                                 -> Result<MappedRwLockWriteGuard<'rwlock, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        match f(unsafe { &mut *orig.__lock.data.get() }) {
+            Some(data) => {
+                let data = data as *mut U;
+                let lock = orig.__lock;
+                let poison = unsafe { ptr::read(&orig.__poison) };
+                mem::forget(orig);
+                Ok(MappedRwLockWriteGuard {
+                    data,
+                    inner: &lock.inner,
+                    poison_flag: &lock.poison,
+                    poison,
+                    _variance: PhantomData,
+                })
+            }
+            None => Err(orig),
+        }
+    }
+}
+
+impl<'rwlock, T: ?Sized> RwLockUpgradableReadGuard<'rwlock, T> {
+    unsafe fn new(lock: &'rwlock RwLock<T>)
+                  -> LockResult<RwLockUpgradableReadGuard<'rwlock, T>> {
+        poison::map_result(lock.poison.borrow(), |_| {
+            RwLockUpgradableReadGuard {
+                __lock: lock,
+            }
+        })
+    }
+
+    /// Atomically upgrades an upgradable read access into an exclusive write
+    /// access, blocking the current thread until it can be acquired.
+    ///
+    /// No writer can modify the data between the upgradable read access and
+    /// the write access, so anything read before the upgrade still holds.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RwLockUpgradableReadGuard::upgrade(...)`. A method would interfere
+    /// with methods of the same name on the contents of the guard used
+    /// through `Deref`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the RwLock is poisoned. As no
+    /// writer can run while the upgradable access is held, this only happens
+    /// if the lock was already poisoned when that access was acquired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(rwlock_upgradable_read)]
+    /// use std::sync::{RwLock, RwLockUpgradableReadGuard};
+    ///
+    /// let lock = RwLock::new(1);
+    ///
+    /// let n = lock.upgradable_read().unwrap();
+    /// assert_eq!(*n, 1);
+    /// let mut n = RwLockUpgradableReadGuard::upgrade(n).unwrap();
+    /// *n += 1;
+    /// drop(n);
+    ///
+    /// assert_eq!(*lock.read().unwrap(), 2);
+    /// ```
+    #[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+    pub fn upgrade(orig: Self) -> LockResult<RwLockWriteGuard<'rwlock, T>> {
+        let lock = orig.__lock;
+        mem::forget(orig);
+        unsafe {
+            // Writers release `inner` while `upgradable` is held, so only
+            // readers can keep the lock in between.
+            lock.inner.read_unlock();
+            lock.inner.write();
+            lock.unlock_upgradable_mutex();
+            RwLockWriteGuard::new(lock)
+        }
+    }
+
+    /// Downgrades an upgradable read access into a plain read access,
+    /// allowing another thread to take the upgradable read access.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RwLockUpgradableReadGuard::downgrade(...)`. A method would interfere
+    /// with methods of the same name on the contents of the guard used
+    /// through `Deref`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(rwlock_upgradable_read)]
+    /// use std::sync::{RwLock, RwLockUpgradableReadGuard};
+    ///
+    /// let lock = RwLock::new(1);
+    ///
+    /// let n = RwLockUpgradableReadGuard::downgrade(lock.upgradable_read().unwrap());
+    /// assert_eq!(*n, 1);
+    /// assert!(lock.try_upgradable_read().is_ok());
+    /// ```
+    #[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+    pub fn downgrade(orig: Self) -> RwLockReadGuard<'rwlock, T> {
+        let lock = orig.__lock;
+        mem::forget(orig);
+        unsafe {
+            lock.unlock_upgradable_mutex();
+        }
+        RwLockReadGuard { __lock: lock }
+    }
+}
+
+impl<'a, T: ?Sized> MappedRwLockReadGuard<'a, T> {
+    /// Makes a [`MappedRwLockReadGuard`] for a component of the borrowed
+    /// data, e.g. an enum variant.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `MappedRwLockReadGuard::map(...)`. A method would interfere with
+    /// methods of the same name on the contents of the guard used through
+    /// `Deref`.
+    ///
+    /// [`MappedRwLockReadGuard`]: struct.MappedRwLockReadGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn map<U: ?Sized, F>(orig: Self, f: F) -> MappedRwLockReadGuard<'a, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        let data = f(unsafe { &*orig.data }) as *const U;
+        let inner = orig.inner;
+        mem::forget(orig);
+        MappedRwLockReadGuard { data, inner, _variance: PhantomData }
+    }
+
+    /// Makes a [`MappedRwLockReadGuard`] for a component of the borrowed
+    /// data. The original guard is returned as an `Err(...)` if the closure
+    /// returns `None`.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `MappedRwLockReadGuard::try_map(...)`. A method would interfere with
+    /// methods of the same name on the contents of the guard used through
+    /// `Deref`.
+    ///
+    /// [`MappedRwLockReadGuard`]: struct.MappedRwLockReadGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn try_map<U: ?Sized, F>(orig: Self, f: F) -> Result<MappedRwLockReadGuard<'a, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        match f(unsafe { &*orig.data }) {
+            Some(data) => {
+                let data = data as *const U;
+                let inner = orig.inner;
+                mem::forget(orig);
+                Ok(MappedRwLockReadGuard { data, inner, _variance: PhantomData })
+            }
+            None => Err(orig),
+        }
+    }
+}
+
+impl<'a, T: ?Sized> MappedRwLockWriteGuard<'a, T> {
+    /// Makes a [`MappedRwLockWriteGuard`] for a component of the borrowed
+    /// data, e.g. an enum variant.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `MappedRwLockWriteGuard::map(...)`. A method would interfere with
+    /// methods of the same name on the contents of the guard used through
+    /// `Deref`.
+    ///
+    /// [`MappedRwLockWriteGuard`]: struct.MappedRwLockWriteGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn map<U: ?Sized, F>(orig: Self, f: F) -> MappedRwLockWriteGuard<'a, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let data = f(unsafe { &mut *orig.data }) as *mut U;
+        let (inner, poison_flag) = (orig.inner, orig.poison_flag);
+        let poison = unsafe { ptr::read(&orig.poison) };
+        mem::forget(orig);
+        MappedRwLockWriteGuard { data, inner, poison_flag, poison, _variance: PhantomData }
+    }
+
+    /// Makes a [`MappedRwLockWriteGuard`] for a component of the borrowed
+    /// data. The original guard is returned as an `Err(...)` if the closure
+    /// returns `None`.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `MappedRwLockWriteGuard::try_map(...)`. A method would interfere with
+    /// methods of the same name on the contents of the guard used through
+    /// `Deref`.
+    ///
+    /// [`MappedRwLockWriteGuard`]: struct.MappedRwLockWriteGuard.html
+    #[unstable(feature = "mapped_lock_guards", issue = "0")]
+    pub fn try_map<U: ?Sized, F>(orig: Self, f: F) -> Result<MappedRwLockWriteGuard<'a, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        match f(unsafe { &mut *orig.data }) {
+            Some(data) => {
+                let data = data as *mut U;
+                let (inner, poison_flag) = (orig.inner, orig.poison_flag);
+                let poison = unsafe { ptr::read(&orig.poison) };
+                mem::forget(orig);
+                Ok(MappedRwLockWriteGuard {
+                    data,
+                    inner,
+                    poison_flag,
+                    poison,
+                    _variance: PhantomData,
+                })
+            }
+            None => Err(orig),
+        }
+    }
 }
 
 #[stable(feature = "std_debug", since = "1.16.0")]
@@ -555,12 +1151,117 @@ impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
     }
 }
 
+#[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockUpgradableReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RwLockUpgradableReadGuard")
+            .field("lock", &self.__lock)
+            .finish()
+    }
+}
+
+#[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+impl<T: ?Sized + fmt::Display> fmt::Display for RwLockUpgradableReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+impl<T: ?Sized> Deref for RwLockUpgradableReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.__lock.data.get() }
+    }
+}
+
+#[unstable(feature = "rwlock_upgradable_read", issue = "0")]
+impl<T: ?Sized> Drop for RwLockUpgradableReadGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.__lock.inner.read_unlock();
+            self.__lock.unlock_upgradable_mutex();
+        }
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MappedRwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized + fmt::Display> fmt::Display for MappedRwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MappedRwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized + fmt::Display> fmt::Display for MappedRwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized> Deref for MappedRwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.data }
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized> Deref for MappedRwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.data }
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized> DerefMut for MappedRwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.data }
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized> Drop for MappedRwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe { self.inner.read_unlock(); }
+    }
+}
+
+#[unstable(feature = "mapped_lock_guards", issue = "0")]
+impl<T: ?Sized> Drop for MappedRwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.poison_flag.done(&self.poison);
+        unsafe { self.inner.write_unlock(); }
+    }
+}
+
 #[cfg(all(test, not(target_os = "emscripten")))]
 mod tests {
     use rand::{self, Rng};
     use crate::sync::mpsc::channel;
     use crate::thread;
     use crate::sync::{Arc, RwLock, TryLockError};
+    use crate::sync::{MappedRwLockWriteGuard, RwLockReadGuard, RwLockWriteGuard};
+    use crate::sync::RwLockUpgradableReadGuard;
     use crate::sync::atomic::{AtomicUsize, Ordering};
 
     #[derive(Eq, PartialEq, Debug)]
@@ -797,4 +1498,109 @@ mod tests {
             Ok(x) => panic!("get_mut of poisoned RwLock is Ok: {:?}", x),
         }
     }
+
+    #[test]
+    fn test_rwlock_guard_map() {
+        let lock = RwLock::new((1, NonCopy(2)));
+        {
+            let guard = RwLockWriteGuard::map(lock.write().unwrap(), |pair| &mut pair.1);
+            let mut guard = MappedRwLockWriteGuard::map(guard, |nc| &mut nc.0);
+            *guard = 10;
+            assert!(lock.try_read().is_err());
+        }
+        let guard = RwLockReadGuard::map(lock.read().unwrap(), |pair| &pair.1);
+        assert_eq!(*guard, NonCopy(10));
+        assert!(lock.try_read().is_ok());
+        assert!(lock.try_write().is_err());
+        drop(guard);
+        assert!(lock.try_write().is_ok());
+    }
+
+    #[test]
+    fn test_rwlock_guard_try_map() {
+        let lock = RwLock::new(vec![1, 2, 3]);
+        let guard = RwLockReadGuard::try_map(lock.read().unwrap(), |v| v.get(3)).unwrap_err();
+        drop(guard);
+        let guard = lock.write().unwrap();
+        let mut last = RwLockWriteGuard::try_map(guard, |v| v.last_mut()).unwrap();
+        *last = 4;
+        drop(last);
+        assert_eq!(*lock.read().unwrap(), [1, 2, 4]);
+    }
+
+    #[test]
+    fn test_mapped_write_guard_poison() {
+        let lock = Arc::new(RwLock::new((1, 2)));
+        let lock2 = lock.clone();
+        let _ = thread::spawn(move || {
+            let _guard = RwLockWriteGuard::map(lock2.write().unwrap(), |pair| &mut pair.0);
+            panic!("test panic in inner thread to poison RwLock");
+        }).join();
+        assert!(lock.is_poisoned());
+    }
+
+    #[test]
+    fn test_upgradable_read() {
+        let lock = RwLock::new(1);
+        let guard = lock.upgradable_read().unwrap();
+        assert!(lock.try_read().is_ok());
+        assert!(lock.try_write().is_err());
+        assert!(lock.try_upgradable_read().is_err());
+
+        let mut guard = RwLockUpgradableReadGuard::upgrade(guard).unwrap();
+        *guard += 1;
+        assert!(lock.try_read().is_err());
+        drop(guard);
+
+        let guard = RwLockUpgradableReadGuard::downgrade(lock.upgradable_read().unwrap());
+        assert_eq!(*guard, 2);
+        assert!(lock.try_upgradable_read().is_ok());
+        assert!(lock.try_write().is_err());
+        drop(guard);
+        assert!(lock.try_write().is_ok());
+    }
+
+    #[test]
+    fn test_write_leaves_upgradable_mutex_uncreated() {
+        let lock = RwLock::new(0);
+        *lock.write().unwrap() += 1;
+        *lock.try_write().unwrap() += 1;
+        assert!(lock.upgradable.load(Ordering::Relaxed).is_null());
+
+        drop(lock.upgradable_read().unwrap());
+        assert!(!lock.upgradable.load(Ordering::Relaxed).is_null());
+        *lock.write().unwrap() += 1;
+        *lock.try_write().unwrap() += 1;
+        assert_eq!(*lock.read().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_upgradable_read_excludes_writers() {
+        const N: usize = 10;
+
+        let lock = Arc::new(RwLock::new(0));
+        let (tx, rx) = channel();
+        for _ in 0..N {
+            let lock = lock.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                // Every upgrade must see the value left by the previous one.
+                let guard = lock.upgradable_read().unwrap();
+                let seen = *guard;
+                thread::yield_now();
+                let mut guard = RwLockUpgradableReadGuard::upgrade(guard).unwrap();
+                assert_eq!(*guard, seen);
+                *guard += 1;
+                drop(guard);
+
+                *lock.write().unwrap() += 1;
+                tx.send(()).unwrap();
+            });
+        }
+        drop(tx);
+        for _ in 0..N {
+            rx.recv().unwrap();
+        }
+        assert_eq!(*lock.read().unwrap(), 2 * N);
+    }
 }
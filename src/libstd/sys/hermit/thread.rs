@@ -4,7 +4,9 @@ use crate::ffi::CStr;
 use crate::fmt;
 use crate::io;
 use crate::mem;
+use crate::num::NonZeroUsize;
 use crate::sys::hermit::abi;
+use crate::sys::unsupported;
 use crate::time::Duration;
 use core::u32;
 
@@ -115,6 +117,10 @@ impl Thread {
     }
 }
 
+pub fn available_concurrency() -> io::Result<NonZeroUsize> {
+    unsupported()
+}
+
 pub mod guard {
     pub type Guard = !;
     pub unsafe fn current() -> Option<Guard> {
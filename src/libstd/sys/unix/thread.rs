@@ -2,6 +2,7 @@ use crate::cmp;
 use crate::ffi::CStr;
 use crate::io;
 use crate::mem;
+use crate::num::NonZeroUsize;
 use crate::ptr;
 use crate::sys::os;
 use crate::time::Duration;
@@ -205,6 +206,96 @@ impl Drop for Thread {
     }
 }
 
+#[cfg(any(
+    target_os = "android",
+    target_os = "emscripten",
+    target_os = "fuchsia",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "solaris",
+))]
+pub fn available_concurrency() -> io::Result<NonZeroUsize> {
+    // The affinity mask of the thread is narrower than the online CPUs when
+    // the process is restricted to a cpuset, by a container or `taskset`.
+    #[cfg(target_os = "linux")]
+    unsafe {
+        let mut set: libc::cpu_set_t = mem::zeroed();
+        if libc::sched_getaffinity(0, mem::size_of::<libc::cpu_set_t>(), &mut set) == 0 {
+            let count = (0..libc::CPU_SETSIZE as usize)
+                .filter(|&cpu| libc::CPU_ISSET(cpu, &set))
+                .count();
+            if let Some(count) = NonZeroUsize::new(count) {
+                return Ok(count);
+            }
+        }
+    }
+
+    match unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) } {
+        -1 => Err(io::Error::last_os_error()),
+        cpus => NonZeroUsize::new(cpus as usize).ok_or_else(unknown_concurrency),
+    }
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd"))]
+pub fn available_concurrency() -> io::Result<NonZeroUsize> {
+    let cpus = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    if cpus > 0 {
+        return Ok(unsafe { NonZeroUsize::new_unchecked(cpus as usize) });
+    }
+    sysctl_ncpu()
+}
+
+#[cfg(target_os = "openbsd")]
+pub fn available_concurrency() -> io::Result<NonZeroUsize> {
+    sysctl_ncpu()
+}
+
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+fn sysctl_ncpu() -> io::Result<NonZeroUsize> {
+    let mut cpus: libc::c_uint = 0;
+    let mut cpus_size = mem::size_of_val(&cpus);
+    let mut mib = [libc::CTL_HW, libc::HW_NCPU, 0, 0];
+
+    let ret = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            2,
+            &mut cpus as *mut _ as *mut _,
+            &mut cpus_size as *mut _ as *mut _,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if ret == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    NonZeroUsize::new(cpus as usize).ok_or_else(unknown_concurrency)
+}
+
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "emscripten",
+    target_os = "fuchsia",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "solaris",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+)))]
+pub fn available_concurrency() -> io::Result<NonZeroUsize> {
+    // FIXME: implement on Haiku, L4Re and Redox
+    Err(unknown_concurrency())
+}
+
 #[cfg(all(
     not(all(target_os = "linux", not(target_env = "musl"))),
     not(target_os = "freebsd"),
@@ -2,6 +2,7 @@ use crate::cmp;
 use crate::ffi::CStr;
 use crate::io;
 use crate::mem;
+use crate::num::NonZeroUsize;
 use crate::ptr;
 use crate::sys::os;
 use crate::time::Duration;
@@ -130,6 +131,11 @@ impl Drop for Thread {
     }
 }
 
+pub fn available_concurrency() -> io::Result<NonZeroUsize> {
+    // FIXME: implement on vxWorks
+    Err(unknown_concurrency())
+}
+
 #[cfg_attr(test, allow(dead_code))]
 pub mod guard {
     use crate::ops::Range;
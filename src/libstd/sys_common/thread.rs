@@ -1,4 +1,5 @@
 use crate::env;
+use crate::io;
 use crate::sync::atomic::{self, Ordering};
 use crate::sys::stack_overflow;
 use crate::sys::thread as imp;
@@ -13,6 +14,14 @@ pub unsafe fn start_thread(main: *mut u8) {
     Box::from_raw(main as *mut Box<dyn FnOnce()>)()
 }
 
+/// The error of `available_concurrency` when the number of hardware threads
+/// can't be queried.
+#[allow(dead_code)] // Not used on all platforms.
+pub fn unknown_concurrency() -> io::Error {
+    io::Error::new(io::ErrorKind::Other,
+                   "the number of hardware threads is not known for the target platform")
+}
+
 pub fn min_stack() -> usize {
     static MIN: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
     match MIN.load(Ordering::SeqCst) {
@@ -161,8 +161,9 @@ use crate::cell::UnsafeCell;
 use crate::ffi::{CStr, CString};
 use crate::fmt;
 use crate::io;
+use crate::marker::PhantomData;
 use crate::mem;
-use crate::num::NonZeroU64;
+use crate::num::{NonZeroU64, NonZeroUsize};
 use crate::panic;
 use crate::panicking;
 use crate::str;
@@ -185,6 +186,11 @@ use crate::time::Duration;
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use self::local::{LocalKey, AccessError};
 
+mod scoped;
+
+#[unstable(feature = "scoped_threads", issue = "0")]
+pub use self::scoped::{scope, Scope, ScopedJoinHandle};
+
 // The types used by the thread_local! macro to access TLS keys. Note that there
 // are two types, the "OS" type and the "fast" type. The OS thread local key
 // type is accessed via platform-specific API calls and is slow, while the fast
@@ -447,6 +453,20 @@ impl Builder {
     #[unstable(feature = "thread_spawn_unchecked", issue = "55132")]
     pub unsafe fn spawn_unchecked<'a, F, T>(self, f: F) -> io::Result<JoinHandle<T>> where
         F: FnOnce() -> T, F: Send + 'a, T: Send + 'a
+    {
+        Ok(JoinHandle(self.spawn_unchecked_(f, None)?))
+    }
+
+    // Spawns the thread for both `spawn_unchecked` and `spawn_scoped`. When
+    // `scope_data` is given, the thread is counted as running in that scope
+    // until its packet is dropped. The packet's `'scope` is independent of the
+    // closure's `'a`, so that `spawn_unchecked` can use `'static` for it.
+    unsafe fn spawn_unchecked_<'a, 'scope, F, T>(
+        self,
+        f: F,
+        scope_data: Option<Arc<scoped::ScopeData>>,
+    ) -> io::Result<JoinInner<'scope, T>> where
+        F: FnOnce() -> T, F: Send + 'a, T: Send + 'a, 'scope: 'a
     {
         let Builder { name, stack_size } = self;
 
@@ -455,10 +475,17 @@ impl Builder {
         let my_thread = Thread::new(name);
         let their_thread = my_thread.clone();
 
-        let my_packet : Arc<UnsafeCell<Option<Result<T>>>>
-            = Arc::new(UnsafeCell::new(None));
+        let my_packet: Arc<Packet<'scope, T>> = Arc::new(Packet {
+            scope: scope_data,
+            result: UnsafeCell::new(None),
+            _marker: PhantomData,
+        });
         let their_packet = my_packet.clone();
 
+        if let Some(scope_data) = &my_packet.scope {
+            scope_data.increment_num_running_threads();
+        }
+
         let main = move || {
             if let Some(name) = their_thread.cname() {
                 imp::Thread::set_name(name);
@@ -468,10 +495,13 @@ impl Builder {
             let try_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
                 crate::sys_common::backtrace::__rust_begin_short_backtrace(f)
             }));
-            *their_packet.get() = Some(try_result);
+            *their_packet.result.get() = Some(try_result);
+            // Drop our reference to the packet here, as the scope this thread
+            // belongs to may end as soon as the last reference is gone.
+            drop(their_packet);
         };
 
-        Ok(JoinHandle(JoinInner {
+        Ok(JoinInner {
             // `imp::Thread::new` takes a closure with a `'static` lifetime, since it's passed
             // through FFI or otherwise used with low-level threading primitives that have no
             // notion of or way to enforce lifetimes.
@@ -490,8 +520,8 @@ impl Builder {
                 )),
             )?),
             thread: my_thread,
-            packet: Packet(my_packet),
-        }))
+            packet: my_packet,
+        })
     }
 }
 
@@ -1008,6 +1038,36 @@ pub fn park_timeout(dur: Duration) {
     }
 }
 
+/// Returns the number of hardware threads available to the program.
+///
+/// This value should be considered only a hint: the number of threads the
+/// program can actually run in parallel may be lower, for instance when other
+/// processes compete for the same cores, or when a container limits the CPU
+/// time of the process rather than the CPUs it may run on.
+///
+/// On Linux, this is the number of CPUs in the affinity mask of the current
+/// thread, so restrictions set with `taskset` or a cgroup cpuset are taken
+/// into account.
+///
+/// # Errors
+///
+/// Returns an error if the number of hardware threads is not known for the
+/// target platform, or if it can't be queried.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(available_concurrency)]
+/// use std::thread;
+///
+/// let count = thread::available_concurrency().map(|n| n.get()).unwrap_or(1);
+/// println!("spawning {} worker threads", count);
+/// ```
+#[unstable(feature = "available_concurrency", issue = "0")]
+pub fn available_concurrency() -> io::Result<NonZeroUsize> {
+    imp::available_concurrency()
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // ThreadId
 ////////////////////////////////////////////////////////////////////////////////
@@ -1308,34 +1368,64 @@ impl fmt::Debug for Thread {
 pub type Result<T> = crate::result::Result<T, Box<dyn Any + Send + 'static>>;
 
 // This packet is used to communicate the return value between the child thread
-// and the parent thread. Memory is shared through the `Arc` within and there's
-// no need for a mutex here because synchronization happens with `join()` (the
-// parent thread never reads this packet until the child has exited).
+// and the parent thread. Memory is shared through an `Arc` and there's no need
+// for a mutex here because synchronization happens with `join()` (the parent
+// thread never reads this packet until the child has exited).
 //
-// This packet itself is then stored into a `JoinInner` which in turns is placed
-// in `JoinHandle` and `JoinGuard`. Due to the usage of `UnsafeCell` we need to
-// manually worry about impls like Send and Sync. The type `T` should
+// An `Arc` to this packet is stored into a `JoinInner` which in turns is placed
+// in `JoinHandle` or `ScopedJoinHandle`. Due to the usage of `UnsafeCell` we
+// need to manually worry about impls like Send and Sync. The type `T` should
 // already always be Send (otherwise the thread could not have been created) and
 // this type is inherently Sync because no methods take &self. Regardless,
 // however, we add inheriting impls for Send/Sync to this type to ensure it's
 // Send/Sync and that future modifications will still appropriately classify it.
-struct Packet<T>(Arc<UnsafeCell<Option<Result<T>>>>);
+//
+// For a scoped thread, the packet also keeps the scope informed: the thread
+// counts as running until the packet is dropped, which happens once both the
+// thread and its `ScopedJoinHandle` are done with it.
+struct Packet<'scope, T> {
+    scope: Option<Arc<scoped::ScopeData>>,
+    result: UnsafeCell<Option<Result<T>>>,
+    _marker: PhantomData<Option<&'scope scoped::ScopeData>>,
+}
+
+unsafe impl<'scope, T: Send> Send for Packet<'scope, T> {}
+unsafe impl<'scope, T: Sync> Sync for Packet<'scope, T> {}
 
-unsafe impl<T: Send> Send for Packet<T> {}
-unsafe impl<T: Sync> Sync for Packet<T> {}
+impl<'scope, T> Drop for Packet<'scope, T> {
+    fn drop(&mut self) {
+        // If the thread panicked and nothing joined it, the panic is still
+        // stored here, and the scope has to report it.
+        let unhandled_panic = match unsafe { &*self.result.get() } {
+            Some(Err(_)) => true,
+            _ => false,
+        };
+        // The result may borrow from the scope, so it has to be dropped before
+        // the scope is told that this thread is done. A panic while dropping
+        // it could not be reported to anyone, so it aborts.
+        if panic::catch_unwind(panic::AssertUnwindSafe(|| unsafe {
+            *self.result.get() = None;
+        })).is_err() {
+            rtabort!("thread result panicked on drop");
+        }
+        if let Some(scope) = &self.scope {
+            scope.decrement_num_running_threads(unhandled_panic);
+        }
+    }
+}
 
-/// Inner representation for JoinHandle
-struct JoinInner<T> {
+/// Inner representation for JoinHandle and ScopedJoinHandle
+struct JoinInner<'scope, T> {
     native: Option<imp::Thread>,
     thread: Thread,
-    packet: Packet<T>,
+    packet: Arc<Packet<'scope, T>>,
 }
 
-impl<T> JoinInner<T> {
+impl<'scope, T> JoinInner<'scope, T> {
     fn join(&mut self) -> Result<T> {
         self.native.take().unwrap().join();
         unsafe {
-            (*self.packet.0.get()).take().unwrap()
+            (*self.packet.result.get()).take().unwrap()
         }
     }
 }
@@ -1404,7 +1494,7 @@ impl<T> JoinInner<T> {
 /// [`thread::spawn`]: fn.spawn.html
 /// [`thread::Builder::spawn`]: struct.Builder.html#method.spawn
 #[stable(feature = "rust1", since = "1.0.0")]
-pub struct JoinHandle<T>(JoinInner<T>);
+pub struct JoinHandle<T>(JoinInner<'static, T>);
 
 #[stable(feature = "joinhandle_impl_send_sync", since = "1.29.0")]
 unsafe impl<T> Send for JoinHandle<T> {}
@@ -1735,6 +1825,12 @@ mod tests {
         thread::sleep(Duration::from_millis(2));
     }
 
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "macos", windows))]
+    fn test_available_concurrency() {
+        assert!(thread::available_concurrency().is_ok());
+    }
+
     #[test]
     fn test_size_of_option_thread_id() {
         assert_eq!(mem::size_of::<Option<ThreadId>>(), mem::size_of::<ThreadId>());
@@ -51,7 +51,7 @@ pub fn expand_test(
     item: Annotatable,
 ) -> Vec<Annotatable> {
     check_builtin_macro_attribute(cx, meta_item, sym::test);
-    expand_test_or_bench(cx, attr_sp, item, false)
+    expand_test_or_bench(cx, attr_sp, item, TestKind::Test)
 }
 
 pub fn expand_bench(
@@ -61,14 +61,44 @@ pub fn expand_bench(
     item: Annotatable,
 ) -> Vec<Annotatable> {
     check_builtin_macro_attribute(cx, meta_item, sym::bench);
-    expand_test_or_bench(cx, attr_sp, item, true)
+    expand_test_or_bench(cx, attr_sp, item, TestKind::Bench)
 }
 
-pub fn expand_test_or_bench(
+pub fn expand_test_setup(
+    cx: &mut ExtCtxt<'_>,
+    attr_sp: Span,
+    meta_item: &ast::MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    check_builtin_macro_attribute(cx, meta_item, sym::test_setup);
+    expand_test_or_bench(cx, attr_sp, item, TestKind::Setup)
+}
+
+pub fn expand_test_teardown(
+    cx: &mut ExtCtxt<'_>,
+    attr_sp: Span,
+    meta_item: &ast::MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    check_builtin_macro_attribute(cx, meta_item, sym::test_teardown);
+    expand_test_or_bench(cx, attr_sp, item, TestKind::Teardown)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TestKind {
+    Test,
+    Bench,
+    /// `#[test_setup]`, run before the tests of its module.
+    Setup,
+    /// `#[test_teardown]`, run after the tests of its module.
+    Teardown,
+}
+
+fn expand_test_or_bench(
     cx: &mut ExtCtxt<'_>,
     attr_sp: Span,
     item: Annotatable,
-    is_bench: bool
+    kind: TestKind
 ) -> Vec<Annotatable> {
     // If we're not in test configuration, remove the annotated item
     if !cx.ecfg.should_test { return vec![]; }
@@ -89,8 +119,14 @@ pub fn expand_test_or_bench(
     // has_*_signature will report any errors in the type so compilation
     // will fail. We shouldn't try to expand in this case because the errors
     // would be spurious.
-    if (!is_bench && !has_test_signature(cx, &item)) ||
-        (is_bench && !has_bench_signature(cx, &item)) {
+    let has_signature = match kind {
+        TestKind::Bench => has_bench_signature(cx, &item),
+        TestKind::Test | TestKind::Setup | TestKind::Teardown => has_test_signature(cx, &item),
+    };
+    if !has_signature {
+        return vec![Annotatable::Item(item)];
+    }
+    if (kind == TestKind::Setup || kind == TestKind::Teardown) && !check_fixture_attrs(cx, &item) {
         return vec![Annotatable::Item(item)];
     }
 
@@ -116,7 +152,7 @@ pub fn expand_test_or_bench(
     // creates $name: $expr
     let field = |name, expr| cx.field_imm(sp, cx.ident_of(name, sp), expr);
 
-    let test_fn = if is_bench {
+    let test_fn = if kind == TestKind::Bench {
         // A simple ident for a lambda
         let b = cx.ident_of("b", attr_sp);
 
@@ -134,7 +170,19 @@ pub fn expand_test_or_bench(
             // )
         ])
     } else {
-        cx.expr_call(sp, cx.expr_path(test_path("StaticTestFn")), vec![
+        // test::StaticTestFn(
+        // | test::StaticFixtureFn(test::FixtureKind::Setup | test::FixtureKind::Teardown,
+        let (fn_path, mut args) = match kind {
+            TestKind::Setup | TestKind::Teardown => {
+                let fixture_kind = if kind == TestKind::Setup { "Setup" } else { "Teardown" };
+                let fixture_kind_path = cx.path(sp, vec![
+                    test_id, cx.ident_of("FixtureKind", sp), cx.ident_of(fixture_kind, sp)
+                ]);
+                (test_path("StaticFixtureFn"), vec![cx.expr_path(fixture_kind_path)])
+            }
+            TestKind::Test | TestKind::Bench => (test_path("StaticTestFn"), vec![]),
+        };
+        args.push(
             // || {
             cx.lambda0(sp,
                 // test::assert_test_result(
@@ -145,8 +193,8 @@ pub fn expand_test_or_bench(
                 ])
             // }
             )
-        // )
-        ])
+        );
+        cx.expr_call(sp, cx.expr_path(fn_path), args)
     };
 
     let mut test_const = cx.item(sp, ast::Ident::new(item.ident.name, sp),
@@ -202,6 +250,7 @@ pub fn expand_test_or_bench(
                 // },
                 ])),
                 // testfn: test::StaticTestFn(...) | test::StaticBenchFn(...)
+                //       | test::StaticFixtureFn(...)
                 field("testfn", test_fn)
             // }
             ])
@@ -360,6 +409,23 @@ fn has_test_signature(cx: &ExtCtxt<'_>, i: &ast::Item) -> bool {
     }
 }
 
+/// Checks that a fixture isn't marked with any of the attributes which only make
+/// sense on tests: an ignored fixture would silently never run, and fixtures
+/// are always expected to succeed.
+fn check_fixture_attrs(cx: &ExtCtxt<'_>, i: &ast::Item) -> bool {
+    let mut ok = true;
+    for &name in &[sym::ignore, sym::should_panic, sym::allow_fail] {
+        if let Some(attr) = attr::find_by_name(&i.attrs, name) {
+            cx.parse_sess.span_diagnostic.span_err(
+                attr.span,
+                &format!("`#[{}]` cannot be used on test fixtures", name),
+            );
+            ok = false;
+        }
+    }
+    ok
+}
+
 fn has_bench_signature(cx: &ExtCtxt<'_>, i: &ast::Item) -> bool {
     let has_sig = if let ast::ItemKind::Fn(ref sig, _, _) = i.kind {
         // N.B., inadequate check, but we're running
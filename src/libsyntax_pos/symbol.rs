@@ -127,6 +127,7 @@ symbols! {
         align,
         alignstack,
         all,
+        alloc,
         allocator,
         allocator_internals,
         alloc_error_handler,
@@ -685,6 +686,8 @@ symbols! {
         test_case,
         test_removed_feature,
         test_runner,
+        test_setup,
+        test_teardown,
         then_with,
         thread_local,
         tool_attributes,
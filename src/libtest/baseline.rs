@@ -0,0 +1,235 @@
+//! Module providing named benchmark baselines: the results of a benchmark run
+//! are saved with `--save-baseline NAME`, and later runs are compared against
+//! them with `--baseline NAME`.
+//!
+//! Baselines are stored next to the test binary, in
+//! `bench-baselines/NAME/<binary name>.txt`, so that every benchmark binary of
+//! a project gets its own file.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use super::{bench::SAMPLE_COUNT, stats};
+
+/// Significance level below which a change is considered to be more than noise.
+const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Smallest change of the mean (in percent) which is reported as a regression or
+/// an improvement, however significant it is.
+const NOISE_THRESHOLD_PCT: f64 = 2.0;
+
+const HEADER: &str = "# libtest benchmark baseline v1";
+
+/// Statistics of a single benchmark, as stored in a baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchStats {
+    /// Mean time per iteration, in nanoseconds.
+    pub mean: f64,
+    pub var: f64,
+    pub samples: usize,
+}
+
+impl BenchStats {
+    pub fn new(summary: &stats::Summary) -> BenchStats {
+        BenchStats {
+            mean: summary.mean,
+            var: summary.var,
+            samples: SAMPLE_COUNT,
+        }
+    }
+
+    /// Checks whether the statistics can be saved and compared, that is
+    /// whether they are finite and computed from at least one sample.
+    pub fn is_valid(&self) -> bool {
+        self.mean.is_finite() && self.var.is_finite() && self.var >= 0.0 && self.samples > 0
+    }
+}
+
+/// Statistics of a set of benchmarks, keyed by their names.
+#[derive(Debug, Default, PartialEq)]
+pub struct Baseline {
+    benches: BTreeMap<String, BenchStats>,
+}
+
+impl Baseline {
+    pub fn new() -> Baseline {
+        Baseline::default()
+    }
+
+    pub fn insert(&mut self, name: &str, stats: BenchStats) {
+        self.benches.insert(name.to_owned(), stats);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BenchStats> {
+        self.benches.get(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.benches.is_empty()
+    }
+
+    /// Loads the baseline with the given name.
+    pub fn load(name: &str) -> io::Result<Baseline> {
+        let path = baseline_path(name)?;
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            let msg = format!("failed to read benchmark baseline {}: {}", path.display(), e);
+            io::Error::new(e.kind(), msg)
+        })?;
+        contents.parse::<Baseline>().map_err(|e| {
+            let msg = format!("malformed benchmark baseline {}: {}", path.display(), e);
+            io::Error::new(io::ErrorKind::InvalidData, msg)
+        })
+    }
+
+    /// Saves the baseline under the given name, replacing the previous one.
+    pub fn save(&self, name: &str) -> io::Result<()> {
+        let path = baseline_path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, self.to_string())
+    }
+}
+
+impl FromStr for Baseline {
+    type Err = String;
+
+    fn from_str(contents: &str) -> Result<Baseline, String> {
+        fn parse_line(line: &str) -> Option<(&str, BenchStats)> {
+            let mut fields = line.splitn(4, ' ');
+            let samples = fields.next()?.parse().ok()?;
+            let mean = fields.next()?.parse().ok()?;
+            let var = fields.next()?.parse().ok()?;
+            let name = fields.next()?;
+            let stats = BenchStats { mean, var, samples };
+            if stats.is_valid() {
+                Some((name, stats))
+            } else {
+                None
+            }
+        }
+
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Err("unsupported format".to_owned());
+        }
+
+        let mut baseline = Baseline::new();
+        for (i, line) in lines.enumerate() {
+            let (name, stats) = parse_line(line)
+                .ok_or_else(|| format!("invalid line {}", i + 2))?;
+            baseline.insert(name, stats);
+        }
+
+        Ok(baseline)
+    }
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for (name, stats) in &self.benches {
+            writeln!(f, "{} {} {} {}", stats.samples, stats.mean, stats.var, name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the path of the file storing the baseline with the given name.
+fn baseline_path(name: &str) -> io::Result<PathBuf> {
+    let exe = env::current_exe()?;
+    let dir = exe.parent().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "the test binary has no parent directory")
+    })?;
+    let file_name = match exe.file_stem() {
+        Some(stem) => format!("{}.txt", stem.to_string_lossy()),
+        None => "test.txt".to_owned(),
+    };
+    Ok(dir.join("bench-baselines").join(name).join(file_name))
+}
+
+/// Checks whether the name can be used for a baseline, that is whether it is a
+/// valid file name.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(&['/', '\\'][..])
+}
+
+/// How a benchmark has changed compared to the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchChange {
+    Regressed,
+    Improved,
+    /// The change is either not significant or too small to matter.
+    Unchanged,
+}
+
+/// Comparison of a benchmark against its result in a baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BaselineComparison {
+    /// Mean time per iteration in the baseline, in nanoseconds.
+    pub baseline_mean: f64,
+    /// Mean time per iteration in this run, in nanoseconds.
+    pub mean: f64,
+    /// Probability of observing a difference at least this large if the
+    /// benchmark's performance hasn't actually changed.
+    pub p_value: f64,
+}
+
+impl BaselineComparison {
+    pub fn new(baseline: &BenchStats, current: &BenchStats) -> BaselineComparison {
+        let p_value = stats::welch_t_test(
+            baseline.mean,
+            baseline.var,
+            baseline.samples,
+            current.mean,
+            current.var,
+            current.samples,
+        );
+        BaselineComparison {
+            baseline_mean: baseline.mean,
+            mean: current.mean,
+            p_value,
+        }
+    }
+
+    /// Relative change of the mean time per iteration, in percent.
+    pub fn change_pct(&self) -> f64 {
+        if self.baseline_mean == 0.0 {
+            return 0.0;
+        }
+        (self.mean - self.baseline_mean) / self.baseline_mean * 100.0
+    }
+
+    pub fn change(&self) -> BenchChange {
+        let change_pct = self.change_pct();
+        if self.p_value >= SIGNIFICANCE_LEVEL || change_pct.abs() < NOISE_THRESHOLD_PCT {
+            BenchChange::Unchanged
+        } else if change_pct > 0.0 {
+            BenchChange::Regressed
+        } else {
+            BenchChange::Improved
+        }
+    }
+}
+
+impl fmt::Display for BenchChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            BenchChange::Regressed => "regressed",
+            BenchChange::Improved => "improved",
+            BenchChange::Unchanged => "no change",
+        };
+        f.write_str(s)
+    }
+}
+
+impl fmt::Display for BaselineComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+.2}% (p = {:.3}, {})", self.change_pct(), self.p_value, self.change())
+    }
+}
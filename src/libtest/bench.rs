@@ -2,6 +2,7 @@
 pub use std::hint::black_box;
 
 use super::{
+    baseline::BaselineComparison,
     event::CompletedTest,
     helpers::sink::Sink,
     options::BenchMode,
@@ -17,6 +18,9 @@ use std::io;
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::sync::{Arc, Mutex};
 
+/// Number of samples collected for each benchmark.
+pub const SAMPLE_COUNT: usize = 50;
+
 /// Manager of the benchmarking runs.
 ///
 /// This is fed into functions marked with `#[bench]` to allow for
@@ -56,6 +60,9 @@ impl Bencher {
 pub struct BenchSamples {
     pub ns_iter_summ: stats::Summary,
     pub mb_s: usize,
+    /// Comparison against the result of the benchmark in the baseline passed
+    /// with `--baseline`, if any.
+    pub baseline: Option<BaselineComparison>,
 }
 
 pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
@@ -77,6 +84,11 @@ pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
             .write_fmt(format_args!(" = {} MB/s", bs.mb_s))
             .unwrap();
     }
+    if let Some(ref comparison) = bs.baseline {
+        output
+            .write_fmt(format_args!(" change: {}", comparison))
+            .unwrap();
+    }
     output
 }
 
@@ -139,7 +151,7 @@ where
     n = cmp::max(1, n);
 
     let mut total_run = Duration::new(0, 0);
-    let samples: &mut [f64] = &mut [0.0_f64; 50];
+    let samples: &mut [f64] = &mut [0.0_f64; SAMPLE_COUNT];
     loop {
         let loop_start = Instant::now();
 
@@ -224,6 +236,7 @@ where
             let bs = BenchSamples {
                 ns_iter_summ,
                 mb_s: mb_s as usize,
+                baseline: None,
             };
             TestResult::TrBench(bs)
         }
@@ -234,6 +247,7 @@ where
             let bs = BenchSamples {
                 ns_iter_summ: stats::Summary::new(samples),
                 mb_s: 0,
+                baseline: None,
             };
             TestResult::TrBench(bs)
         }
@@ -2,9 +2,11 @@
 
 use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
 use getopts;
 
-use super::options::{RunIgnored, ColorConfig, OutputFormat, Options};
+use super::options::{RunIgnored, ColorConfig, OutputFormat, Options, Shard};
+use super::baseline;
 use super::time::TestTimeOptions;
 use super::helpers::isatty;
 
@@ -24,6 +26,11 @@ pub struct TestOpts {
     pub format: OutputFormat,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
+    pub shard: Option<Shard>,
+    pub retries: usize,
+    pub test_timeout: Option<Duration>,
+    pub save_baseline: Option<String>,
+    pub baseline: Option<String>,
     pub time_options: Option<TestTimeOptions>,
     pub options: Options,
 }
@@ -80,6 +87,50 @@ fn optgroups() -> getopts::Options {
              be used multiple times)",
             "FILTER",
         )
+        .optopt(
+            "",
+            "shard-index",
+            "Run only the tests assigned to the shard with this index \
+             (0-based, requires --shard-count)",
+            "N",
+        )
+        .optopt(
+            "",
+            "shard-count",
+            "Deterministically split the tests into this many shards \
+             by hashing their names (requires --shard-index)",
+            "M",
+        )
+        .optopt(
+            "",
+            "retries",
+            "Run each failed test again up to N times, and report the tests \
+             which passed after a retry as flaky. Dynamic tests whose function \
+             can only be called once are not retried",
+            "N",
+        )
+        .optopt(
+            "",
+            "timeout",
+            "Fail the tests which are still running after SECS seconds. \
+             When tests are run in separate processes (panic=abort), \
+             the hung processes are killed",
+            "SECS",
+        )
+        .optopt(
+            "",
+            "save-baseline",
+            "Save the results of the benchmarks as a baseline with the given name, \
+             next to the test binary",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "baseline",
+            "Compare the results of the benchmarks against the baseline with the given \
+             name, and fail if any of them has regressed significantly",
+            "NAME",
+        )
         .optflag(
             "q",
             "quiet",
@@ -106,8 +157,9 @@ fn optgroups() -> getopts::Options {
             "Configure formatting of output:
             pretty = Print verbose output;
             terse  = Display one character per test;
-            json   = Output a json document",
-            "pretty|terse|json",
+            json   = Output a json document;
+            junit  = Output a JUnit XML document",
+            "pretty|terse|json|junit",
         )
         .optflag(
             "",
@@ -239,6 +291,11 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     let exclude_should_panic = unstable_optflag!(matches, allow_unstable, "exclude-should-panic");
     let include_ignored = unstable_optflag!(matches, allow_unstable, "include-ignored");
     let time_options = get_time_options(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
+    let test_timeout = get_test_timeout(&matches, allow_unstable)?;
+    let save_baseline = get_baseline_name(&matches, allow_unstable, "save-baseline")?;
+    let baseline = get_baseline_name(&matches, allow_unstable, "baseline")?;
 
     let quiet = matches.opt_present("quiet");
     let exact = matches.opt_present("exact");
@@ -256,6 +313,13 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     let color = get_color_config(&matches)?;
     let format = get_format(&matches, quiet, allow_unstable)?;
 
+    // JUnit reports carry the execution time of every test case, so it has to be
+    // measured even if `--report-time` wasn't requested.
+    let time_options = match (time_options, format) {
+        (None, OutputFormat::Junit) => Some(TestTimeOptions::default()),
+        (time_options, _) => time_options,
+    };
+
     let options = Options::new().display_output(matches.opt_present("show-output"));
 
     let test_opts = TestOpts {
@@ -273,6 +337,11 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         format,
         test_threads,
         skip,
+        shard,
+        retries,
+        test_timeout,
+        save_baseline,
+        baseline,
         time_options,
         options,
     };
@@ -314,6 +383,121 @@ fn get_time_options(
     Ok(options)
 }
 
+// Gets the CLI options associated with test sharding.
+fn get_shard(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<Shard>> {
+    fn parse_number(matches: &getopts::Matches, name: &str) -> OptPartRes<Option<usize>> {
+        match matches.opt_str(name) {
+            Some(n_str) => n_str.parse::<usize>().map(Some).map_err(|e| {
+                format!("argument for --{} must be a number (error: {})", name, e)
+            }),
+            None => Ok(None),
+        }
+    }
+
+    let index = parse_number(matches, "shard-index")?;
+    let count = parse_number(matches, "shard-count")?;
+
+    let shard = match (index, count) {
+        (None, None) => return Ok(None),
+        (Some(_), None) | (None, Some(_)) => {
+            return Err(
+                "the options --shard-index and --shard-count must be used together".into(),
+            );
+        }
+        (Some(index), Some(count)) => Shard { index, count },
+    };
+
+    if !allow_unstable {
+        return Err("The \"shard-index\" and \"shard-count\" flags are only accepted \
+                    on the nightly compiler".into());
+    }
+    if shard.count == 0 {
+        return Err("argument for --shard-count must not be 0".into());
+    }
+    if shard.index >= shard.count {
+        return Err(format!(
+            "argument for --shard-index must be less than --shard-count ({} >= {})",
+            shard.index, shard.count
+        ));
+    }
+
+    Ok(Some(shard))
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match matches.opt_str("retries") {
+        Some(n_str) => {
+            if !allow_unstable {
+                return Err(
+                    "The \"retries\" flag is only accepted on the nightly compiler".into(),
+                );
+            }
+            n_str.parse::<usize>().map_err(|e| {
+                format!("argument for --retries must be a number (error: {})", e)
+            })?
+        }
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
+fn get_test_timeout(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Duration>> {
+    let test_timeout = match matches.opt_str("timeout") {
+        Some(n_str) => {
+            if !allow_unstable {
+                return Err(
+                    "The \"timeout\" flag is only accepted on the nightly compiler".into(),
+                );
+            }
+            match n_str.parse::<u64>() {
+                Ok(0) => return Err("argument for --timeout must not be 0".to_string()),
+                Ok(secs) => Some(Duration::from_secs(secs)),
+                Err(e) => {
+                    return Err(format!(
+                        "argument for --timeout must be a number of seconds > 0 \
+                         (error: {})",
+                        e
+                    ));
+                }
+            }
+        }
+        None => None,
+    };
+
+    Ok(test_timeout)
+}
+
+// Gets the name of the benchmark baseline passed to the given option.
+fn get_baseline_name(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+    option_name: &str,
+) -> OptPartRes<Option<String>> {
+    let name = match matches.opt_str(option_name) {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+
+    if !allow_unstable {
+        return Err(format!(
+            "The \"{}\" flag is only accepted on the nightly compiler",
+            option_name
+        ));
+    }
+    if !baseline::is_valid_name(&name) {
+        return Err(format!(
+            "argument for --{} must be a valid file name (was {})",
+            option_name, name
+        ));
+    }
+
+    Ok(Some(name))
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
@@ -350,10 +534,18 @@ fn get_format(
             }
             OutputFormat::Json
         }
+        Some("junit") => {
+            if !allow_unstable {
+                return Err(
+                    "The \"junit\" format is only accepted on the nightly compiler".into(),
+                );
+            }
+            OutputFormat::Junit
+        }
 
         Some(v) => {
             return Err(format!(
-                "argument for --format must be pretty, terse, or json (was \
+                "argument for --format must be pretty, terse, json, or junit (was \
                  {})",
                 v
             ));
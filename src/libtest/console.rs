@@ -1,16 +1,21 @@
 //! Module providing interface for running tests in the console.
 
+use std::env;
 use std::fs::File;
 use std::io::prelude::Write;
 use std::io;
+use std::path::Path;
 
 use term;
 
 use super::{
-    bench::fmt_bench_samples,
+    baseline::{Baseline, BaselineComparison, BenchChange, BenchStats},
+    bench::{fmt_bench_samples, BenchSamples},
     cli::TestOpts,
     event::{TestEvent, CompletedTest},
-    formatters::{JsonFormatter, OutputFormatter, PrettyFormatter, TerseFormatter},
+    formatters::{
+        JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter,
+    },
     helpers::{
         concurrency::get_concurrency,
         metrics::MetricMap,
@@ -52,12 +57,20 @@ pub struct ConsoleTestState {
     pub failed: usize,
     pub ignored: usize,
     pub allowed_fail: usize,
+    pub flaky: usize,
     pub filtered_out: usize,
     pub measured: usize,
     pub metrics: MetricMap,
     pub failures: Vec<(TestDesc, Vec<u8>)>,
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
     pub time_failures: Vec<(TestDesc, Vec<u8>)>,
+    pub flaky_tests: Vec<(TestDesc, Vec<u8>)>,
+    pub fixture_failures: Vec<(TestDesc, Vec<u8>)>,
+    pub bench_regressions: Vec<(TestDesc, Vec<u8>)>,
+    /// Baseline the benchmarks are compared against.
+    pub baseline: Option<Baseline>,
+    /// Results of the benchmarks run so far, to be saved as a baseline.
+    pub bench_stats: Baseline,
     pub options: Options,
 }
 
@@ -68,6 +81,11 @@ impl ConsoleTestState {
             None => None,
         };
 
+        let baseline = match opts.baseline {
+            Some(ref name) => Some(Baseline::load(name)?),
+            None => None,
+        };
+
         Ok(ConsoleTestState {
             log_out,
             total: 0,
@@ -75,12 +93,18 @@ impl ConsoleTestState {
             failed: 0,
             ignored: 0,
             allowed_fail: 0,
+            flaky: 0,
             filtered_out: 0,
             measured: 0,
             metrics: MetricMap::new(),
             failures: Vec::new(),
             not_failures: Vec::new(),
             time_failures: Vec::new(),
+            flaky_tests: Vec::new(),
+            fixture_failures: Vec::new(),
+            bench_regressions: Vec::new(),
+            baseline,
+            bench_stats: Baseline::new(),
             options: opts.options,
         })
     }
@@ -117,6 +141,7 @@ impl ConsoleTestState {
                 TestResult::TrAllowedFail => "failed (allowed)".to_owned(),
                 TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                 TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                TestResult::TrFlaky(retries) => format!("ok (flaky, {} retries)", retries),
             },
             test.name,
         ))?;
@@ -126,8 +151,29 @@ impl ConsoleTestState {
         self.write_log(|| "\n")
     }
 
+    /// Checks whether the test run has succeeded so far.
+    pub fn is_success(&self) -> bool {
+        self.failed == 0 && self.fixture_failures.is_empty() && self.bench_regressions.is_empty()
+    }
+
+    /// Records the result of the benchmark so that it can be saved as a
+    /// baseline, and compares it against the current baseline, if any.
+    fn record_bench(&mut self, test: &TestDesc, bs: &mut BenchSamples) {
+        let name = test.name.as_slice();
+        let stats = BenchStats::new(&bs.ns_iter_summ);
+        // Neither saved nor compared, as they would make the baseline unusable.
+        if !stats.is_valid() {
+            return;
+        }
+        bs.baseline = self.baseline
+            .as_ref()
+            .and_then(|baseline| baseline.get(name))
+            .map(|baseline_stats| BaselineComparison::new(baseline_stats, &stats));
+        self.bench_stats.insert(name, stats);
+    }
+
     fn current_test_count(&self) -> usize {
-        self.passed + self.failed + self.ignored + self.measured + self.allowed_fail
+        self.passed + self.failed + self.ignored + self.measured + self.allowed_fail + self.flaky
     }
 }
 
@@ -153,7 +199,7 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
         } = test;
 
         let fntype = match testfn {
-            StaticTestFn(..) | DynTestFn(..) => {
+            StaticTestFn(..) | DynTestFn(..) | DynRetryableTestFn(..) => {
                 ntest += 1;
                 "test"
             }
@@ -161,6 +207,8 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
                 nbench += 1;
                 "benchmark"
             }
+            // Fixtures are run as a part of the tests, rather than on their own.
+            StaticFixtureFn(..) => continue,
         };
 
         writeln!(output, "{}: {}", name, fntype)?;
@@ -207,6 +255,9 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
                 bs.ns_iter_summ.median,
                 bs.ns_iter_summ.max - bs.ns_iter_summ.min,
             );
+            if let Some(BenchChange::Regressed) = bs.baseline.map(|c| c.change()) {
+                st.bench_regressions.push((test, stdout));
+            }
             st.measured += 1
         }
         TestResult::TrFailed => {
@@ -223,6 +274,10 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrFlaky(_) => {
+            st.flaky += 1;
+            st.flaky_tests.push((test, stdout));
+        }
     }
 }
 
@@ -243,7 +298,11 @@ fn on_test_event(
         }
         TestEvent::TeWait(ref test) => out.write_test_start(test)?,
         TestEvent::TeTimeout(ref test) => out.write_timeout(test)?,
-        TestEvent::TeResult(completed_test) => {
+        TestEvent::TeResult(mut completed_test) => {
+            if let TestResult::TrBench(ref mut bs) = completed_test.result {
+                st.record_bench(&completed_test.desc, bs);
+            }
+
             let test = &completed_test.desc;
             let result = &completed_test.result;
             let exec_time = &completed_test.exec_time;
@@ -253,6 +312,12 @@ fn on_test_event(
             out.write_result(test, result, exec_time.as_ref(), &*stdout, st)?;
             handle_test_result(st, completed_test);
         }
+        TestEvent::TeFixtureFailed(completed_fixture) => {
+            let fixture = completed_fixture.desc;
+            st.write_log(|| format!("fixture failed {}\n", fixture.name))?;
+            out.write_fixture_failure(&fixture, &completed_fixture.stdout)?;
+            st.fixture_failures.push((fixture, completed_fixture.stdout));
+        }
     }
 
     Ok(())
@@ -289,6 +354,7 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
             is_multithreaded,
         )),
         OutputFormat::Json => Box::new(JsonFormatter::new(output)),
+        OutputFormat::Junit => Box::new(JunitFormatter::new(output, suite_name())),
     };
     let mut st = ConsoleTestState::new(opts)?;
 
@@ -296,9 +362,27 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
 
     assert!(st.current_test_count() == st.total);
 
+    // Keep the previous baseline if no benchmark ran, e.g., because of a filter.
+    if let (true, Some(name)) = (opts.bench_benchmarks, &opts.save_baseline) {
+        if !st.bench_stats.is_empty() {
+            st.bench_stats.save(name)?;
+        }
+    }
+
     out.write_run_finish(&st)
 }
 
+// Name of the test suite as reported by machine-readable formatters: the file
+// stem of the test binary, falling back to a generic name if it's unknown.
+fn suite_name() -> String {
+    env::args_os()
+        .next()
+        .as_ref()
+        .and_then(|arg0| Path::new(arg0).file_stem())
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "test".to_owned())
+}
+
 // Calculates padding for given test description.
 fn len_if_padded(t: &TestDescAndFn) -> usize {
     match t.testfn.padding() {
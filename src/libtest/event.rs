@@ -38,4 +38,5 @@ pub enum TestEvent {
     TeResult(CompletedTest),
     TeTimeout(TestDesc),
     TeFilteredOut(usize),
+    TeFixtureFailed(CompletedTest),
 }
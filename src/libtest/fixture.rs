@@ -0,0 +1,253 @@
+//! Module providing the setup and teardown fixtures of tests, defined with
+//! the `#[test_setup]` and `#[test_teardown]` attributes.
+
+use std::collections::HashSet;
+use std::io;
+use std::mem;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+use super::{
+    cli::TestOpts,
+    event::{CompletedTest, TestEvent},
+    helpers::sink::Sink,
+    test_result::TestResult,
+    types::{FixtureKind, TestDesc, TestDescAndFn, TestFn, TestName},
+    __rust_begin_short_backtrace,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+enum ScopeState {
+    /// None of the tests in the scope has been started yet.
+    Pending,
+    /// The setup fixtures have run successfully.
+    SetUp,
+    /// The setup fixture with the given name has failed, so none of the
+    /// tests in the scope can be run.
+    SetupFailed(String),
+    /// All the tests in the scope have completed.
+    Finished,
+}
+
+/// Fixtures defined in a single module, which apply to all the tests of the
+/// module and its submodules.
+struct FixtureScope {
+    /// Path of the module, empty for the crate root.
+    module_path: String,
+    setup: Vec<(TestDesc, fn())>,
+    teardown: Vec<(TestDesc, fn())>,
+    /// Number of the tests within the scope which are yet to complete.
+    pending_tests: usize,
+    state: ScopeState,
+}
+
+impl FixtureScope {
+    fn new(module_path: &str) -> Self {
+        FixtureScope {
+            module_path: module_path.to_owned(),
+            setup: Vec::new(),
+            teardown: Vec::new(),
+            pending_tests: 0,
+            state: ScopeState::Pending,
+        }
+    }
+
+    fn contains(&self, desc: &TestDesc) -> bool {
+        let test_module = desc.module_path();
+        self.module_path.is_empty()
+            || test_module == self.module_path
+            || (test_module.starts_with(&*self.module_path)
+                && test_module[self.module_path.len()..].starts_with("::"))
+    }
+}
+
+/// Keeps track of the fixtures of the test binary, and runs them as the
+/// tests they apply to are started and completed.
+///
+/// Fixtures are run in the main process and on the thread scheduling the
+/// tests, so with `panic=abort` the state they set up in-process is not
+/// visible to the tests, which are run in subprocesses.
+pub struct Fixtures {
+    /// Fixture scopes, sorted so that outer modules precede the inner ones.
+    scopes: Vec<FixtureScope>,
+    /// Tests which have been registered to run, but haven't completed yet.
+    registered: HashSet<TestName>,
+    nocapture: bool,
+}
+
+/// Checks that the fixtures among `tests` can be run with the given options.
+///
+/// Fixtures are always run in the harness process. When the tests are run in
+/// subprocesses because of `panic=abort`, a fixture couldn't set up anything
+/// the tests would see, and a panicking fixture would abort the whole run
+/// without any report.
+pub fn check_supported(opts: &TestOpts, tests: &[TestDescAndFn]) -> Result<(), String> {
+    let has_fixtures = tests.iter().any(|test| match test.testfn {
+        TestFn::StaticFixtureFn(..) => true,
+        _ => false,
+    });
+    if has_fixtures && opts.options.panic_abort && !opts.force_run_in_process {
+        return Err("test fixtures are not supported with `panic=abort`, since the tests \
+                    are run in subprocesses (pass `-Z unstable-options --force-run-in-process` \
+                    to run them in the harness process)"
+            .to_string());
+    }
+    Ok(())
+}
+
+impl Fixtures {
+    /// Separates the fixtures from the actual tests.
+    pub fn split(tests: Vec<TestDescAndFn>, nocapture: bool) -> (Self, Vec<TestDescAndFn>) {
+        let mut scopes: Vec<FixtureScope> = Vec::new();
+        let mut rest = Vec::with_capacity(tests.len());
+
+        for test in tests {
+            let (kind, f) = match test.testfn {
+                TestFn::StaticFixtureFn(kind, f) => (kind, f),
+                _ => {
+                    rest.push(test);
+                    continue;
+                }
+            };
+            if test.desc.ignore {
+                continue;
+            }
+
+            let module_path = test.desc.module_path();
+            let idx = match scopes.iter().position(|scope| scope.module_path == module_path) {
+                Some(idx) => idx,
+                None => {
+                    scopes.push(FixtureScope::new(module_path));
+                    scopes.len() - 1
+                }
+            };
+            match kind {
+                FixtureKind::Setup => scopes[idx].setup.push((test.desc, f)),
+                FixtureKind::Teardown => scopes[idx].teardown.push((test.desc, f)),
+            }
+        }
+
+        // Parent modules are prefixes of their children, so they're sorted first.
+        scopes.sort_by(|a, b| a.module_path.cmp(&b.module_path));
+
+        let fixtures = Fixtures {
+            scopes,
+            registered: HashSet::new(),
+            nocapture,
+        };
+        (fixtures, rest)
+    }
+
+    /// Registers a test which is going to be run, so that the teardown
+    /// fixtures are run only after it has completed.
+    pub fn register_test(&mut self, desc: &TestDesc) {
+        let mut in_scope = false;
+        for scope in self.scopes.iter_mut().filter(|scope| scope.contains(desc)) {
+            scope.pending_tests += 1;
+            in_scope = true;
+        }
+        if in_scope {
+            self.registered.insert(desc.name.clone());
+        }
+    }
+
+    /// Runs the setup fixtures the test depends on, unless they have already
+    /// been run.
+    ///
+    /// Returns the result to be reported for the test if it can't be run
+    /// because one of the setup fixtures has failed.
+    pub fn start_test(
+        &mut self,
+        desc: &TestDesc,
+        notify_about_test_event: &mut dyn FnMut(TestEvent) -> io::Result<()>,
+    ) -> io::Result<Option<CompletedTest>> {
+        if !self.registered.contains(&desc.name) {
+            return Ok(None);
+        }
+
+        let nocapture = self.nocapture;
+        for scope in self.scopes.iter_mut().filter(|scope| scope.contains(desc)) {
+            if scope.state == ScopeState::Pending {
+                scope.state = ScopeState::SetUp;
+                for &(ref fixture, f) in &scope.setup {
+                    let completed_fixture = run_fixture(fixture, f, nocapture);
+                    if completed_fixture.result != TestResult::TrOk {
+                        scope.state = ScopeState::SetupFailed(fixture.name.to_string());
+                        notify_about_test_event(TestEvent::TeFixtureFailed(completed_fixture))?;
+                        break;
+                    }
+                }
+            }
+
+            if let ScopeState::SetupFailed(ref fixture_name) = scope.state {
+                let msg = format!("setup fixture `{}` failed", fixture_name);
+                let result = TestResult::TrFailedMsg(msg);
+                return Ok(Some(CompletedTest::new(desc.clone(), result, None, Vec::new())));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Marks the test as completed, running the teardown fixtures of the
+    /// scopes which have no more tests left to run.
+    pub fn complete_test(
+        &mut self,
+        desc: &TestDesc,
+        notify_about_test_event: &mut dyn FnMut(TestEvent) -> io::Result<()>,
+    ) -> io::Result<()> {
+        if !self.registered.remove(&desc.name) {
+            return Ok(());
+        }
+
+        // Inner scopes are torn down before the outer ones.
+        let nocapture = self.nocapture;
+        for scope in self.scopes.iter_mut().rev().filter(|scope| scope.contains(desc)) {
+            scope.pending_tests -= 1;
+            if scope.pending_tests != 0 {
+                continue;
+            }
+
+            // If the setup has failed, there is nothing to tear down.
+            if mem::replace(&mut scope.state, ScopeState::Finished) != ScopeState::SetUp {
+                continue;
+            }
+            for &(ref fixture, f) in &scope.teardown {
+                let completed_fixture = run_fixture(fixture, f, nocapture);
+                if completed_fixture.result != TestResult::TrOk {
+                    notify_about_test_event(TestEvent::TeFixtureFailed(completed_fixture))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn run_fixture(desc: &TestDesc, f: fn(), nocapture: bool) -> CompletedTest {
+    // Buffer for capturing standard I/O
+    let data = Arc::new(Mutex::new(Vec::new()));
+
+    let oldio = if !nocapture {
+        Some((
+            io::set_print(Some(Sink::new_boxed(&data))),
+            io::set_panic(Some(Sink::new_boxed(&data))),
+        ))
+    } else {
+        None
+    };
+
+    let result = catch_unwind(AssertUnwindSafe(|| __rust_begin_short_backtrace(f)));
+
+    if let Some((printio, panicio)) = oldio {
+        io::set_print(printio);
+        io::set_panic(panicio);
+    }
+
+    let result = match result {
+        Ok(()) => TestResult::TrOk,
+        Err(_) => TestResult::TrFailed,
+    };
+    let stdout = data.lock().unwrap().to_vec();
+    CompletedTest::new(desc.clone(), result, None, stdout)
+}
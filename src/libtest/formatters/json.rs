@@ -138,6 +138,15 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
                 None,
             ),
 
+            TestResult::TrFlaky(retries) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "flaky",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""retries": {}"#, retries)),
+            ),
+
             TestResult::TrBench(ref bs) => {
                 let median = bs.ns_iter_summ.median as usize;
                 let deviation = (bs.ns_iter_summ.max - bs.ns_iter_summ.min) as usize;
@@ -148,12 +157,28 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
                     format!(r#", "mib_per_second": {}"#, bs.mb_s)
                 };
 
+                let baseline = match bs.baseline {
+                    Some(ref comparison) => format!(
+                        ", \"baseline_mean\": {}, \
+                         \"mean\": {}, \
+                         \"change_pct\": {}, \
+                         \"p_value\": {}, \
+                         \"change\": \"{}\"",
+                        json_number(comparison.baseline_mean),
+                        json_number(comparison.mean),
+                        json_number(comparison.change_pct()),
+                        json_number(comparison.p_value),
+                        comparison.change()
+                    ),
+                    None => String::new(),
+                };
+
                 let line = format!(
                     "{{ \"type\": \"bench\", \
                      \"name\": \"{}\", \
                      \"median\": {}, \
-                     \"deviation\": {}{} }}",
-                    desc.name, median, deviation, mbps
+                     \"deviation\": {}{}{} }}",
+                    desc.name, median, deviation, mbps, baseline
                 );
 
                 self.writeln_message(&*line)
@@ -168,6 +193,15 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
         ))
     }
 
+    fn write_fixture_failure(&mut self, desc: &TestDesc, stdout: &[u8]) -> io::Result<()> {
+        let stdout = if stdout.len() > 0 {
+            Some(String::from_utf8_lossy(stdout))
+        } else {
+            None
+        };
+        self.write_event("fixture", desc.name.as_slice(), "failed", None, stdout, None)
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         self.writeln_message(&*format!(
             "{{ \"type\": \"suite\", \
@@ -175,19 +209,31 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
              \"passed\": {}, \
              \"failed\": {}, \
              \"allowed_fail\": {}, \
+             \"flaky\": {}, \
              \"ignored\": {}, \
              \"measured\": {}, \
              \"filtered_out\": {} }}",
-            if state.failed == 0 { "ok" } else { "failed" },
-            state.passed,
+            if state.is_success() { "ok" } else { "failed" },
+            state.passed + state.flaky,
             state.failed + state.allowed_fail,
             state.allowed_fail,
+            state.flaky,
             state.ignored,
             state.measured,
             state.filtered_out
         ))?;
 
-        Ok(state.failed == 0)
+        Ok(state.is_success())
+    }
+}
+
+/// Formats a number as JSON, which has no representation of NaN and infinities,
+/// so they are written as `null`.
+fn json_number(x: f64) -> String {
+    if x.is_finite() {
+        x.to_string()
+    } else {
+        "null".to_owned()
     }
 }
 
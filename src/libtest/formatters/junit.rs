@@ -0,0 +1,232 @@
+use std::{
+    io,
+    io::prelude::Write,
+    time::Duration,
+};
+
+use crate::{
+    types::TestDesc,
+    time,
+    test_result::TestResult,
+    console::{ConsoleTestState, OutputLocation},
+    bench::fmt_bench_samples,
+    baseline::BenchChange,
+};
+use super::OutputFormatter;
+
+/// Formatter producing a JUnit XML report.
+///
+/// Unlike the other formatters, nothing is written until the whole run is
+/// finished, since the `<testsuite>` element has to carry the totals of
+/// the run as its attributes.
+pub(crate) struct JunitFormatter<T> {
+    out: OutputLocation<T>,
+    suite_name: String,
+    results: Vec<(TestDesc, TestResult, Duration, Vec<u8>)>,
+}
+
+impl<T: Write> JunitFormatter<T> {
+    pub fn new(out: OutputLocation<T>, suite_name: String) -> Self {
+        Self {
+            out,
+            suite_name,
+            results: Vec::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
+    fn writeln_message(&mut self, s: &str) -> io::Result<()> {
+        self.out.write_all(s.as_ref())?;
+        self.out.write_all(b"\n")
+    }
+
+    fn write_system_out(&mut self, stdout: &[u8]) -> io::Result<()> {
+        if stdout.is_empty() {
+            return Ok(());
+        }
+
+        self.writeln_message(&*format!(
+            "<system-out>{}</system-out>",
+            EscapedXml(String::from_utf8_lossy(stdout))
+        ))
+    }
+}
+
+impl<T: Write> OutputFormatter for JunitFormatter<T> {
+    fn write_run_start(&mut self, _test_count: usize) -> io::Result<()> {
+        // The report is written as a whole in `write_run_finish`.
+        Ok(())
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // Long-running tests are not failures, so there is nothing to record
+        // until the test actually completes.
+        Ok(())
+    }
+
+    fn write_fixture_failure(&mut self, _desc: &TestDesc, _stdout: &[u8]) -> io::Result<()> {
+        // Fixture failures are reported as errors from `state` once the run is finished.
+        Ok(())
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        _state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        let duration = exec_time.map(|t| t.0).unwrap_or_default();
+        self.results.push((desc.clone(), result.clone(), duration, stdout.to_vec()));
+        Ok(())
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        let total_time: Duration = self.results.iter().map(|&(_, _, time, _)| time).sum();
+
+        self.writeln_message(r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        self.writeln_message("<testsuites>")?;
+        self.writeln_message(&*format!(
+            "<testsuite name=\"{}\" \
+             tests=\"{}\" \
+             failures=\"{}\" \
+             errors=\"{}\" \
+             skipped=\"{}\" \
+             time=\"{:.3}\">",
+            EscapedXml(&self.suite_name),
+            state.total + state.fixture_failures.len(),
+            state.failed + state.bench_regressions.len(),
+            state.fixture_failures.len(),
+            state.ignored,
+            total_time.as_secs_f64()
+        ))?;
+
+        let suite_name = self.suite_name.clone();
+        let results = std::mem::replace(&mut self.results, Vec::new());
+        for (desc, result, duration, stdout) in results {
+            let (class_name, test_name) = split_test_name(desc.name.as_slice(), &suite_name);
+            self.writeln_message(&*format!(
+                r#"<testcase classname="{}" name="{}" time="{:.3}">"#,
+                EscapedXml(class_name),
+                EscapedXml(test_name),
+                duration.as_secs_f64()
+            ))?;
+
+            match result {
+                TestResult::TrOk | TestResult::TrAllowedFail => {}
+                TestResult::TrIgnored => self.writeln_message("<skipped/>")?,
+                TestResult::TrFailed => {
+                    self.writeln_message(r#"<failure type="assert" message="test failed"/>"#)?
+                }
+                TestResult::TrFailedMsg(ref m) => self.writeln_message(&*format!(
+                    r#"<failure type="assert" message="{}"/>"#,
+                    EscapedXml(m)
+                ))?,
+                TestResult::TrTimedFail => self.writeln_message(
+                    r#"<failure type="timeout" message="time limit exceeded"/>"#,
+                )?,
+                TestResult::TrFlaky(retries) => self.writeln_message(&*format!(
+                    r#"<flakyFailure type="retry" message="passed after {} retries"/>"#,
+                    retries
+                ))?,
+                TestResult::TrBench(ref bs) => {
+                    if let Some(BenchChange::Regressed) = bs.baseline.map(|c| c.change()) {
+                        self.writeln_message(
+                            r#"<failure type="regression" message="benchmark regressed"/>"#,
+                        )?;
+                    }
+                    self.writeln_message(&*format!(
+                        "<system-out>{}</system-out>",
+                        EscapedXml(fmt_bench_samples(bs).trim())
+                    ))?
+                }
+            }
+
+            self.write_system_out(&stdout)?;
+            self.writeln_message("</testcase>")?;
+        }
+
+        for (desc, stdout) in &state.fixture_failures {
+            let (class_name, fixture_name) = split_test_name(desc.name.as_slice(), &suite_name);
+            self.writeln_message(&*format!(
+                r#"<testcase classname="{}" name="{}" time="0.000">"#,
+                EscapedXml(class_name),
+                EscapedXml(fixture_name)
+            ))?;
+            self.writeln_message(r#"<error type="fixture" message="fixture failed"/>"#)?;
+            self.write_system_out(stdout)?;
+            self.writeln_message("</testcase>")?;
+        }
+
+        self.writeln_message("</testsuite>")?;
+        self.writeln_message("</testsuites>")?;
+
+        Ok(state.is_success())
+    }
+}
+
+/// Splits a test path such as `module::submodule::test` into the JUnit
+/// class name (`module::submodule`) and the test name (`test`).
+///
+/// Tests defined at the crate root are attributed to the suite itself.
+fn split_test_name<'a>(name: &'a str, suite_name: &'a str) -> (&'a str, &'a str) {
+    match name.rfind("::") {
+        Some(pos) => (&name[..pos], &name[pos + 2..]),
+        None => (suite_name, name),
+    }
+}
+
+/// A formatting utility used to print strings as XML character data or
+/// attribute values.
+///
+/// Characters that are not allowed in XML 1.0 documents at all (most of the
+/// C0 control characters) are replaced with U+FFFD, so that arbitrary test
+/// output doesn't produce a malformed report.
+struct EscapedXml<S: AsRef<str>>(S);
+
+impl<S: AsRef<str>> ::std::fmt::Display for EscapedXml<S> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        let s = self.0.as_ref();
+        let mut start = 0;
+
+        for (i, byte) in s.bytes().enumerate() {
+            let escaped = match byte {
+                b'<' => "&lt;",
+                b'>' => "&gt;",
+                b'&' => "&amp;",
+                b'"' => "&quot;",
+                b'\'' => "&apos;",
+                b'\t' | b'\n' | b'\r' => {
+                    continue;
+                }
+                b'\x00'..=b'\x1f' => "\u{FFFD}",
+                _ => {
+                    continue;
+                }
+            };
+
+            if start < i {
+                f.write_str(&s[start..i])?;
+            }
+
+            f.write_str(escaped)?;
+
+            start = i + 1;
+        }
+
+        if start != s.len() {
+            f.write_str(&s[start..])?;
+        }
+
+        Ok(())
+    }
+}
@@ -12,16 +12,19 @@ use crate::{
 
 mod pretty;
 mod json;
+mod junit;
 mod terse;
 
 pub(crate) use self::pretty::PrettyFormatter;
 pub(crate) use self::json::JsonFormatter;
+pub(crate) use self::junit::JunitFormatter;
 pub(crate) use self::terse::TerseFormatter;
 
 pub(crate) trait OutputFormatter {
     fn write_run_start(&mut self, test_count: usize) -> io::Result<()>;
     fn write_test_start(&mut self, desc: &TestDesc) -> io::Result<()>;
     fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()>;
+    fn write_fixture_failure(&mut self, desc: &TestDesc, stdout: &[u8]) -> io::Result<()>;
     fn write_result(
         &mut self,
         desc: &TestDesc,
@@ -65,6 +65,10 @@ impl<T: Write> PrettyFormatter<T> {
         self.write_short_result("FAILED (time limit exceeded)", term::color::RED)
     }
 
+    pub fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("ok (flaky)", term::color::YELLOW)
+    }
+
     pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -176,6 +180,18 @@ impl<T: Write> PrettyFormatter<T> {
         self.write_results(&state.time_failures, "failures (time limit exceeded)")
     }
 
+    pub fn write_fixture_failures(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_results(&state.fixture_failures, "fixture failures")
+    }
+
+    pub fn write_flaky_tests(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_results(&state.flaky_tests, "flaky tests")
+    }
+
+    pub fn write_bench_regressions(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_results(&state.bench_regressions, "benchmark regressions")
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         self.write_plain(&format!("test {} ... ", name))?;
@@ -224,6 +240,7 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
                 self.write_plain(&format!(": {}", fmt_bench_samples(bs)))?;
             }
             TestResult::TrTimedFail => self.write_time_failed()?,
+            TestResult::TrFlaky(_) => self.write_flaky()?,
         }
 
         self.write_time(desc, exec_time)?;
@@ -241,12 +258,25 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
         ))
     }
 
+    fn write_fixture_failure(&mut self, desc: &TestDesc, _stdout: &[u8]) -> io::Result<()> {
+        self.write_plain(&format!("fixture {} ... ", desc.name))?;
+        self.write_failed()?;
+        self.write_plain("\n")
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_successes(state)?;
         }
-        let success = state.failed == 0;
+        if !state.flaky_tests.is_empty() {
+            self.write_flaky_tests(state)?;
+        }
+        let success = state.is_success();
         if !success {
+            if !state.fixture_failures.is_empty() {
+                self.write_fixture_failures(state)?;
+            }
+
             if !state.failures.is_empty() {
                 self.write_failures(state)?;
             }
@@ -254,6 +284,10 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
             if !state.time_failures.is_empty() {
                 self.write_time_failures(state)?;
             }
+
+            if !state.bench_regressions.is_empty() {
+                self.write_bench_regressions(state)?;
+            }
         }
 
         self.write_plain("\ntest result: ")?;
@@ -265,20 +299,32 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let passed = if state.flaky > 0 {
+            format!("{} passed ({} flaky)", state.passed + state.flaky, state.flaky)
+        } else {
+            format!("{} passed", state.passed)
+        };
+
+        let measured = if !state.bench_regressions.is_empty() {
+            format!("{} measured ({} regressed)", state.measured, state.bench_regressions.len())
+        } else {
+            format!("{} measured", state.measured)
+        };
+
         let s = if state.allowed_fail > 0 {
             format!(
-                ". {} passed; {} failed ({} allowed); {} ignored; {} measured; {} filtered out\n\n",
-                state.passed,
+                ". {}; {} failed ({} allowed); {} ignored; {}; {} filtered out\n\n",
+                passed,
                 state.failed + state.allowed_fail,
                 state.allowed_fail,
                 state.ignored,
-                state.measured,
+                measured,
                 state.filtered_out
             )
         } else {
             format!(
-                ". {} passed; {} failed; {} ignored; {} measured; {} filtered out\n\n",
-                state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+                ". {}; {} failed; {} ignored; {}; {} filtered out\n\n",
+                passed, state.failed, state.ignored, measured, state.filtered_out
             )
         };
 
@@ -60,6 +60,10 @@ impl<T: Write> TerseFormatter<T> {
         self.write_short_result("a", term::color::YELLOW)
     }
 
+    pub fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("f", term::color::YELLOW)
+    }
+
     pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -137,7 +141,7 @@ impl<T: Write> TerseFormatter<T> {
         self.write_plain("\nfailures:\n")?;
         let mut failures = Vec::new();
         let mut fail_out = String::new();
-        for &(ref f, ref stdout) in &state.failures {
+        for &(ref f, ref stdout) in state.fixture_failures.iter().chain(&state.failures) {
             failures.push(f.name.to_string());
             if !stdout.is_empty() {
                 fail_out.push_str(&format!("---- {} stdout ----\n", f.name));
@@ -159,6 +163,32 @@ impl<T: Write> TerseFormatter<T> {
         Ok(())
     }
 
+    pub fn write_flaky_tests(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_plain("\nflaky tests:\n")?;
+        let mut flaky_tests: Vec<_> = state.flaky_tests
+            .iter()
+            .map(|&(ref f, _)| f.name.to_string())
+            .collect();
+        flaky_tests.sort();
+        for name in &flaky_tests {
+            self.write_plain(&format!("    {}\n", name))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_bench_regressions(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_plain("\nbenchmark regressions:\n")?;
+        let mut regressions: Vec<_> = state.bench_regressions
+            .iter()
+            .map(|&(ref f, _)| f.name.to_string())
+            .collect();
+        regressions.sort();
+        for name in &regressions {
+            self.write_plain(&format!("    {}\n", name))?;
+        }
+        Ok(())
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         self.write_plain(&format!("test {} ... ", name))?;
@@ -201,6 +231,7 @@ impl<T: Write> OutputFormatter for TerseFormatter<T> {
                 | TestResult::TrTimedFail => self.write_failed(),
             TestResult::TrIgnored => self.write_ignored(),
             TestResult::TrAllowedFail => self.write_allowed_fail(),
+            TestResult::TrFlaky(_) => self.write_flaky(),
             TestResult::TrBench(ref bs) => {
                 if self.is_multithreaded {
                     self.write_test_name(desc)?;
@@ -218,13 +249,23 @@ impl<T: Write> OutputFormatter for TerseFormatter<T> {
         ))
     }
 
+    fn write_fixture_failure(&mut self, desc: &TestDesc, _stdout: &[u8]) -> io::Result<()> {
+        self.write_plain(&format!("\nfixture {} failed\n", desc.name))
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_outputs(state)?;
         }
-        let success = state.failed == 0;
+        if !state.flaky_tests.is_empty() {
+            self.write_flaky_tests(state)?;
+        }
+        let success = state.is_success();
         if !success {
             self.write_failures(state)?;
+            if !state.bench_regressions.is_empty() {
+                self.write_bench_regressions(state)?;
+            }
         }
 
         self.write_plain("\ntest result: ")?;
@@ -236,20 +277,32 @@ impl<T: Write> OutputFormatter for TerseFormatter<T> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let passed = if state.flaky > 0 {
+            format!("{} passed ({} flaky)", state.passed + state.flaky, state.flaky)
+        } else {
+            format!("{} passed", state.passed)
+        };
+
+        let measured = if !state.bench_regressions.is_empty() {
+            format!("{} measured ({} regressed)", state.measured, state.bench_regressions.len())
+        } else {
+            format!("{} measured", state.measured)
+        };
+
         let s = if state.allowed_fail > 0 {
             format!(
-                ". {} passed; {} failed ({} allowed); {} ignored; {} measured; {} filtered out\n\n",
-                state.passed,
+                ". {}; {} failed ({} allowed); {} ignored; {}; {} filtered out\n\n",
+                passed,
                 state.failed + state.allowed_fail,
                 state.allowed_fail,
                 state.ignored,
-                state.measured,
+                measured,
                 state.filtered_out
             )
         } else {
             format!(
-                ". {} passed; {} failed; {} ignored; {} measured; {} filtered out\n\n",
-                state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+                ". {}; {} failed; {} ignored; {}; {} filtered out\n\n",
+                passed, state.failed, state.ignored, measured, state.filtered_out
             )
         };
 
@@ -0,0 +1,72 @@
+//! Helper module to wait for a child process with a time limit.
+
+use std::io::{self, Read};
+use std::process::{Child, Output};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Longest interval between two checks of whether the child has exited.
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Waits for the child to exit and collects its output, like
+/// `Child::wait_with_output`, but kills the child if it is still running
+/// once `timeout` has elapsed.
+///
+/// The second element of the returned tuple is `true` if the child has been
+/// killed. The output it has produced until then is returned anyway.
+pub fn wait_with_output_timeout(
+    mut child: Child,
+    timeout: Duration,
+) -> io::Result<(Output, bool)> {
+    // The pipes are drained on separate threads, so that a child writing a lot
+    // of output doesn't block on a full pipe while we're waiting for it.
+    fn read_pipe<R: Read + Send + 'static>(
+        pipe: Option<R>,
+    ) -> Option<JoinHandle<io::Result<Vec<u8>>>> {
+        pipe.map(|mut pipe| {
+            thread::spawn(move || {
+                let mut buf = Vec::new();
+                pipe.read_to_end(&mut buf)?;
+                Ok(buf)
+            })
+        })
+    }
+
+    fn join_pipe(reader: Option<JoinHandle<io::Result<Vec<u8>>>>) -> io::Result<Vec<u8>> {
+        match reader {
+            Some(reader) => reader.join().unwrap_or_else(|_| Ok(Vec::new())),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let mut poll_interval = Duration::from_millis(1);
+    let mut killed = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            // The child may have exited in the meantime, in which case killing
+            // it fails, but its status can still be collected.
+            let _ = child.kill();
+            killed = true;
+            break child.wait()?;
+        }
+
+        thread::sleep(poll_interval.min(deadline - now));
+        poll_interval = (poll_interval * 2).min(MAX_POLL_INTERVAL);
+    };
+
+    let output = Output {
+        status,
+        stdout: join_pipe(stdout)?,
+        stderr: join_pipe(stderr)?,
+    };
+    Ok((output, killed))
+}
@@ -6,3 +6,4 @@ pub mod isatty;
 pub mod metrics;
 pub mod sink;
 pub mod exit_code;
+pub mod child;
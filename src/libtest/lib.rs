@@ -21,6 +21,7 @@
 #![unstable(feature = "test", issue = "50297")]
 #![doc(html_root_url = "https://doc.rust-lang.org/nightly/", test(attr(deny(warnings))))]
 #![feature(asm)]
+#![feature(available_concurrency)]
 #![cfg_attr(any(unix, target_os = "cloudabi"), feature(libc))]
 #![feature(rustc_private)]
 #![feature(nll)]
@@ -34,7 +35,7 @@
 pub use self::ColorConfig::*;
 pub use self::types::*;
 pub use self::types::TestName::*;
-pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic};
+pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, Shard, ShouldPanic};
 pub use self::bench::{Bencher, black_box};
 pub use self::console::run_tests_console;
 pub use cli::TestOpts;
@@ -45,12 +46,13 @@ pub mod test {
         bench::Bencher,
         cli::{parse_opts, TestOpts},
         helpers::metrics::{Metric, MetricMap},
-        options::{ShouldPanic, Options, RunIgnored, RunStrategy},
+        options::{ShouldPanic, Options, RunIgnored, RunStrategy, Shard},
         test_result::{TestResult, TrFailed, TrFailedMsg, TrIgnored, TrOk},
         time::{TestTimeOptions, TestExecTime},
         types::{
-            DynTestFn, DynTestName, StaticBenchFn, StaticTestFn, StaticTestName,
-            TestDesc, TestDescAndFn, TestName, TestType,
+            DynRetryableTestFn, DynTestFn, DynTestName, FixtureKind, StaticBenchFn,
+            StaticFixtureFn, StaticTestFn, StaticTestName, TestDesc, TestDescAndFn, TestName,
+            TestType,
         },
         assert_test_result, filter_tests, run_test, test_main, test_main_static,
     };
@@ -62,8 +64,8 @@ use std::{
     io::prelude::Write,
     panic::{self, catch_unwind, AssertUnwindSafe, PanicInfo},
     process,
-    process::{Command, Termination},
-    sync::mpsc::{channel, Sender},
+    process::{Command, Stdio, Termination},
+    sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender},
     sync::{Arc, Mutex},
     thread,
     time::{Duration, Instant},
@@ -71,14 +73,17 @@ use std::{
 
 pub mod stats;
 pub mod bench;
+mod baseline;
 mod formatters;
 mod cli;
 mod console;
 mod event;
+mod fixture;
 mod helpers;
 mod time;
 mod types;
 mod options;
+mod retry;
 mod test_result;
 
 #[cfg(test)]
@@ -88,9 +93,12 @@ use test_result::*;
 use time::TestExecTime;
 use options::{RunStrategy, Concurrent};
 use event::{CompletedTest, TestEvent};
+use fixture::Fixtures;
+use retry::RetryTracker;
 use helpers::sink::Sink;
 use helpers::concurrency::get_concurrency;
 use helpers::exit_code::get_exit_code;
+use helpers::child::wait_with_output_timeout;
 
 // Process exit code to be used to indicate test failures.
 const ERROR_EXIT_CODE: i32 = 101;
@@ -117,6 +125,11 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Opt
             process::exit(ERROR_EXIT_CODE);
         }
     } else {
+        // Listing the tests doesn't run any fixtures.
+        if let Err(msg) = fixture::check_supported(&opts, &tests) {
+            eprintln!("error: {}", msg);
+            process::exit(ERROR_EXIT_CODE);
+        }
         match console::run_tests_console(&opts, tests) {
             Ok(true) => {}
             Ok(false) => process::exit(ERROR_EXIT_CODE),
@@ -184,6 +197,10 @@ fn make_owned_test(test: &&TestDescAndFn) -> TestDescAndFn {
             testfn: StaticBenchFn(f),
             desc: test.desc.clone(),
         },
+        StaticFixtureFn(kind, f) => TestDescAndFn {
+            testfn: StaticFixtureFn(kind, f),
+            desc: test.desc.clone(),
+        },
         _ => panic!("non-static tests passed to test::test_main_static"),
     }
 }
@@ -209,13 +226,14 @@ pub fn run_tests<F>(
 where
     F: FnMut(TestEvent) -> io::Result<()>,
 {
-    use std::collections::{self, HashMap};
+    use std::collections::{self, HashMap, HashSet};
     use std::hash::BuildHasherDefault;
-    use std::sync::mpsc::RecvTimeoutError;
     // Use a deterministic hasher
     type TestMap =
         HashMap<TestDesc, Instant, BuildHasherDefault<collections::hash_map::DefaultHasher>>;
 
+    let (mut fixtures, tests) = Fixtures::split(tests, opts.nocapture);
+
     let tests_len = tests.len();
 
     let mut filtered_tests = filter_tests(opts, tests);
@@ -243,12 +261,34 @@ where
 
     let (filtered_tests, filtered_benchs): (Vec<_>, _) =
         filtered_tests.into_iter().partition(|e| match e.testfn {
-            StaticTestFn(_) | DynTestFn(_) => true,
+            StaticTestFn(_) | DynTestFn(_) | DynRetryableTestFn(_) => true,
             _ => false,
         });
 
+    // Only the tests which are actually going to be run need the fixtures.
+    if opts.run_tests {
+        for test in filtered_tests.iter().filter(|test| !test.desc.ignore) {
+            fixtures.register_test(&test.desc);
+        }
+    }
+    if opts.bench_benchmarks {
+        for bench in filtered_benchs.iter().filter(|bench| !bench.desc.ignore) {
+            fixtures.register_test(&bench.desc);
+        }
+    }
+
     let concurrency = opts.test_threads.unwrap_or_else(get_concurrency);
 
+    let mut retry_tracker = RetryTracker::new(opts.retries, &filtered_tests);
+    let unretryable = retry_tracker.unretryable_tests();
+    if unretryable > 0 {
+        eprintln!(
+            "warning: {} of the tests can't be retried since their test function can only be \
+             called once; they are run a single time despite `--retries`",
+            unretryable
+        );
+    }
+
     let mut remaining = filtered_tests;
     remaining.reverse();
     let mut pending = 0;
@@ -262,6 +302,16 @@ where
 
     let mut running_tests: TestMap = HashMap::default();
 
+    // Tests run in-process can't be stopped, so the ones still running after the
+    // timeout are reported as failed and abandoned. Subprocesses are killed by
+    // `spawn_test_subprocess` instead.
+    let in_process_timeout = match run_strategy {
+        RunStrategy::InProcess => opts.test_timeout,
+        RunStrategy::SpawnPrimary => None,
+    };
+    let mut hung_deadlines: TestMap = HashMap::default();
+    let mut abandoned_tests = HashSet::new();
+
     fn get_timed_out_tests(running_tests: &mut TestMap) -> Vec<TestDesc> {
         let now = Instant::now();
         let timed_out = running_tests
@@ -294,42 +344,102 @@ where
     if concurrency == 1 {
         while !remaining.is_empty() {
             let test = remaining.pop().unwrap();
-            let event = TestEvent::TeWait(test.desc.clone());
-            notify_about_test_event(event)?;
-            run_test(opts, !opts.run_tests, test, run_strategy, tx.clone(), Concurrent::No);
-            let completed_test = rx.recv().unwrap();
+            let setup_failure = fixtures.start_test(&test.desc, &mut notify_about_test_event)?;
+            if !retry_tracker.is_retry(&test.desc) {
+                let event = TestEvent::TeWait(test.desc.clone());
+                notify_about_test_event(event)?;
+            }
+            let completed_test = match (setup_failure, in_process_timeout) {
+                (Some(completed_test), _) => completed_test,
+                (None, Some(timeout)) => {
+                    // The test is run on its own thread, so that it can be abandoned if it
+                    // hangs. Abandoned tests aren't retried.
+                    let desc = test.desc.clone();
+                    run_test(
+                        opts, !opts.run_tests, test, run_strategy, tx.clone(), Concurrent::Yes,
+                    );
+                    match recv_test_result(&rx, &desc, timeout) {
+                        Some(completed_test) => completed_test,
+                        None => {
+                            let event = TestEvent::TeResult(abandoned_test(desc.clone(), timeout));
+                            notify_about_test_event(event)?;
+                            fixtures.complete_test(&desc, &mut notify_about_test_event)?;
+                            continue;
+                        }
+                    }
+                }
+                (None, None) => {
+                    run_test(opts, !opts.run_tests, test, run_strategy, tx.clone(), Concurrent::No);
+                    rx.recv().unwrap()
+                }
+            };
 
-            let event = TestEvent::TeResult(completed_test);
-            notify_about_test_event(event)?;
+            match retry_tracker.check(completed_test) {
+                Ok(completed_test) => {
+                    let desc = completed_test.desc.clone();
+                    let event = TestEvent::TeResult(completed_test);
+                    notify_about_test_event(event)?;
+                    fixtures.complete_test(&desc, &mut notify_about_test_event)?;
+                }
+                Err(test) => remaining.push(test),
+            }
         }
     } else {
         while pending > 0 || !remaining.is_empty() {
             while pending < concurrency && !remaining.is_empty() {
                 let test = remaining.pop().unwrap();
+                let setup_failure = fixtures.start_test(&test.desc, &mut notify_about_test_event)?;
+                if let Some(completed_test) = setup_failure {
+                    // The test can't be run, so its result is reported right away.
+                    let event = TestEvent::TeWait(test.desc.clone());
+                    notify_about_test_event(event)?;
+                    let event = TestEvent::TeResult(completed_test);
+                    notify_about_test_event(event)?;
+                    fixtures.complete_test(&test.desc, &mut notify_about_test_event)?;
+                    continue;
+                }
+
                 let timeout = time::get_default_test_timeout();
                 running_tests.insert(test.desc.clone(), timeout);
+                if let Some(timeout) = in_process_timeout {
+                    hung_deadlines.insert(test.desc.clone(), Instant::now() + timeout);
+                }
 
-                let event = TestEvent::TeWait(test.desc.clone());
-                notify_about_test_event(event)?; //here no pad
+                if !retry_tracker.is_retry(&test.desc) {
+                    let event = TestEvent::TeWait(test.desc.clone());
+                    notify_about_test_event(event)?; //here no pad
+                }
                 run_test(opts, !opts.run_tests, test, run_strategy, tx.clone(), Concurrent::Yes);
                 pending += 1;
             }
 
+            if pending == 0 {
+                // All the remaining tests have been skipped because of failed fixtures.
+                continue;
+            }
+
             let mut res;
+            let mut hung_tests = Vec::new();
             loop {
-                if let Some(timeout) = calc_timeout(&running_tests) {
+                let warn_timeout = calc_timeout(&running_tests);
+                let next_timeout = match (warn_timeout, calc_timeout(&hung_deadlines)) {
+                    (Some(warn), Some(hung)) => Some(warn.min(hung)),
+                    (warn, hung) => warn.or(hung),
+                };
+                if let Some(timeout) = next_timeout {
                     res = rx.recv_timeout(timeout);
                     for test in get_timed_out_tests(&mut running_tests) {
                         let event = TestEvent::TeTimeout(test);
                         notify_about_test_event(event)?;
                     }
+                    hung_tests = get_timed_out_tests(&mut hung_deadlines);
 
                     match res {
-                        Err(RecvTimeoutError::Timeout) => {
+                        Err(RecvTimeoutError::Timeout) if hung_tests.is_empty() => {
                             // Result is not yet ready, continue waiting.
                         }
                         _ => {
-                            // We've got a result, stop the loop.
+                            // We've got a result or a hung test, stop the loop.
                             break;
                         }
                     }
@@ -339,30 +449,95 @@ where
                 }
             }
 
-            let completed_test = res.unwrap();
-            running_tests.remove(&completed_test.desc);
+            if let Some(timeout) = in_process_timeout {
+                for desc in hung_tests {
+                    // Abandoned tests aren't retried, and their results are discarded
+                    // if they ever complete.
+                    running_tests.remove(&desc);
+                    pending -= 1;
+                    let event = TestEvent::TeResult(abandoned_test(desc.clone(), timeout));
+                    notify_about_test_event(event)?;
+                    fixtures.complete_test(&desc, &mut notify_about_test_event)?;
+                    abandoned_tests.insert(desc);
+                }
+            }
 
-            let event = TestEvent::TeResult(completed_test);
-            notify_about_test_event(event)?;
+            let completed_test = match res {
+                Err(RecvTimeoutError::Timeout) => continue,
+                res => res.unwrap(),
+            };
+            if abandoned_tests.contains(&completed_test.desc) {
+                continue;
+            }
+            running_tests.remove(&completed_test.desc);
+            hung_deadlines.remove(&completed_test.desc);
             pending -= 1;
+
+            match retry_tracker.check(completed_test) {
+                Ok(completed_test) => {
+                    let desc = completed_test.desc.clone();
+                    let event = TestEvent::TeResult(completed_test);
+                    notify_about_test_event(event)?;
+                    fixtures.complete_test(&desc, &mut notify_about_test_event)?;
+                }
+                Err(test) => remaining.push(test),
+            }
         }
     }
 
     if opts.bench_benchmarks {
         // All benchmarks run at the end, in serial.
         for b in filtered_benchs {
+            let setup_failure = fixtures.start_test(&b.desc, &mut notify_about_test_event)?;
             let event = TestEvent::TeWait(b.desc.clone());
             notify_about_test_event(event)?;
-            run_test(opts, false, b, run_strategy, tx.clone(), Concurrent::No);
-            let completed_test = rx.recv().unwrap();
+            let completed_test = match setup_failure {
+                Some(completed_test) => completed_test,
+                None => {
+                    run_test(opts, false, b, run_strategy, tx.clone(), Concurrent::No);
+                    rx.recv().unwrap()
+                }
+            };
 
+            let desc = completed_test.desc.clone();
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
+            fixtures.complete_test(&desc, &mut notify_about_test_event)?;
         }
     }
     Ok(())
 }
 
+/// Waits for the result of the given test, which is run in-process on its own
+/// thread. Returns `None` if the test hasn't completed within `timeout`.
+fn recv_test_result(
+    rx: &Receiver<CompletedTest>,
+    desc: &TestDesc,
+    timeout: Duration,
+) -> Option<CompletedTest> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let now = Instant::now();
+        let remaining = if deadline > now { deadline - now } else { Duration::new(0, 0) };
+        match rx.recv_timeout(remaining) {
+            Ok(completed_test) if completed_test.desc == *desc => return Some(completed_test),
+            Ok(_) => {
+                // A late result of a previously abandoned test, which is discarded.
+            }
+            Err(RecvTimeoutError::Timeout) => return None,
+            Err(RecvTimeoutError::Disconnected) => panic!("test result channel disconnected"),
+        }
+    }
+}
+
+/// Result reported for a test run in-process which hasn't completed within
+/// `timeout`. Its thread can't be killed, so it's left running until the
+/// process exits.
+fn abandoned_test(desc: TestDesc, timeout: Duration) -> CompletedTest {
+    let msg = format!("test did not complete within {}s", timeout.as_secs());
+    CompletedTest::new(desc, TrFailedMsg(msg), None, Vec::new())
+}
+
 pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
     let mut filtered = tests;
     let matches_filter = |test: &TestDescAndFn, filter: &str| {
@@ -403,6 +578,11 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         RunIgnored::No => {}
     }
 
+    // Only keep the tests assigned to the requested shard
+    if let Some(shard) = opts.shard {
+        filtered.retain(|test| shard.contains(test.desc.name.as_slice()));
+    }
+
     // Sort the tests alphabetically
     filtered.sort_by(|t1, t2| t1.desc.name.as_slice().cmp(t2.desc.name.as_slice()));
 
@@ -418,7 +598,7 @@ pub fn convert_benchmarks_to_tests(tests: Vec<TestDescAndFn>) -> Vec<TestDescAnd
                 DynBenchFn(bench) => DynTestFn(Box::new(move || {
                     bench::run_once(|b| __rust_begin_short_backtrace(|| bench.run(b)))
                 })),
-                StaticBenchFn(benchfn) => DynTestFn(Box::new(move || {
+                StaticBenchFn(benchfn) => DynRetryableTestFn(Arc::new(move || {
                     bench::run_once(|b| __rust_begin_short_backtrace(|| benchfn(b)))
                 })),
                 f => f,
@@ -456,6 +636,7 @@ pub fn run_test(
         pub nocapture: bool,
         pub concurrency: Concurrent,
         pub time: Option<time::TestTimeOptions>,
+        pub timeout: Option<Duration>,
     }
 
     fn run_test_inner(
@@ -479,7 +660,13 @@ pub fn run_test(
                         opts.time
                     ),
                 RunStrategy::SpawnPrimary =>
-                    spawn_test_subprocess(desc, opts.time.is_some(), monitor_ch, opts.time),
+                    spawn_test_subprocess(
+                        desc,
+                        opts.time.is_some(),
+                        monitor_ch,
+                        opts.time,
+                        opts.timeout,
+                    ),
             }
         };
 
@@ -499,7 +686,8 @@ pub fn run_test(
         strategy,
         nocapture: opts.nocapture,
         concurrency,
-        time: opts.time_options
+        time: opts.time_options,
+        timeout: opts.test_timeout,
     };
 
     match testfn {
@@ -527,7 +715,19 @@ pub fn run_test(
                 test_run_opts,
             );
         }
-        StaticTestFn(f) => run_test_inner(
+        DynRetryableTestFn(f) => {
+            match strategy {
+                RunStrategy::InProcess => (),
+                _ => panic!("Cannot run dynamic test fn out-of-process"),
+            };
+            run_test_inner(
+                desc,
+                monitor_ch,
+                Box::new(move || __rust_begin_short_backtrace(|| f())),
+                test_run_opts,
+            );
+        }
+        StaticTestFn(f) | StaticFixtureFn(_, f) => run_test_inner(
             desc,
             monitor_ch,
             Box::new(move || __rust_begin_short_backtrace(f)),
@@ -592,6 +792,7 @@ fn spawn_test_subprocess(
     report_time: bool,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
+    timeout: Option<Duration>,
 ) {
     let (result, test_output, exec_time) = (|| {
         let args = env::args().collect::<Vec<_>>();
@@ -602,15 +803,24 @@ fn spawn_test_subprocess(
         } else {
             None
         };
-        let output = match Command::new(current_exe)
-            .env(SECONDARY_TEST_INVOKER_VAR, desc.name.as_slice())
-            .output() {
-                Ok(out) => out,
-                Err(e) => {
-                    let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
-                    return (TrFailed, err.into_bytes(), None);
-                }
-            };
+        let mut command = Command::new(current_exe);
+        command.env(SECONDARY_TEST_INVOKER_VAR, desc.name.as_slice());
+        let output = match timeout {
+            Some(timeout) => command
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .and_then(|child| wait_with_output_timeout(child, timeout)),
+            None => command.output().map(|output| (output, false)),
+        };
+        let (output, killed) = match output {
+            Ok(out) => out,
+            Err(e) => {
+                let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
+                return (TrFailed, err.into_bytes(), None);
+            }
+        };
         let exec_time = start.map(|start| {
             let duration = start.elapsed();
             TestExecTime(duration)
@@ -621,6 +831,11 @@ fn spawn_test_subprocess(
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
+        if let (true, Some(timeout)) = (killed, timeout) {
+            let msg = format!("test did not complete within {}s and was killed", timeout.as_secs());
+            return (TrFailedMsg(msg), test_output, exec_time);
+        }
+
         let result = match (|| -> Result<TestResult, String> {
             let exit_code = get_exit_code(status)?;
             Ok(get_result_from_exit_code(&desc, exit_code, &time_opts, &exec_time))
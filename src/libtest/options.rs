@@ -39,6 +39,8 @@ pub enum OutputFormat {
     Terse,
     /// JSON output
     Json,
+    /// JUnit XML output
+    Junit,
 }
 
 /// Whether ignored test should be runned or not
@@ -50,6 +52,35 @@ pub enum RunIgnored {
     Only,
 }
 
+/// Subset of the tests to be run, used to split a single test binary between
+/// several processes or machines.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Shard {
+    /// Zero-based index of the shard to run.
+    pub index: usize,
+    /// Total number of shards the tests are split into.
+    pub count: usize,
+}
+
+impl Shard {
+    /// Checks whether the test with the provided name belongs to this shard.
+    ///
+    /// Tests are assigned to shards by hashing their names with FNV-1a, which
+    /// (unlike the hashers in `std`) is guaranteed to be stable, so the same
+    /// test always lands in the same shard regardless of the platform or the
+    /// toolchain version the binary was built with.
+    pub fn contains(&self, test_name: &str) -> bool {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let hash = test_name.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        });
+
+        hash % self.count as u64 == self.index as u64
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum RunStrategy {
     /// Runs the test in the current process, and sends the result back over the
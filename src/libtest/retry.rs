@@ -0,0 +1,133 @@
+//! Module providing the retrying of failed tests (see the `--retries` option).
+
+use std::collections::HashMap;
+use std::io::prelude::Write;
+use std::sync::Arc;
+
+use super::{
+    event::CompletedTest,
+    test_result::TestResult,
+    types::{TestDesc, TestDescAndFn, TestFn},
+};
+
+/// A test function which can be called again.
+#[derive(Clone)]
+enum RetryableFn {
+    Static(fn()),
+    Dynamic(Arc<dyn Fn() + Send + Sync>),
+}
+
+impl RetryableFn {
+    fn into_test_fn(self) -> TestFn {
+        match self {
+            RetryableFn::Static(f) => TestFn::StaticTestFn(f),
+            RetryableFn::Dynamic(f) => TestFn::DynRetryableTestFn(f),
+        }
+    }
+}
+
+/// Keeps track of the failed tests which are going to be run again.
+pub struct RetryTracker {
+    max_retries: usize,
+    /// Functions of the tests that can be retried. `DynTestFn` tests are not
+    /// retried, since their functions can be called just once.
+    test_fns: HashMap<TestDesc, RetryableFn>,
+    /// Number of tests which would be retried if their functions could be
+    /// called again.
+    unretryable: usize,
+    /// Output of the failed attempts of every test being retried.
+    failed_attempts: HashMap<TestDesc, Vec<Vec<u8>>>,
+}
+
+impl RetryTracker {
+    pub fn new(max_retries: usize, tests: &[TestDescAndFn]) -> Self {
+        let mut test_fns = HashMap::new();
+        let mut unretryable = 0;
+        if max_retries > 0 {
+            for test in tests {
+                let f = match test.testfn {
+                    TestFn::StaticTestFn(f) => RetryableFn::Static(f),
+                    TestFn::DynRetryableTestFn(ref f) => RetryableFn::Dynamic(f.clone()),
+                    TestFn::DynTestFn(_) => {
+                        unretryable += 1;
+                        continue;
+                    }
+                    _ => continue,
+                };
+                test_fns.insert(test.desc.clone(), f);
+            }
+        }
+
+        Self {
+            max_retries,
+            test_fns,
+            unretryable,
+            failed_attempts: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of tests which won't be retried despite `--retries`,
+    /// because their test function can be called only once.
+    pub fn unretryable_tests(&self) -> usize {
+        self.unretryable
+    }
+
+    /// Checks whether the test is about to be run again after a failure.
+    pub fn is_retry(&self, desc: &TestDesc) -> bool {
+        self.failed_attempts.contains_key(desc)
+    }
+
+    /// Decides what to do with the test which has just completed.
+    ///
+    /// Returns `Err` with the test to be scheduled again if it has failed and
+    /// there are retries left. Otherwise returns the result to be reported,
+    /// which is `TrFlaky` for a test which passed only after being retried.
+    /// In the latter case, the output of the failed attempts is prepended to
+    /// the output of the test.
+    pub fn check(
+        &mut self,
+        mut completed_test: CompletedTest,
+    ) -> Result<CompletedTest, TestDescAndFn> {
+        let failed = match completed_test.result {
+            TestResult::TrFailed | TestResult::TrFailedMsg(_) | TestResult::TrTimedFail => true,
+            _ => false,
+        };
+        let desc = &completed_test.desc;
+        let retries = self.failed_attempts.get(desc).map_or(0, |attempts| attempts.len());
+
+        if failed && retries < self.max_retries {
+            if let Some(f) = self.test_fns.get(desc) {
+                let testfn = f.clone().into_test_fn();
+                let mut output = attempt_header(desc, retries + 1);
+                output.extend_from_slice(&completed_test.stdout);
+                if let TestResult::TrFailedMsg(ref msg) = completed_test.result {
+                    write!(output, "note: {}\n", msg).unwrap();
+                }
+                self.failed_attempts.entry(desc.clone()).or_default().push(output);
+
+                return Err(TestDescAndFn {
+                    desc: completed_test.desc,
+                    testfn,
+                });
+            }
+        }
+
+        if let Some(attempts) = self.failed_attempts.remove(desc) {
+            let retries = attempts.len();
+            let mut stdout = attempts.concat();
+            stdout.extend(attempt_header(desc, retries + 1));
+            stdout.extend_from_slice(&completed_test.stdout);
+            completed_test.stdout = stdout;
+
+            if completed_test.result == TestResult::TrOk {
+                completed_test.result = TestResult::TrFlaky(retries);
+            }
+        }
+
+        Ok(completed_test)
+    }
+}
+
+fn attempt_header(desc: &TestDesc, attempt: usize) -> Vec<u8> {
+    format!("---- {} attempt {} ----\n", desc.name, attempt).into_bytes()
+}
@@ -2,6 +2,7 @@
 #![allow(deprecated)] // Float
 
 use std::cmp::Ordering::{self, Equal, Greater, Less};
+use std::f64;
 use std::mem;
 
 #[cfg(test)]
@@ -317,3 +318,32 @@ pub fn winsorize(samples: &mut [f64], pct: f64) {
         }
     }
 }
+
+/// Computes the two-sided p-value of Welch's t-test for the hypothesis that two
+/// sample sets, given by their mean, variance and number of samples, come from
+/// distributions with equal means.
+///
+/// The t distribution is approximated by the standard normal distribution, which
+/// is accurate as long as both sample sets are reasonably large, as is the case
+/// for the samples collected by benchmarks.
+///
+/// See: <https://en.wikipedia.org/wiki/Welch%27s_t-test>
+pub fn welch_t_test(mean1: f64, var1: f64, n1: usize, mean2: f64, var2: f64, n2: usize) -> f64 {
+    let std_err = (var1 / n1 as f64 + var2 / n2 as f64).sqrt();
+    if std_err == 0.0 {
+        // Without any variance, any difference between the means is significant.
+        return if mean1 == mean2 { 1.0 } else { 0.0 };
+    }
+    let t = (mean1 - mean2) / std_err;
+    erfc(t.abs() / f64::consts::SQRT_2)
+}
+
+// Helper function: the complementary error function of a non-negative `x`, with an absolute error
+// below 1.5e-7 (Abramowitz and Stegun, formula 7.1.26).
+fn erfc(x: f64) -> f64 {
+    assert!(x >= 0.0);
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592
+        + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    poly * (-x * x).exp()
+}
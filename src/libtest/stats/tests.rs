@@ -577,6 +577,18 @@ fn test_sum_f64_between_ints_that_sum_to_0() {
     assert_eq!([1e30f64, 1.2f64, -1e30f64].sum(), 1.2);
 }
 
+#[test]
+fn test_welch_t_test() {
+    // Identical sample sets.
+    assert_approx_eq!(welch_t_test(10.0, 4.0, 50, 10.0, 4.0, 50), 1.0);
+    // A difference of 1.96 standard errors is significant at the 5% level.
+    assert_approx_eq!(welch_t_test(1.96, 0.5, 1, 0.0, 0.5, 1), 0.0499958);
+    assert_approx_eq!(welch_t_test(0.0, 0.5, 1, 1.96, 0.5, 1), 0.0499958);
+    // Constant sample sets.
+    assert_eq!(welch_t_test(1.0, 0.0, 50, 1.0, 0.0, 50), 1.0);
+    assert_eq!(welch_t_test(1.0, 0.0, 50, 2.0, 0.0, 50), 0.0);
+}
+
 #[bench]
 pub fn sum_three_items(b: &mut Bencher) {
     b.iter(|| {
@@ -22,6 +22,8 @@ pub enum TestResult {
     TrAllowedFail,
     TrBench(BenchSamples),
     TrTimedFail,
+    /// The test passed, but only after being retried the given number of times.
+    TrFlaky(usize),
 }
 
 unsafe impl Send for TestResult {}
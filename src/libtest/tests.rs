@@ -1,14 +1,15 @@
 use super::*;
 
 use crate::{
+    baseline::{Baseline, BaselineComparison, BenchChange, BenchStats},
     bench::Bencher,
     console::OutputLocation,
     options::OutputFormat,
     time::{TimeThreshold, TestTimeOptions},
-    formatters::PrettyFormatter,
+    formatters::{JunitFormatter, OutputFormatter, PrettyFormatter},
     test::{
         filter_tests, parse_opts, run_test, DynTestFn, DynTestName, MetricMap,
-        RunIgnored, RunStrategy, ShouldPanic, StaticTestName, TestDesc,
+        RunIgnored, RunStrategy, Shard, ShouldPanic, StaticTestName, TestDesc,
         TestDescAndFn, TestOpts, TrIgnored, TrOk,
         // FIXME (introduced by #65251)
         // ShouldPanic, StaticTestName, TestDesc, TestDescAndFn, TestOpts, TestTimeOptions,
@@ -16,6 +17,7 @@ use crate::{
     },
 };
 use std::any::TypeId;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::channel;
 use std::time::Duration;
 
@@ -36,6 +38,11 @@ impl TestOpts {
             format: OutputFormat::Pretty,
             test_threads: None,
             skip: vec![],
+            shard: None,
+            retries: 0,
+            test_timeout: None,
+            save_baseline: None,
+            baseline: None,
             time_options: None,
             options: Options::new(),
         }
@@ -230,6 +237,296 @@ fn test_should_panic_but_succeeds() {
     assert_eq!(result, TrFailedMsg("test did not panic as expected".to_string()));
 }
 
+fn retryable_test(testfn: TestFn) -> TestDescAndFn {
+    TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName("whatever"),
+            ignore: false,
+            should_panic: ShouldPanic::No,
+            allow_fail: false,
+            test_type: TestType::Unknown,
+        },
+        testfn,
+    }
+}
+
+fn retried_test_results(retries: usize, testfn: TestFn) -> Vec<TestResult> {
+    let test = retryable_test(testfn);
+    let test_opts = TestOpts {
+        run_tests: true,
+        test_threads: Some(1),
+        retries,
+        ..TestOpts::new()
+    };
+
+    let mut results = Vec::new();
+    run_tests(&test_opts, vec![test], |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            results.push(completed_test.result);
+        }
+        Ok(())
+    }).unwrap();
+    results
+}
+
+// FIXME: Re-enable emscripten once it can catch panics again (introduced by #65251)
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_passing_after_retry_is_flaky() {
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    fn f() {
+        if ATTEMPTS.fetch_add(1, Ordering::SeqCst) < 2 {
+            panic!();
+        }
+    }
+
+    assert_eq!(retried_test_results(2, StaticTestFn(f)), vec![TestResult::TrFlaky(2)]);
+}
+
+// FIXME: Re-enable emscripten once it can catch panics again (introduced by #65251)
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_dynamic_test_is_retried() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let f = move || {
+        if counter.fetch_add(1, Ordering::SeqCst) < 1 {
+            panic!();
+        }
+    };
+
+    let results = retried_test_results(2, DynRetryableTestFn(Arc::new(f)));
+    assert_eq!(results, vec![TestResult::TrFlaky(1)]);
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_single_use_dynamic_test_is_not_retryable() {
+    let tests = vec![
+        retryable_test(DynTestFn(Box::new(move || {}))),
+        retryable_test(DynRetryableTestFn(Arc::new(move || {}))),
+        retryable_test(StaticTestFn(|| {})),
+    ];
+    assert_eq!(RetryTracker::new(1, &tests).unretryable_tests(), 1);
+    assert_eq!(RetryTracker::new(0, &tests).unretryable_tests(), 0);
+}
+
+// FIXME: Re-enable emscripten once it can catch panics again (introduced by #65251)
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_failing_after_retries_is_failed() {
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    fn f() {
+        ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+        panic!();
+    }
+
+    assert_eq!(retried_test_results(3, StaticTestFn(f)), vec![TestResult::TrFailed]);
+    assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 4);
+}
+
+fn timed_out_test_results(test_threads: usize) -> Vec<(&'static str, TestResult)> {
+    fn hangs() {
+        loop {
+            thread::park();
+        }
+    }
+    fn passes() {}
+
+    let tests = vec![("hangs", hangs as fn()), ("passes", passes as fn())]
+        .into_iter()
+        .map(|(name, f)| TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName(name),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                allow_fail: false,
+                test_type: TestType::Unknown,
+            },
+            testfn: StaticTestFn(f),
+        })
+        .collect();
+    let test_opts = TestOpts {
+        run_tests: true,
+        test_threads: Some(test_threads),
+        test_timeout: Some(Duration::from_secs(1)),
+        ..TestOpts::new()
+    };
+
+    let mut results = Vec::new();
+    run_tests(&test_opts, tests, |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            let name = match completed_test.desc.name {
+                StaticTestName(name) => name,
+                _ => unreachable!(),
+            };
+            results.push((name, completed_test.result));
+        }
+        Ok(())
+    }).unwrap();
+    results.sort_by_key(|&(name, _)| name);
+    results
+}
+
+// Emscripten can't run tests on separate threads, so a hung test hangs the run.
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_hung_test_is_abandoned() {
+    let expected = vec![
+        ("hangs", TrFailedMsg("test did not complete within 1s".to_string())),
+        ("passes", TrOk),
+    ];
+    assert_eq!(timed_out_test_results(1), expected);
+    assert_eq!(timed_out_test_results(2), expected);
+}
+
+fn fixture_test_events(tests: Vec<(&'static str, TestFn)>) -> Vec<TestEvent> {
+    let tests = tests
+        .into_iter()
+        .map(|(name, testfn)| TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName(name),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                allow_fail: false,
+                test_type: TestType::Unknown,
+            },
+            testfn,
+        })
+        .collect();
+    let test_opts = TestOpts {
+        run_tests: true,
+        test_threads: Some(1),
+        ..TestOpts::new()
+    };
+
+    let mut events = Vec::new();
+    run_tests(&test_opts, tests, |event| {
+        match event {
+            TestEvent::TeResult(..) | TestEvent::TeFixtureFailed(..) => events.push(event),
+            _ => {}
+        }
+        Ok(())
+    }).unwrap();
+    events
+}
+
+#[test]
+fn test_fixtures_run_around_tests() {
+    static SETUPS: AtomicUsize = AtomicUsize::new(0);
+    static TESTS: AtomicUsize = AtomicUsize::new(0);
+    static TEARDOWNS: AtomicUsize = AtomicUsize::new(0);
+
+    fn setup() {
+        SETUPS.fetch_add(1, Ordering::SeqCst);
+    }
+    fn teardown() {
+        assert_eq!(TESTS.load(Ordering::SeqCst), 2);
+        TEARDOWNS.fetch_add(1, Ordering::SeqCst);
+    }
+    fn test() {
+        assert_eq!(SETUPS.load(Ordering::SeqCst), 1);
+        assert_eq!(TEARDOWNS.load(Ordering::SeqCst), 0);
+        TESTS.fetch_add(1, Ordering::SeqCst);
+    }
+    fn unrelated_test() {}
+
+    let events = fixture_test_events(vec![
+        ("m::teardown", StaticFixtureFn(FixtureKind::Teardown, teardown)),
+        ("m::a", StaticTestFn(test)),
+        ("m::setup", StaticFixtureFn(FixtureKind::Setup, setup)),
+        ("m::nested::b", StaticTestFn(test)),
+        ("other", StaticTestFn(unrelated_test)),
+    ]);
+
+    let results: Vec<_> = events
+        .into_iter()
+        .map(|event| match event {
+            TestEvent::TeResult(completed_test) => completed_test.result,
+            _ => panic!("unexpected fixture failure"),
+        })
+        .collect();
+    assert_eq!(results, vec![TrOk, TrOk, TrOk]);
+    assert_eq!(SETUPS.load(Ordering::SeqCst), 1);
+    assert_eq!(TEARDOWNS.load(Ordering::SeqCst), 1);
+}
+
+// FIXME: Re-enable emscripten once it can catch panics again (introduced by #65251)
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_failed_setup_fixture_fails_tests() {
+    fn setup() {
+        panic!();
+    }
+    fn test() {}
+
+    let events = fixture_test_events(vec![
+        ("m::setup", StaticFixtureFn(FixtureKind::Setup, setup)),
+        ("m::a", StaticTestFn(test)),
+        ("other", StaticTestFn(test)),
+    ]);
+
+    assert_eq!(events.len(), 3);
+    match events[0] {
+        TestEvent::TeFixtureFailed(ref fixture) => {
+            assert_eq!(fixture.desc.name.as_slice(), "m::setup")
+        }
+        _ => panic!("expected the setup fixture to fail"),
+    }
+    match events[1] {
+        TestEvent::TeResult(ref completed_test) => assert_eq!(
+            completed_test.result,
+            TestResult::TrFailedMsg("setup fixture `m::setup` failed".to_string())
+        ),
+        _ => panic!("expected the test to fail"),
+    }
+    match events[2] {
+        TestEvent::TeResult(ref completed_test) => assert_eq!(completed_test.result, TrOk),
+        _ => panic!("expected the unrelated test to pass"),
+    }
+}
+
+#[test]
+fn test_fixtures_are_rejected_with_panic_abort() {
+    fn f() {}
+    let make_tests = || {
+        vec![
+            TestDescAndFn {
+                desc: TestDesc {
+                    name: StaticTestName("setup"),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    allow_fail: false,
+                    test_type: TestType::Unknown,
+                },
+                testfn: StaticFixtureFn(FixtureKind::Setup, f),
+            },
+            TestDescAndFn {
+                desc: TestDesc {
+                    name: StaticTestName("test"),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    allow_fail: false,
+                    test_type: TestType::Unknown,
+                },
+                testfn: StaticTestFn(f),
+            },
+        ]
+    };
+
+    let mut opts = TestOpts::new();
+    assert!(fixture::check_supported(&opts, &make_tests()).is_ok());
+
+    opts.options = Options::new().panic_abort(true);
+    assert!(fixture::check_supported(&opts, &make_tests()).is_err());
+    let mut tests = make_tests();
+    tests.remove(0);
+    assert!(fixture::check_supported(&opts, &tests).is_ok());
+
+    opts.force_run_in_process = true;
+    assert!(fixture::check_supported(&opts, &make_tests()).is_ok());
+}
+
 fn report_time_test_template(report_time: bool) -> Option<TestExecTime> {
     fn f() {}
     let desc = TestDescAndFn {
@@ -395,6 +692,97 @@ fn parse_include_ignored_flag() {
     assert_eq!(opts.run_ignored, RunIgnored::Yes);
 }
 
+#[test]
+fn parse_shard_options() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--shard-index".to_string(),
+        "1".to_string(),
+        "--shard-count".to_string(),
+        "3".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.shard, Some(Shard { index: 1, count: 3 }));
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--shard-index".to_string(),
+        "3".to_string(),
+        "--shard-count".to_string(),
+        "3".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--shard-count".to_string(),
+        "3".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+fn parse_timeout_option() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--timeout".to_string(),
+        "30".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_secs(30)));
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--timeout".to_string(),
+        "0".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+
+    let args = vec!["progname".to_string(), "--timeout".to_string(), "30".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+pub fn filter_for_shard_option() {
+    fn tests() -> Vec<TestDescAndFn> {
+        (0..100)
+            .map(|i| TestDescAndFn {
+                desc: TestDesc {
+                    name: DynTestName(format!("module::test_{}", i)),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    allow_fail: false,
+                    test_type: TestType::Unknown,
+                },
+                testfn: DynTestFn(Box::new(move || {})),
+            })
+            .collect()
+    }
+
+    let count = 4;
+    let mut names = Vec::new();
+    for index in 0..count {
+        let opts = TestOpts {
+            shard: Some(Shard { index, count }),
+            ..TestOpts::new()
+        };
+        let filtered = filter_tests(&opts, tests());
+        assert!(!filtered.is_empty());
+        names.extend(filtered.into_iter().map(|test| test.desc.name.to_string()));
+    }
+
+    // Every test must be assigned to exactly one shard.
+    let mut expected: Vec<_> = tests().into_iter().map(|test| test.desc.name.to_string()).collect();
+    expected.sort();
+    names.sort();
+    assert_eq!(names, expected);
+}
+
 #[test]
 pub fn filter_for_ignored_option() {
     // When we run ignored tests the test filter should filter out all the
@@ -707,6 +1095,7 @@ fn should_sort_failures_before_printing_them() {
         failed: 0,
         ignored: 0,
         allowed_fail: 0,
+        flaky: 0,
         filtered_out: 0,
         measured: 0,
         metrics: MetricMap::new(),
@@ -714,6 +1103,11 @@ fn should_sort_failures_before_printing_them() {
         options: Options::new(),
         not_failures: Vec::new(),
         time_failures: Vec::new(),
+        flaky_tests: Vec::new(),
+        fixture_failures: Vec::new(),
+        bench_regressions: Vec::new(),
+        baseline: None,
+        bench_stats: Baseline::new(),
     };
 
     out.write_failures(&st).unwrap();
@@ -726,3 +1120,105 @@ fn should_sort_failures_before_printing_them() {
     let bpos = s.find("b").unwrap();
     assert!(apos < bpos);
 }
+
+#[test]
+fn junit_report_contains_escaped_results() {
+    fn desc(name: &'static str) -> TestDesc {
+        TestDesc {
+            name: StaticTestName(name),
+            ignore: false,
+            should_panic: ShouldPanic::No,
+            allow_fail: false,
+            test_type: TestType::Unknown,
+        }
+    }
+
+    let mut out = JunitFormatter::new(OutputLocation::Raw(Vec::new()), "suite".to_string());
+
+    let st = console::ConsoleTestState {
+        log_out: None,
+        total: 3,
+        passed: 1,
+        failed: 1,
+        ignored: 1,
+        allowed_fail: 0,
+        flaky: 0,
+        filtered_out: 0,
+        measured: 0,
+        metrics: MetricMap::new(),
+        failures: Vec::new(),
+        options: Options::new(),
+        not_failures: Vec::new(),
+        time_failures: Vec::new(),
+        flaky_tests: Vec::new(),
+        fixture_failures: Vec::new(),
+        bench_regressions: Vec::new(),
+        baseline: None,
+        bench_stats: Baseline::new(),
+    };
+
+    let exec_time = test_exec_time(1500);
+    out.write_result(&desc("a"), &TrOk, Some(&exec_time), b"", &st).unwrap();
+    out.write_result(
+        &desc("m::b"),
+        &TestResult::TrFailedMsg("expected <1> & got \"2\"".to_string()),
+        None,
+        b"output\x07",
+        &st,
+    ).unwrap();
+    out.write_result(&desc("m::c"), &TrIgnored, None, b"", &st).unwrap();
+    assert!(!out.write_run_finish(&st).unwrap());
+
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]).into_owned(),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+
+    assert!(s.starts_with("<?xml"));
+    assert!(s.contains(r#"<testsuite name="suite" tests="3" failures="1" errors="0" skipped="1""#));
+    assert!(s.contains(r#"<testcase classname="suite" name="a" time="1.500">"#));
+    assert!(s.contains(r#"<testcase classname="m" name="b" time="0.000">"#));
+    assert!(s.contains(
+        r#"<failure type="assert" message="expected &lt;1&gt; &amp; got &quot;2&quot;"/>"#
+    ));
+    assert!(s.contains("<system-out>output\u{FFFD}</system-out>"));
+    assert!(s.contains("<testcase classname=\"m\" name=\"c\" time=\"0.000\">\n<skipped/>"));
+    assert!(s.ends_with("</testsuite>\n</testsuites>\n"));
+}
+
+#[test]
+fn baseline_roundtrip() {
+    let mut baseline = Baseline::new();
+    baseline.insert("bench_a", BenchStats { mean: 1234.5, var: 67.25, samples: 50 });
+    baseline.insert("mod::bench b", BenchStats { mean: 0.125, var: 0.0, samples: 1 });
+
+    let saved = baseline.to_string();
+    assert_eq!(saved.parse::<Baseline>(), Ok(baseline));
+
+    assert!("".parse::<Baseline>().is_err());
+    assert!(format!("{}50 1.0\n", saved).parse::<Baseline>().is_err());
+    assert!(format!("{}50 NaN 1.0 nan\n", saved).parse::<Baseline>().is_err());
+    assert!(format!("{}50 1.0 inf inf\n", saved).parse::<Baseline>().is_err());
+    assert!(format!("{}50 1.0 -1.0 negative\n", saved).parse::<Baseline>().is_err());
+}
+
+#[test]
+fn compare_bench_to_baseline() {
+    let compare = |baseline_mean, mean| {
+        let baseline = BenchStats { mean: baseline_mean, var: 100.0, samples: 50 };
+        let current = BenchStats { mean, var: 100.0, samples: 50 };
+        BaselineComparison::new(&baseline, &current)
+    };
+
+    // The difference is significant, but below the noise threshold.
+    assert_eq!(compare(1000.0, 1010.0).change(), BenchChange::Unchanged);
+    assert_eq!(compare(1000.0, 1100.0).change(), BenchChange::Regressed);
+    assert_eq!(compare(1000.0, 900.0).change(), BenchChange::Improved);
+    assert_eq!(compare(1000.0, 1250.0).change_pct(), 25.0);
+
+    // The difference is large, but not significant.
+    let noisy_baseline = BenchStats { mean: 10.0, var: 10_000.0, samples: 50 };
+    let current = BenchStats { mean: 15.0, var: 10_000.0, samples: 50 };
+    let comparison = BaselineComparison::new(&noisy_baseline, &current);
+    assert_eq!(comparison.change(), BenchChange::Unchanged);
+}
@@ -2,6 +2,7 @@
 
 use std::fmt;
 use std::borrow::Cow;
+use std::sync::Arc;
 
 use super::options;
 use super::bench::Bencher;
@@ -79,6 +80,19 @@ pub trait TDynBenchFn: Send {
     fn run(&self, harness: &mut Bencher);
 }
 
+/// Denotes when a fixture function is run relative to the tests it applies to.
+///
+/// A fixture applies to all the tests in the module it is defined in,
+/// including the nested modules. Fixtures defined at the crate root apply
+/// to the whole test binary.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FixtureKind {
+    /// Run once before the first of the tests starts.
+    Setup,
+    /// Run once after the last of the tests has completed.
+    Teardown,
+}
+
 // A function that runs a test. If the function returns successfully,
 // the test succeeds; if the function panics then the test fails. We
 // may need to come up with a more clever definition of test in order
@@ -87,7 +101,11 @@ pub enum TestFn {
     StaticTestFn(fn()),
     StaticBenchFn(fn(&mut Bencher)),
     DynTestFn(Box<dyn FnOnce() + Send>),
+    /// A dynamic test which can be run more than once, allowing it to be
+    /// retried when it fails (see the `--retries` option).
+    DynRetryableTestFn(Arc<dyn Fn() + Send + Sync>),
     DynBenchFn(Box<dyn TDynBenchFn + 'static>),
+    StaticFixtureFn(FixtureKind, fn()),
 }
 
 impl TestFn {
@@ -96,7 +114,9 @@ impl TestFn {
             StaticTestFn(..) => PadNone,
             StaticBenchFn(..) => PadOnRight,
             DynTestFn(..) => PadNone,
+            DynRetryableTestFn(..) => PadNone,
             DynBenchFn(..) => PadOnRight,
+            StaticFixtureFn(..) => PadNone,
         }
     }
 }
@@ -107,7 +127,9 @@ impl fmt::Debug for TestFn {
             StaticTestFn(..) => "StaticTestFn(..)",
             StaticBenchFn(..) => "StaticBenchFn(..)",
             DynTestFn(..) => "DynTestFn(..)",
+            DynRetryableTestFn(..) => "DynRetryableTestFn(..)",
             DynBenchFn(..) => "DynBenchFn(..)",
+            StaticFixtureFn(..) => "StaticFixtureFn(..)",
         })
     }
 }
@@ -124,6 +146,16 @@ pub struct TestDesc {
 }
 
 impl TestDesc {
+    /// Path of the module containing the test, or an empty string for tests
+    /// defined at the crate root.
+    pub fn module_path(&self) -> &str {
+        let name = self.name.as_slice();
+        match name.rfind("::") {
+            Some(pos) => &name[..pos],
+            None => "",
+        }
+    }
+
     pub fn padded_name(&self, column_count: usize, align: NamePadding) -> String {
         let mut name = String::from(self.name.as_slice());
         let fill = column_count.saturating_sub(name.len());
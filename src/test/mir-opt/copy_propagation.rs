@@ -1,3 +1,5 @@
+// EMIT_MIR rustc.test.CopyPropagation.before.mir
+// EMIT_MIR rustc.test.CopyPropagation.after.mir
 fn test(x: u32) -> u32 {
     let y = x;
     y
@@ -7,23 +9,3 @@ fn main() {
     // Make sure the function actually gets instantiated.
     test(0);
 }
-
-// END RUST SOURCE
-// START rustc.test.CopyPropagation.before.mir
-//  bb0: {
-//      ...
-//      _2 = _1;
-//      ...
-//      _0 = _2;
-//      ...
-//      return;
-//  }
-// END rustc.test.CopyPropagation.before.mir
-// START rustc.test.CopyPropagation.after.mir
-//  bb0: {
-//      ...
-//      _0 = _1;
-//      ...
-//      return;
-//  }
-// END rustc.test.CopyPropagation.after.mir
@@ -0,0 +1,30 @@
+trait Shape {
+    fn area(&self) -> u32;
+}
+
+struct Square(u32);
+
+impl Shape for Square {
+    fn area(&self) -> u32 {
+        self.0 * self.0
+    }
+}
+
+fn total_area(shapes: &[&dyn Shape]) -> u32 {
+    shapes.iter().map(|shape| shape.area()).sum()
+}
+
+fn apply(f: fn(u32) -> u32, x: u32) -> u32 {
+    f(x)
+}
+
+fn double(x: u32) -> u32 {
+    x * 2
+}
+
+fn main() {
+    let shapes: [&dyn Shape; 1] = [&Square(2)];
+    let areas = [total_area(&shapes)];
+    let index = std::env::args().count();
+    println!("{}", apply(double, areas[index]));
+}
@@ -0,0 +1,7 @@
+pub fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+pub fn first(x: &[u8]) -> u8 {
+    x[0]
+}
@@ -0,0 +1,15 @@
+use std::fmt::Display;
+
+fn describe<T: Display>(value: T) -> String {
+    format!("<{}>", value)
+}
+
+fn log<T: Display>(value: T) {
+    println!("{}", describe(value));
+}
+
+fn main() {
+    log(1u8);
+    log("one");
+    describe(2u16);
+}
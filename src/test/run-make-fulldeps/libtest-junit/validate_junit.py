@@ -0,0 +1,28 @@
+#!/usr/bin/env python
+
+import sys
+import xml.etree.ElementTree as ET
+
+# Try to decode the report in order to ensure it is a valid XML document,
+# then check that every test case ended up with the expected outcome.
+suites = ET.parse(sys.stdin).getroot()
+assert suites.tag == 'testsuites'
+
+suite = suites.find('testsuite')
+assert suite.get('name') == 'f'
+assert suite.get('tests') == '4'
+assert suite.get('failures') == '1'
+assert suite.get('skipped') == '1'
+
+cases = dict((case.get('name'), case) for case in suite.findall('testcase'))
+assert sorted(cases.keys()) == ['a', 'b', 'c', 'd']
+
+for case in cases.values():
+    assert case.get('classname') == 'f'
+    float(case.get('time'))
+
+assert cases['a'].find('failure') is None
+assert cases['b'].find('failure') is not None
+assert 'assertion failed' in cases['b'].find('system-out').text
+assert cases['c'].find('failure') is None
+assert cases['d'].find('skipped') is not None
@@ -0,0 +1,25 @@
+mod inner {
+    pub fn double(x: u32) -> u32 {
+        x * 2
+    }
+
+    #[macro_export]
+    macro_rules! add_ten_twice {
+        ($e:expr) => {{
+            let x = 10;
+            'outer: loop {
+                break 'outer $crate::inner::double($e + x);
+            }
+        }};
+    }
+}
+
+use inner::double;
+
+fn main() {
+    let x = 1;
+    let y = add_ten_twice!(x);
+    if y != 22 || double(x) != 2 {
+        std::process::exit(1);
+    }
+}
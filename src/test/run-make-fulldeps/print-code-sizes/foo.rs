@@ -0,0 +1,11 @@
+pub mod shapes {
+    pub fn area<T: Into<f64>>(width: T, height: T) -> f64 {
+        width.into() * height.into()
+    }
+}
+
+fn main() {
+    println!("{}", shapes::area(2u8, 3u8));
+    println!("{}", shapes::area(2u32, 3u32));
+    println!("{}", shapes::area(2.0f32, 3.0f32));
+}
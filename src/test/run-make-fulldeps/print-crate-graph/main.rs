@@ -0,0 +1,5 @@
+extern crate dep;
+
+fn main() {
+    dep::dep();
+}
@@ -0,0 +1,40 @@
+#![no_std]
+
+use core::panic::PanicInfo;
+
+#[panic_handler]
+fn panic(_: &PanicInfo<'_>) -> ! {
+    loop {}
+}
+
+#[inline(never)]
+fn sum(xs: &[u32; 16]) -> u32 {
+    xs.iter().fold(0, |acc, x| acc.wrapping_add(*x))
+}
+
+#[no_mangle]
+pub extern "C" fn bounded(x: u32) -> u32 {
+    let xs = [x; 16];
+    sum(unsafe { core::ptr::read_volatile(&&xs) })
+}
+
+#[inline(never)]
+fn fact(n: u32) -> u32 {
+    if n == 0 { 1 } else { n.wrapping_mul(fact(n - 1)) }
+}
+
+#[no_mangle]
+pub extern "C" fn recursive(n: u32) -> u32 {
+    fact(n)
+}
+
+#[inline(never)]
+fn double(x: u32) -> u32 {
+    x.wrapping_mul(2)
+}
+
+#[no_mangle]
+pub extern "C" fn indirect(x: u32) -> u32 {
+    let f: fn(u32) -> u32 = unsafe { core::ptr::read_volatile(&(double as fn(u32) -> u32)) };
+    f(x)
+}
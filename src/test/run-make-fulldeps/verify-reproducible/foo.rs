@@ -0,0 +1,6 @@
+pub fn answer() -> u32 {
+    42
+}
+
+#[cfg(tmpdir)]
+pub static TMPDIR: &str = env!("TMPDIR");
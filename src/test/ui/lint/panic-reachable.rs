@@ -0,0 +1,22 @@
+// Checks that `panic_reachable` reports the functions from which a panic is
+// reachable, through calls and the implicit bounds checks of indexing.
+
+#![deny(panic_reachable)]
+#![crate_type = "lib"]
+
+pub fn get(values: &[u32], index: usize) -> u32 { //~ ERROR a panic is reachable from `get`
+    values[index]
+}
+
+pub fn first(values: &[u32]) -> u32 { //~ ERROR a panic is reachable from `first`
+    get(values, 0)
+}
+
+pub fn sum(a: u32, b: u32) -> u32 {
+    a.wrapping_add(b)
+}
+
+#[allow(panic_reachable)]
+pub fn last(values: &[u32]) -> u32 {
+    values[values.len() - 1]
+}
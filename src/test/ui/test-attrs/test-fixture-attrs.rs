@@ -0,0 +1,19 @@
+// Attributes which only make sense on tests are rejected on fixtures.
+// compile-flags: --test
+
+#![feature(test_fixtures, allow_fail)]
+
+#[test_setup]
+#[ignore] //~ ERROR `#[ignore]` cannot be used on test fixtures
+fn ignored_setup() {}
+
+#[test_teardown]
+#[should_panic] //~ ERROR `#[should_panic]` cannot be used on test fixtures
+fn panicking_teardown() {}
+
+#[test_setup]
+#[allow_fail] //~ ERROR `#[allow_fail]` cannot be used on test fixtures
+fn failing_setup() {}
+
+#[test]
+fn test() {}
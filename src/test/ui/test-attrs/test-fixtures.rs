@@ -0,0 +1,54 @@
+// run-pass
+// compile-flags: --test
+// run-flags: --test-threads=1
+#![feature(test_fixtures)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static SETUP: AtomicUsize = AtomicUsize::new(0);
+static INNER_SETUP: AtomicUsize = AtomicUsize::new(0);
+static INNER_TEARDOWN: AtomicUsize = AtomicUsize::new(0);
+
+#[test_setup]
+fn setup() {
+    SETUP.fetch_add(1, Ordering::SeqCst);
+}
+
+#[test_teardown]
+fn teardown() {
+    assert_eq!(SETUP.load(Ordering::SeqCst), 1);
+    assert_eq!(INNER_TEARDOWN.load(Ordering::SeqCst), 1);
+}
+
+// Run after the tests of `inner` on a single thread, since tests run in order.
+#[test]
+fn outer() {
+    assert_eq!(SETUP.load(Ordering::SeqCst), 1);
+    assert_eq!(INNER_TEARDOWN.load(Ordering::SeqCst), 1);
+}
+
+mod inner {
+    use super::*;
+
+    #[test_setup]
+    fn setup() {
+        assert_eq!(SETUP.load(Ordering::SeqCst), 1);
+        INNER_SETUP.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test_teardown]
+    fn teardown() {
+        assert_eq!(INNER_SETUP.load(Ordering::SeqCst), 1);
+        INNER_TEARDOWN.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn first() {
+        assert_eq!(INNER_SETUP.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn second() {
+        assert_eq!(INNER_SETUP.load(Ordering::SeqCst), 1);
+    }
+}
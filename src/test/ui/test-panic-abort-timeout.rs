@@ -0,0 +1,22 @@
+// no-prefer-dynamic
+// compile-flags: --test -Cpanic=abort -Zpanic_abort_tests
+// run-flags: --test-threads=1 -Zunstable-options --timeout=1
+// run-fail
+// check-run-results
+
+// ignore-wasm no subprocess support
+// ignore-emscripten no subprocess support
+
+#![cfg(test)]
+
+#[test]
+fn it_works() {
+    assert_eq!(1 + 1, 2);
+}
+
+#[test]
+fn it_hangs() {
+    loop {
+        std::thread::park();
+    }
+}
@@ -293,6 +293,16 @@ pub struct Config {
     /// created in `/<build_base>/rustfix_missing_coverage.txt`
     pub rustfix_coverage: bool,
 
+    /// If true, the error codes emitted while compiling the tests are recorded, and a report
+    /// mapping them to the tests emitting them is created in
+    /// `/<build_base>/error_code_coverage.json`. Tests are then run even if they are up to
+    /// date, since the codes emitted by the skipped ones would be missing from the report.
+    pub error_code_coverage: bool,
+
+    /// If true, the run fails when an error code documented in `librustc_error_codes` is not
+    /// emitted by any of the tests. Implies `error_code_coverage`.
+    pub deny_untested_error_codes: bool,
+
     // Configuration for various run-make tests frobbing things like C compilers
     // or querying about various LLVM component information.
     pub cc: String,
@@ -335,6 +345,12 @@ pub fn expected_output_path(
     testpaths.file.with_extension(extension)
 }
 
+/// Used by `mir-opt` tests to locate the expected version of a MIR dump named by an
+/// `// EMIT_MIR` annotation, like `foo/rustc.main.SimplifyCfg-final.after.mir` for `foo.rs`.
+pub fn expected_mir_dump_path(testpaths: &TestPaths, dump_name: &str) -> PathBuf {
+    testpaths.file.with_extension("").join(dump_name)
+}
+
 pub const UI_EXTENSIONS: &[&str] = &[UI_STDERR, UI_STDOUT, UI_FIXED, UI_RUN_STDERR, UI_RUN_STDOUT];
 pub const UI_STDERR: &str = "stderr";
 pub const UI_STDOUT: &str = "stdout";
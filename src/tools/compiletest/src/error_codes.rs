@@ -0,0 +1,112 @@
+//! Coverage of the error codes of `librustc_error_codes` by the tests.
+//!
+//! Unlike tidy, which only checks that every error code is mentioned somewhere, this
+//! records the codes of the diagnostics actually emitted while compiling each test,
+//! and writes a report mapping every code to the tests emitting it once they have run.
+
+use crate::common::Config;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Documented error codes which can't be emitted by tests, see also the whitelist of
+/// `tidy/src/error_codes_check.rs`.
+const UNTESTABLE: &[&str] = &[
+    "E0554", // `#![feature]` on the stable release channel
+    "E0570", // ABI not supported by the target
+    "E0729", // NLL migration errors
+];
+
+lazy_static! {
+    /// Names of the tests emitting each error code.
+    static ref EMITTED_CODES: Mutex<BTreeMap<String, BTreeSet<String>>> =
+        Mutex::new(BTreeMap::new());
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    /// The tests emitting each error code.
+    emitted: &'a BTreeMap<String, BTreeSet<String>>,
+    /// The documented error codes emitted by none of the tests.
+    untested: &'a [String],
+}
+
+/// Records the diagnostic codes emitted while compiling the given test, ignoring the
+/// ones which are not error codes (e.g. lint names).
+pub fn record(test_name: &str, codes: &[String]) {
+    let mut emitted = EMITTED_CODES.lock().unwrap();
+    for code in codes.iter().filter(|code| is_error_code(code)) {
+        emitted.entry(code.clone()).or_default().insert(test_name.to_owned());
+    }
+}
+
+fn is_error_code(code: &str) -> bool {
+    code.len() == 5 && code.starts_with('E') && code[1..].bytes().all(|b| b.is_ascii_digit())
+}
+
+pub fn report_path(config: &Config) -> PathBuf {
+    config.build_base.join("error_code_coverage.json")
+}
+
+/// Writes the report of the error codes emitted by the tests which have been run.
+///
+/// Returns the error codes which have an explanation in `librustc_error_codes`, are
+/// still emitted by the compiler and yet are emitted by none of the tests.
+pub fn write_report(config: &Config) -> Vec<String> {
+    let emitted = EMITTED_CODES.lock().unwrap();
+    let error_codes_dir = config
+        .src_base
+        .parent()
+        .and_then(Path::parent)
+        .expect("the tests are not in `src/test`")
+        .join("librustc_error_codes");
+    let untested = untested_error_codes(documented_error_codes(&error_codes_dir), &emitted);
+
+    let path = report_path(config);
+    let report = Report { emitted: &emitted, untested: &untested };
+    let json = serde_json::to_string_pretty(&report).unwrap();
+    if let Err(e) = fs::write(&path, json) {
+        panic!("couldn't write {}: {}", path.display(), e);
+    }
+
+    untested
+}
+
+/// Returns the `documented` error codes which are emitted by none of the tests, leaving
+/// out the untestable ones.
+pub fn untested_error_codes(
+    documented: Vec<String>,
+    emitted: &BTreeMap<String, BTreeSet<String>>,
+) -> Vec<String> {
+    documented
+        .into_iter()
+        .filter(|code| !emitted.contains_key(code) && !UNTESTABLE.contains(&&**code))
+        .collect()
+}
+
+/// Returns the error codes registered with an explanation in `librustc_error_codes`,
+/// except the ones which are no longer emitted by the compiler.
+pub fn documented_error_codes(error_codes_dir: &Path) -> Vec<String> {
+    let path = error_codes_dir.join("error_codes.rs");
+    let contents = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("couldn't read {}: {}", path.display(), e));
+
+    contents
+        .lines()
+        .filter(|line| line.contains("include_str!"))
+        .filter_map(|line| line.split(':').next())
+        .filter(|code| is_error_code(code))
+        .filter(|code| {
+            let explanation = error_codes_dir.join("error_codes").join(format!("{}.md", code));
+            match fs::read_to_string(&explanation) {
+                Ok(explanation) => !explanation
+                    .contains("#### Note: this error code is no longer emitted by the compiler"),
+                Err(_) => true,
+            }
+        })
+        .map(|code| code.to_owned())
+        .collect()
+}
@@ -94,6 +94,27 @@ pub fn extract_rendered(output: &str) -> String {
         .collect()
 }
 
+/// Returns the codes of the diagnostics in the compiler output, including the ones of their
+/// children. Lines which are not JSON diagnostics are ignored.
+pub fn extract_error_codes(output: &str) -> Vec<String> {
+    fn push_codes(codes: &mut Vec<String>, diagnostic: &Diagnostic) {
+        if let Some(ref code) = diagnostic.code {
+            codes.push(code.code.clone());
+        }
+        for child in &diagnostic.children {
+            push_codes(codes, child);
+        }
+    }
+
+    let mut codes = vec![];
+    for line in output.lines().filter(|line| line.starts_with('{')) {
+        if let Ok(diagnostic) = serde_json::from_str::<Diagnostic>(line) {
+            push_codes(&mut codes, &diagnostic);
+        }
+    }
+    codes
+}
+
 pub fn parse_output(file_name: &str, output: &str, proc_res: &ProcRes) -> Vec<Error> {
     output
         .lines()
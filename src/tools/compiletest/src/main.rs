@@ -30,6 +30,7 @@ use self::header::{EarlyProps, Ignore};
 mod tests;
 
 pub mod common;
+pub mod error_codes;
 pub mod errors;
 pub mod header;
 mod json;
@@ -248,6 +249,19 @@ pub fn parse_config(args: Vec<String>) -> Config {
             "enable this to generate a Rustfix coverage file, which is saved in \
                 `./<build_base>/rustfix_missing_coverage.txt`",
         )
+        .optflag(
+            "",
+            "error-code-coverage",
+            "generate a report of the tests emitting each error code, which is saved in \
+                `./<build_base>/error_code_coverage.json`; all the selected tests are run \
+                even if they are up to date",
+        )
+        .optflag(
+            "",
+            "deny-untested-error-codes",
+            "fail if a documented error code is not emitted by any test, \
+                implies --error-code-coverage",
+        )
         .optflag("h", "help", "show this message");
 
     let (argv0, args_) = args.split_first().unwrap();
@@ -359,6 +373,9 @@ pub fn parse_config(args: Vec<String>) -> Config {
         remote_test_client: matches.opt_str("remote-test-client").map(PathBuf::from),
         compare_mode: matches.opt_str("compare-mode").map(CompareMode::parse),
         rustfix_coverage: matches.opt_present("rustfix-coverage"),
+        error_code_coverage: matches.opt_present("error-code-coverage")
+            || matches.opt_present("deny-untested-error-codes"),
+        deny_untested_error_codes: matches.opt_present("deny-untested-error-codes"),
 
         cc: matches.opt_str("cc").unwrap(),
         cxx: matches.opt_str("cxx").unwrap(),
@@ -532,6 +549,20 @@ pub fn run_tests(config: &Config) {
     env::set_var("TARGET", &config.target);
 
     let res = test::run_tests_console(&opts, tests);
+
+    if config.error_code_coverage {
+        let untested = error_codes::write_report(config);
+        let report_path = error_codes::report_path(config);
+        println!("error code coverage report saved in {}", report_path.display());
+        if !untested.is_empty() {
+            println!("documented error codes not emitted by any test: {}", untested.join(", "));
+            // Codes emitted by the tests left out by the filter would be reported too.
+            if config.deny_untested_error_codes && config.filter.is_none() {
+                panic!("Some error codes are untested");
+            }
+        }
+    }
+
     match res {
         Ok(true) => {}
         Ok(false) => panic!("Some tests failed"),
@@ -541,10 +572,22 @@ pub fn run_tests(config: &Config) {
     }
 }
 
+/// Returns the filter to give to libtest.
+///
+/// Incremental tests run all their revisions as a single test, since each revision
+/// depends on the previous ones, so a filter selecting one of them selects the whole test.
+fn test_filter(config: &Config) -> Option<String> {
+    let filter = config.filter.as_ref()?;
+    match filter.rfind('#') {
+        Some(i) if config.mode == Mode::Incremental => Some(filter[..i].to_owned()),
+        _ => Some(filter.clone()),
+    }
+}
+
 pub fn test_opts(config: &Config) -> test::TestOpts {
     test::TestOpts {
         exclude_should_panic: false,
-        filter: config.filter.clone(),
+        filter: test_filter(config),
         filter_exact: config.filter_exact,
         run_ignored: if config.run_ignored {
             test::RunIgnored::Yes
@@ -566,6 +609,22 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         color: config.color,
         test_threads: None,
         skip: vec![],
+        // FIXME: remove the attributes once the bootstrap compiler's libtest
+        // knows about test sharding, retries, timeouts and benchmark baselines.
+        #[cfg(not(bootstrap))]
+        shard: None,
+        // Flaky tests can be retried by setting `RUST_TEST_RETRIES`.
+        #[cfg(not(bootstrap))]
+        retries: match env::var("RUST_TEST_RETRIES") {
+            Ok(val) => val.parse().expect("RUST_TEST_RETRIES must be a number"),
+            Err(_) => 0,
+        },
+        #[cfg(not(bootstrap))]
+        test_timeout: None,
+        #[cfg(not(bootstrap))]
+        save_baseline: None,
+        #[cfg(not(bootstrap))]
+        baseline: None,
         list: false,
         options: test::Options::new(),
         time_options: None,
@@ -723,6 +782,7 @@ fn make_test(config: &Config, testpaths: &TestPaths, inputs: &Stamp) -> Vec<test
     };
     revisions
         .into_iter()
+        .filter(|revision| is_selected_revision(config, testpaths, *revision))
         .map(|revision| {
             let ignore = early_props.ignore == Ignore::Ignore
                 // Debugging emscripten code doesn't make sense today
@@ -731,8 +791,9 @@ fn make_test(config: &Config, testpaths: &TestPaths, inputs: &Stamp) -> Vec<test
                     && config.target.contains("emscripten"))
                 || (config.mode == DebugInfoGdb && !early_props.ignore.can_run_gdb())
                 || (config.mode == DebugInfoLldb && !early_props.ignore.can_run_lldb())
-                // Ignore tests that already run and are up to date with respect to inputs.
-                || is_up_to_date(
+                // Ignore tests that already run and are up to date with respect to inputs,
+                // unless the error codes they emit have to be recorded.
+                || !config.error_code_coverage && is_up_to_date(
                     config,
                     testpaths,
                     &early_props,
@@ -794,6 +855,14 @@ fn is_up_to_date(
         inputs.add_path(path);
     }
 
+    // Expected MIR dumps of MIR-opt tests.
+    if config.mode == Mode::MirOpt {
+        let dir = testpaths.file.with_extension("");
+        if dir.is_dir() {
+            inputs.add_dir(&dir);
+        }
+    }
+
     inputs < Stamp::from_path(&stamp_name)
 }
 
@@ -853,6 +922,18 @@ fn make_test_name(
     ))
 }
 
+/// Checks whether a filter naming a single revision, like `foo.rs#rev`, doesn't only match
+/// the test because the test's revision has a longer name starting with `rev`.
+fn is_selected_revision(config: &Config, testpaths: &TestPaths, revision: Option<&String>) -> bool {
+    match (revision, &config.filter) {
+        (Some(_), Some(filter)) if filter.contains('#') && !config.filter_exact => {
+            let name = make_test_name(config, testpaths, revision).to_string();
+            !name.contains(&**filter) || name.ends_with(&**filter)
+        }
+        _ => true,
+    }
+}
+
 fn make_test_closure(
     config: &Config,
     ignore: Ignore,
@@ -872,9 +953,18 @@ fn make_test_closure(
 
     let testpaths = testpaths.clone();
     let revision = revision.cloned();
-    test::DynTestFn(Box::new(move || {
+    // Keep the test re-invocable so that it can be run again with `--retries`.
+    // FIXME: remove the `cfg`s once the bootstrap compiler's libtest knows
+    // about retryable tests.
+    #[cfg(not(bootstrap))]
+    let testfn = test::DynRetryableTestFn(std::sync::Arc::new(move || {
+        runtest::run(config.clone(), &testpaths, revision.as_ref().map(|s| s.as_str()))
+    }));
+    #[cfg(bootstrap)]
+    let testfn = test::DynTestFn(Box::new(move || {
         runtest::run(config, &testpaths, revision.as_ref().map(|s| s.as_str()))
-    }))
+    }));
+    testfn
 }
 
 /// Returns `true` if the given target is an Android target for the
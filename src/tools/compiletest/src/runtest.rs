@@ -2,6 +2,7 @@
 
 use crate::common::{CompareMode, PassMode};
 use crate::common::{expected_output_path, UI_EXTENSIONS, UI_FIXED, UI_STDERR, UI_STDOUT};
+use crate::common::expected_mir_dump_path;
 use crate::common::{UI_RUN_STDERR, UI_RUN_STDOUT};
 use crate::common::{output_base_dir, output_base_name, output_testname_unique};
 use crate::common::{Codegen, CodegenUnits, Rustdoc};
@@ -10,6 +11,7 @@ use crate::common::{CompileFail, Pretty, RunFail, RunPassValgrind};
 use crate::common::{Config, TestPaths};
 use crate::common::{Incremental, MirOpt, RunMake, Ui, JsDocTest, Assembly};
 use diff;
+use crate::error_codes;
 use crate::errors::{self, Error, ErrorKind};
 use crate::header::TestProps;
 use crate::json;
@@ -184,6 +186,74 @@ pub fn make_diff(expected: &str, actual: &str, context_size: usize) -> Vec<Misma
     results
 }
 
+// Produces a diff between the expected output and actual output in the unified format, without
+// the file headers.
+pub fn make_unified_diff(expected: &str, actual: &str, context_size: usize) -> String {
+    let results = diff::lines(expected, actual);
+    let is_change = |result: &diff::Result<&str>| match *result {
+        diff::Result::Both(..) => false,
+        diff::Result::Left(_) | diff::Result::Right(_) => true,
+    };
+
+    // Line numbers of both sides before each of the results.
+    let mut line_numbers = Vec::with_capacity(results.len() + 1);
+    let (mut expected_line, mut actual_line) = (1, 1);
+    for result in &results {
+        line_numbers.push((expected_line, actual_line));
+        match *result {
+            diff::Result::Left(_) => expected_line += 1,
+            diff::Result::Right(_) => actual_line += 1,
+            diff::Result::Both(..) => {
+                expected_line += 1;
+                actual_line += 1;
+            }
+        }
+    }
+    line_numbers.push((expected_line, actual_line));
+
+    let mut output = String::new();
+    let mut next = 0;
+    while let Some(first_change) = (next..results.len()).find(|&i| is_change(&results[i])) {
+        // Changes separated by less than twice the context are put in the same hunk.
+        let mut last_change = first_change;
+        let mut i = first_change + 1;
+        while i < results.len() && i <= last_change + 2 * context_size {
+            if is_change(&results[i]) {
+                last_change = i;
+            }
+            i += 1;
+        }
+
+        let start = first_change.saturating_sub(context_size);
+        let end = (last_change + 1 + context_size).min(results.len());
+        let (expected_start, actual_start) = line_numbers[start];
+        let (expected_end, actual_end) = line_numbers[end];
+        let range = |start: usize, end: usize| {
+            let count = end - start;
+            // Empty ranges are numbered after the line preceding them.
+            let start = if count == 0 { start - 1 } else { start };
+            format!("{},{}", start, count)
+        };
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(expected_start, expected_end),
+            range(actual_start, actual_end)
+        ));
+
+        for result in &results[start..end] {
+            match *result {
+                diff::Result::Left(line) => output.push_str(&format!("-{}\n", line)),
+                diff::Result::Right(line) => output.push_str(&format!("+{}\n", line)),
+                diff::Result::Both(line, _) => output.push_str(&format!(" {}\n", line)),
+            }
+        }
+
+        next = end;
+    }
+
+    output
+}
+
 pub fn run(config: Config, testpaths: &TestPaths, revision: Option<&str>) {
     match &*config.target {
         "arm-linux-androideabi"
@@ -1590,7 +1660,19 @@ impl<'test> TestCx<'test> {
             _ => {}
         }
 
-        self.compose_and_run_compiler(rustc, None)
+        let proc_res = self.compose_and_run_compiler(rustc, None);
+        if self.config.error_code_coverage {
+            // Name the test like in the output of libtest, e.g. `ui/foo/bar.rs#revision`.
+            let path = Path::new(self.config.src_base.file_name().unwrap())
+                .join(&self.testpaths.relative_dir)
+                .join(self.testpaths.file.file_name().unwrap());
+            let test_name = match self.revision {
+                Some(revision) => format!("{}#{}", path.display(), revision),
+                None => path.display().to_string(),
+            };
+            error_codes::record(&test_name, &json::extract_error_codes(&proc_res.stderr));
+        }
+        proc_res
     }
 
     fn document(&self, out_dir: &Path) -> ProcRes {
@@ -3222,6 +3304,19 @@ impl<'test> TestCx<'test> {
 
     fn check_mir_dump(&self) {
         let test_file_contents = fs::read_to_string(&self.testpaths.file).unwrap();
+
+        let mut errors = 0;
+        for l in test_file_contents.lines() {
+            if l.starts_with("// EMIT_MIR ") {
+                let (_, dump_name) = l.split_at("// EMIT_MIR ".len());
+                errors += self.compare_mir_dump_file(dump_name.trim());
+            }
+        }
+        if errors > 0 {
+            println!("To update references, rerun the tests and pass the `--bless` flag");
+            self.fatal(&format!("{} errors occurred comparing MIR dumps.", errors));
+        }
+
         if let Some(idx) = test_file_contents.find("// END RUST SOURCE") {
             let (_, tests_text) = test_file_contents.split_at(idx + "// END_RUST SOURCE".len());
             let tests_text_str = String::from(tests_text);
@@ -3253,6 +3348,50 @@ impl<'test> TestCx<'test> {
         }
     }
 
+    /// Compares a MIR dump requested with `// EMIT_MIR` with the expected one stored next to
+    /// the test, or replaces the latter when blessing. Returns the number of mismatches.
+    fn compare_mir_dump_file(&self, dump_name: &str) -> usize {
+        let output_file = self.get_mir_dump_dir().join(dump_name);
+        if !output_file.exists() {
+            self.fatal(&format!(
+                "Output file `{}` from test does not exist, available files are in `{}`",
+                output_file.display(),
+                self.get_mir_dump_dir().display()
+            ));
+        }
+        let dumped = fs::read_to_string(&output_file).unwrap();
+        let actual = normalize_mir_dump(&self.normalize_output(&dumped, &[]));
+
+        let expected_file = expected_mir_dump_path(self.testpaths, dump_name);
+        let expected = if expected_file.exists() {
+            match self.load_expected_output_from_path(&expected_file) {
+                Ok(x) => x,
+                Err(x) => self.fatal(&x),
+            }
+        } else {
+            String::new()
+        };
+
+        if actual == expected {
+            return 0;
+        }
+
+        if self.config.bless {
+            create_dir_all(expected_file.parent().unwrap()).unwrap();
+            if let Err(err) = fs::write(&expected_file, &actual) {
+                self.fatal(&format!("failed to write {}: {:?}", expected_file.display(), err));
+            }
+            println!("Blessed MIR dump saved to {}", expected_file.display());
+            return 0;
+        }
+
+        println!("--- {}", expected_file.display());
+        println!("+++ {}", output_file.display());
+        print!("{}", make_unified_diff(&expected, &actual, 3));
+        println!();
+        1
+    }
+
     fn check_mir_test_timestamp(&self, test_name: &str, output_file: &Path) {
         let t = |file| fs::metadata(file).unwrap().modified().unwrap();
         let source_file = &self.testpaths.file;
@@ -3724,6 +3863,15 @@ where
     }
 }
 
+/// Replaces the crate disambiguators in the `DefId`s of a MIR dump, which change
+/// along with the crate metadata, e.g., `copy_propagation[317d]`.
+fn normalize_mir_dump(dump: &str) -> String {
+    lazy_static! {
+        static ref DEF_ID_CRATE_RE: Regex = Regex::new(r"~ ([^\[\s]+)\[[0-9a-f]{4}\]").unwrap();
+    }
+    DEF_ID_CRATE_RE.replace_all(dump, "~ $1[HASH]").into_owned()
+}
+
 fn normalize_mir_line(line: &str) -> String {
     nocomment_mir_line(line).replace(char::is_whitespace, "")
 }
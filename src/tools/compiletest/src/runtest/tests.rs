@@ -59,3 +59,28 @@ fn normalize_platform_differences() {
         r#"println!("test\ntest")"#,
     );
 }
+
+#[test]
+fn unified_diff() {
+    assert_eq!(make_unified_diff("a\nb\nc", "a\nb\nc", 3), "");
+    assert_eq!(
+        make_unified_diff("a\nb\nc\nd\ne\nf", "a\nb\nx\nd\ne\nf", 1),
+        "@@ -2,3 +2,3 @@\n b\n-c\n+x\n d\n"
+    );
+    // Distant changes get their own hunks.
+    assert_eq!(
+        make_unified_diff("a\nb\nc\nd\ne\nf\ng", "x\nb\nc\nd\ne\nf", 1),
+        "@@ -1,2 +1,2 @@\n-a\n+x\n b\n@@ -6,2 +6,1 @@\n f\n-g\n"
+    );
+    // Empty ranges are numbered after the preceding line.
+    assert_eq!(make_unified_diff("a", "a\nb", 0), "@@ -1,0 +2,1 @@\n+b\n");
+}
+
+#[test]
+fn mir_dump_crate_disambiguators() {
+    assert_eq!(
+        normalize_mir_dump("Item(DefId(0:3 ~ copy_propagation[317d]::test[0]))"),
+        "Item(DefId(0:3 ~ copy_propagation[HASH]::test[0]))",
+    );
+    assert_eq!(normalize_mir_dump("_1 = [const 1234u32];"), "_1 = [const 1234u32];");
+}
@@ -1,5 +1,7 @@
 use super::*;
 
+use std::collections::BTreeMap;
+
 #[test]
 fn test_extract_gdb_version() {
     macro_rules! test { ($($expectation:tt: $input:tt,)*) => {{$(
@@ -49,3 +51,143 @@ fn is_test_test() {
     assert_eq!(false, is_test(&OsString::from("#a_dog_gif")));
     assert_eq!(false, is_test(&OsString::from("~a_temp_file")));
 }
+
+fn make_config(mode: &str, filter: Option<&str>) -> Config {
+    let mut args: Vec<String> = vec![
+        "compiletest",
+        "--compile-lib-path=",
+        "--run-lib-path=",
+        "--rustc-path=rustc",
+        "--lldb-python=",
+        "--docck-python=",
+        "--src-base=src/test/ui",
+        "--build-base=build",
+        "--stage-id=stage2",
+        "--cc=c",
+        "--cxx=c++",
+        "--cflags=",
+        "--llvm-components=",
+        "--llvm-cxxflags=",
+    ].into_iter().map(String::from).collect();
+    args.push(format!("--mode={}", mode));
+    args.extend(filter.map(String::from));
+    parse_config(args)
+}
+
+fn testpaths() -> TestPaths {
+    TestPaths {
+        file: PathBuf::from("src/test/ui/foo.rs"),
+        relative_dir: PathBuf::new(),
+    }
+}
+
+// Whether libtest's substring filter would keep the test for `revision`.
+fn is_filtered_in(config: &Config, revision: Option<&str>) -> bool {
+    let revision = revision.map(String::from);
+    let name = make_test_name(config, &testpaths(), revision.as_ref()).to_string();
+    is_selected_revision(config, &testpaths(), revision.as_ref())
+        && name.contains(&*test_filter(config).unwrap())
+}
+
+#[test]
+fn test_filter_plain_path() {
+    let config = make_config("ui", Some("foo.rs"));
+    assert_eq!(test_filter(&config), Some("foo.rs".to_owned()));
+    assert!(is_filtered_in(&config, None));
+    assert!(is_filtered_in(&config, Some("a")));
+    assert!(is_filtered_in(&config, Some("ab")));
+}
+
+#[test]
+fn test_filter_matching_revision() {
+    let config = make_config("ui", Some("foo.rs#a"));
+    assert_eq!(test_filter(&config), Some("foo.rs#a".to_owned()));
+    assert!(is_filtered_in(&config, Some("a")));
+}
+
+#[test]
+fn test_filter_non_matching_revision() {
+    let config = make_config("ui", Some("foo.rs#a"));
+    assert!(!is_filtered_in(&config, Some("b")));
+    // `foo.rs#ab` contains the filter, but names a different revision.
+    assert!(!is_filtered_in(&config, Some("ab")));
+}
+
+#[test]
+fn test_filter_revision_on_test_without_revisions() {
+    let config = make_config("ui", Some("foo.rs#a"));
+    assert!(!is_filtered_in(&config, None));
+
+    // Incremental tests run all their revisions at once, so only the path is matched.
+    let config = make_config("incremental", Some("foo.rs#a"));
+    assert_eq!(test_filter(&config), Some("foo.rs".to_owned()));
+    assert!(is_filtered_in(&config, None));
+}
+
+#[test]
+fn test_extract_error_codes() {
+    let output = concat!(
+        "not a diagnostic\n",
+        r#"{"message":"mismatched types","code":{"code":"E0308","explanation":null},"#,
+        r#""level":"error","spans":[],"children":[{"message":"nested","#,
+        r#""code":{"code":"E0277","explanation":null},"level":"note","spans":[],"#,
+        r#""children":[],"rendered":null}],"rendered":null}"#,
+        "\n",
+        r#"{"message":"unused variable","code":{"code":"unused_variables","#,
+        r#""explanation":null},"level":"warning","spans":[],"children":[],"rendered":null}"#,
+        "\n",
+        r#"{"message":"aborting due to previous error","code":null,"level":"error","#,
+        r#""spans":[],"children":[],"rendered":null}"#,
+        "\n",
+        "{ not json either\n",
+    );
+    assert_eq!(
+        json::extract_error_codes(output),
+        vec!["E0308".to_owned(), "E0277".to_owned(), "unused_variables".to_owned()],
+    );
+}
+
+#[test]
+fn test_untested_error_codes() {
+    let documented: Vec<String> =
+        vec!["E0001", "E0002", "E0554", "E0570", "E0729", "E0999"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+    let mut emitted = BTreeMap::new();
+    emitted.insert("E0001".to_owned(), vec!["[ui] ui/foo.rs".to_owned()].into_iter().collect());
+    emitted.insert("E0998".to_owned(), vec!["[ui] ui/bar.rs".to_owned()].into_iter().collect());
+
+    // The untestable codes are never reported.
+    assert_eq!(
+        error_codes::untested_error_codes(documented, &emitted),
+        vec!["E0002".to_owned(), "E0999".to_owned()],
+    );
+}
+
+#[test]
+fn test_documented_error_codes() {
+    let dir = env::temp_dir().join(format!("compiletest-error-codes-{}", std::process::id()));
+    fs::create_dir_all(dir.join("error_codes")).unwrap();
+    fs::write(
+        dir.join("error_codes.rs"),
+        concat!(
+            "register_diagnostics! {\n",
+            "E0001: include_str!(\"./error_codes/E0001.md\"),\n",
+            "E0002: include_str!(\"./error_codes/E0002.md\"),\n",
+            ";\n",
+            "//  E0003, // merged with E0001\n",
+            "    E0004, // no explanation\n",
+            "}\n",
+        ),
+    ).unwrap();
+    fs::write(dir.join("error_codes/E0001.md"), "An error.\n").unwrap();
+    fs::write(
+        dir.join("error_codes/E0002.md"),
+        "#### Note: this error code is no longer emitted by the compiler.\n",
+    ).unwrap();
+
+    let documented = error_codes::documented_error_codes(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(documented, vec!["E0001".to_owned()]);
+}